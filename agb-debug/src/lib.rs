@@ -1,9 +1,11 @@
 mod gwilym_encoding;
 mod load_dwarf;
+mod profile;
 
 use addr2line::gimli;
 pub use gwilym_encoding::{gwilym_decode, GwilymDecodeError};
 pub use load_dwarf::{load_dwarf, GimliDwarf, LoadDwarfError};
+pub use profile::{parse_profile, FunctionCost, Profile, ProfileParseError};
 use thiserror::Error;
 
 pub use addr2line;
@@ -2,38 +2,99 @@ use std::{
     borrow::Cow,
     error::Error,
     fs::{self, File},
-    io::Read,
-    path::PathBuf,
+    io::{BufWriter, Read},
+    path::{Path, PathBuf},
     time::SystemTime,
 };
 
 use agb_debug::{address_info, AddressInfo, Location};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The filename of the elf file
-    elf_path: PathBuf,
+    #[arg(required = true)]
+    elf_path: Option<PathBuf>,
 
     /// The output of agb's dump
-    dump: String,
+    #[arg(required = true)]
+    dump: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Shows where the time was spent in the output of agb's profiler
+    Profile {
+        /// The filename of the elf file
+        elf_path: PathBuf,
+
+        /// A file containing the mgba log with the profiler's output
+        profile: PathBuf,
+
+        /// Write the stacks in the folded format used by flamegraph tools
+        #[arg(long)]
+        folded: Option<PathBuf>,
+
+        /// Write a flamegraph svg
+        #[arg(long)]
+        flamegraph: Option<PathBuf>,
+
+        /// The number of functions to show
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Args::parse();
 
-    let modification_time = fs::metadata(&cli.elf_path)?
-        .modified()
-        .unwrap_or(SystemTime::UNIX_EPOCH);
+    match cli.command {
+        Some(Command::Profile {
+            elf_path,
+            profile,
+            folded,
+            flamegraph,
+            top,
+        }) => print_profile(
+            &elf_path,
+            &profile,
+            folded.as_deref(),
+            flamegraph.as_deref(),
+            top,
+        ),
+        None => print_dump(
+            &cli.elf_path.expect("elf path is required"),
+            &cli.dump.expect("dump is required"),
+        ),
+    }
+}
 
-    let file = fs::read(&cli.elf_path)?;
+fn load_context(elf_path: &Path) -> Result<agb_debug::Addr2LineContext, Box<dyn Error>> {
+    let file = fs::read(elf_path)?;
     let dwarf = agb_debug::load_dwarf(&file)?;
 
-    let ctx = addr2line::Context::from_dwarf(dwarf)?;
+    Ok(addr2line::Context::from_dwarf(dwarf)?)
+}
+
+fn print_dump(elf_path: &Path, dump: &str) -> Result<(), Box<dyn Error>> {
+    let modification_time = fs::metadata(elf_path)?
+        .modified()
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let ctx = load_context(elf_path)?;
 
-    for (i, address) in agb_debug::gwilym_decode(&cli.dump)?.enumerate() {
+    for (i, address) in agb_debug::gwilym_decode(dump)?.enumerate() {
         let infos = address_info(&ctx, address.into())?;
         for info in infos {
             print_address_info(&info, i, modification_time)?;
@@ -43,6 +104,63 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn print_profile(
+    elf_path: &Path,
+    profile_path: &Path,
+    folded_path: Option<&Path>,
+    flamegraph_path: Option<&Path>,
+    top: usize,
+) -> Result<(), Box<dyn Error>> {
+    let ctx = load_context(elf_path)?;
+    let profile = agb_debug::parse_profile(&fs::read_to_string(profile_path)?)?;
+
+    if profile.dropped > 0 {
+        eprintln!(
+            "Warning: {} samples were dropped because the profiler ran out of space",
+            profile.dropped
+        );
+    }
+
+    let folded = profile.folded_stacks(&ctx)?;
+
+    if let Some(folded_path) = folded_path {
+        fs::write(folded_path, folded.join("\n") + "\n")?;
+    }
+
+    if let Some(flamegraph_path) = flamegraph_path {
+        let mut options = inferno::flamegraph::Options::default();
+        options.title = "agb profile".to_string();
+        options.count_name = "samples".to_string();
+
+        let output = BufWriter::new(File::create(flamegraph_path)?);
+        inferno::flamegraph::from_lines(&mut options, folded.iter().map(String::as_str), output)?;
+    }
+
+    let total = profile.samples.max(1);
+    println!(
+        "{} samples, {} cycles",
+        profile.samples,
+        profile.cycles(profile.samples)
+    );
+    println!(
+        "{:>12} {:>7} {:>12} {:>7}  function",
+        "self", "", "total", ""
+    );
+
+    for cost in profile.function_costs(&ctx)?.iter().take(top) {
+        println!(
+            "{:>12} {:>6.2}% {:>12} {:>6.2}%  {}",
+            profile.cycles(cost.self_samples),
+            cost.self_samples as f64 * 100. / total as f64,
+            profile.cycles(cost.total_samples),
+            cost.total_samples as f64 * 100. / total as f64,
+            cost.function
+        );
+    }
+
+    Ok(())
+}
+
 fn print_address_info(
     info: &AddressInfo,
     index: usize,
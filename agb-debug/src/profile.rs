@@ -0,0 +1,296 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use thiserror::Error;
+
+use crate::{address_info, gwilym_decode, Addr2LineContext, AddressInfoError, GwilymDecodeError};
+
+const HEADER: &str = "agb-profile v1";
+const SAMPLE_PREFIX: &str = "profile: ";
+
+#[derive(Debug, Error)]
+pub enum ProfileParseError {
+    #[error("Could not find the start of a profile")]
+    NoHeader,
+    #[error("Invalid profile header {0}")]
+    InvalidHeader(String),
+    #[error("Invalid profile line {0}")]
+    InvalidLine(String),
+    #[error(transparent)]
+    Decode(#[from] GwilymDecodeError),
+}
+
+/// The samples printed by agb's profiler. Each stack starts with the address
+/// that was running followed by the addresses of its callers.
+pub struct Profile {
+    pub cycles_per_sample: u32,
+    pub samples: usize,
+    pub dropped: usize,
+    pub stacks: Vec<(Vec<u32>, usize)>,
+}
+
+pub struct FunctionCost {
+    pub function: String,
+    /// The number of samples where this function was running.
+    pub self_samples: usize,
+    /// The number of samples where this function was running or was one of the
+    /// callers.
+    pub total_samples: usize,
+}
+
+/// Reads the first profile in `input`, which can be the whole mgba log. Lines
+/// can have anything before the profile output, such as the log level.
+pub fn parse_profile(input: &str) -> Result<Profile, ProfileParseError> {
+    let mut lines = input.lines();
+
+    let header = lines
+        .by_ref()
+        .find_map(|line| line.find(HEADER).map(|start| &line[start + HEADER.len()..]))
+        .ok_or(ProfileParseError::NoHeader)?;
+
+    let mut cycles_per_sample = None;
+    let mut samples = None;
+    let mut dropped = None;
+
+    for field in header.split_whitespace() {
+        let invalid = || ProfileParseError::InvalidHeader(header.to_string());
+
+        let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+        match key {
+            "cycles_per_sample" => cycles_per_sample = Some(value.parse().map_err(|_| invalid())?),
+            "samples" => samples = Some(value.parse().map_err(|_| invalid())?),
+            "dropped" => dropped = Some(value.parse().map_err(|_| invalid())?),
+            _ => {}
+        }
+    }
+
+    let (Some(cycles_per_sample), Some(samples), Some(dropped)) =
+        (cycles_per_sample, samples, dropped)
+    else {
+        return Err(ProfileParseError::InvalidHeader(header.to_string()));
+    };
+
+    let mut stacks = Vec::new();
+    for line in lines {
+        if line.contains("agb-profile end") {
+            break;
+        }
+
+        let Some(start) = line.find(SAMPLE_PREFIX) else {
+            continue;
+        };
+
+        let sample = &line[start + SAMPLE_PREFIX.len()..];
+        let (count, frames) = sample
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| ProfileParseError::InvalidLine(line.to_string()))?;
+        let count = count
+            .parse()
+            .map_err(|_| ProfileParseError::InvalidLine(line.to_string()))?;
+
+        stacks.push((gwilym_decode(frames)?.collect(), count));
+    }
+
+    Ok(Profile {
+        cycles_per_sample,
+        samples,
+        dropped,
+        stacks,
+    })
+}
+
+impl Profile {
+    /// The stacks in the folded format used by flamegraph tools, with one line
+    /// per stack of the functions from the outermost caller inwards separated by
+    /// `;` followed by the number of samples. Inlined functions get their own
+    /// entries.
+    pub fn folded_stacks(&self, ctx: &Addr2LineContext) -> Result<Vec<String>, AddressInfoError> {
+        self.folded_stacks_with(|address| function_names(ctx, address))
+    }
+
+    /// Works out how much time was spent in each function, most expensive
+    /// first.
+    pub fn function_costs(
+        &self,
+        ctx: &Addr2LineContext,
+    ) -> Result<Vec<FunctionCost>, AddressInfoError> {
+        self.function_costs_with(|address| function_names(ctx, address))
+    }
+
+    /// The number of cycles that `samples` samples represent.
+    pub fn cycles(&self, samples: usize) -> u64 {
+        samples as u64 * u64::from(self.cycles_per_sample)
+    }
+
+    fn resolved_stacks<E>(
+        &self,
+        mut resolve: impl FnMut(u32) -> Result<Vec<String>, E>,
+    ) -> Result<Vec<(Vec<String>, usize)>, E> {
+        let mut cache = HashMap::new();
+
+        self.stacks
+            .iter()
+            .map(|(addresses, count)| {
+                let mut functions = Vec::new();
+                for &address in addresses.iter().rev() {
+                    let names = match cache.entry(address) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(resolve(address)?),
+                    };
+
+                    functions.extend(names.iter().cloned());
+                }
+
+                Ok((functions, *count))
+            })
+            .collect()
+    }
+
+    fn folded_stacks_with<E>(
+        &self,
+        resolve: impl FnMut(u32) -> Result<Vec<String>, E>,
+    ) -> Result<Vec<String>, E> {
+        Ok(self
+            .resolved_stacks(resolve)?
+            .into_iter()
+            .map(|(functions, count)| format!("{} {count}", functions.join(";")))
+            .collect())
+    }
+
+    fn function_costs_with<E>(
+        &self,
+        resolve: impl FnMut(u32) -> Result<Vec<String>, E>,
+    ) -> Result<Vec<FunctionCost>, E> {
+        let mut costs: HashMap<String, FunctionCost> = HashMap::new();
+
+        for (functions, count) in self.resolved_stacks(resolve)? {
+            let mut seen = Vec::new();
+
+            for (i, function) in functions.iter().enumerate() {
+                let cost = costs
+                    .entry(function.clone())
+                    .or_insert_with(|| FunctionCost {
+                        function: function.clone(),
+                        self_samples: 0,
+                        total_samples: 0,
+                    });
+
+                if i == functions.len() - 1 {
+                    cost.self_samples += count;
+                }
+
+                // recursive functions only count once per stack
+                if !seen.contains(&function) {
+                    cost.total_samples += count;
+                    seen.push(function);
+                }
+            }
+        }
+
+        let mut costs: Vec<_> = costs.into_values().collect();
+        costs.sort_by(|a, b| {
+            b.self_samples
+                .cmp(&a.self_samples)
+                .then(b.total_samples.cmp(&a.total_samples))
+                .then_with(|| a.function.cmp(&b.function))
+        });
+
+        Ok(costs)
+    }
+}
+
+/// The functions at `address`, outermost first, so the function containing it
+/// comes before anything inlined into it.
+fn function_names(ctx: &Addr2LineContext, address: u32) -> Result<Vec<String>, AddressInfoError> {
+    let infos = address_info(ctx, address.into())?;
+
+    if infos.is_empty() {
+        return Ok(vec![format!("{address:#010x}")]);
+    }
+
+    Ok(infos
+        .into_iter()
+        .rev()
+        // the folded format uses ; to separate functions, which can appear in
+        // array types
+        .map(|info| info.function.replace(';', ","))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    const LOG: &str = "\
+[INFO] GBA Debug: hello
+[INFO] GBA Debug: agb-profile v1 cycles_per_sample=1000 samples=6 dropped=2
+[INFO] GBA Debug: profile: 3 200400v1
+[INFO] GBA Debug: profile: 2 400v1
+[INFO] GBA Debug: profile: 1 200v1
+[INFO] GBA Debug: agb-profile end
+[INFO] GBA Debug: profile: 7 400v1
+";
+
+    fn resolve(address: u32) -> Result<Vec<String>, Infallible> {
+        Ok(match address {
+            0x0800_1000 => vec!["update".to_string(), "inlined".to_string()],
+            _ => vec!["main".to_string()],
+        })
+    }
+
+    #[test]
+    fn parses_profiles_from_logs() {
+        let profile = parse_profile(LOG).unwrap();
+
+        assert_eq!(profile.cycles_per_sample, 1000);
+        assert_eq!(profile.samples, 6);
+        assert_eq!(profile.dropped, 2);
+        assert_eq!(profile.stacks.len(), 3);
+        assert_eq!(profile.stacks[0].1, 3);
+        assert_eq!(profile.stacks[0].0, [0x0800_1000, 0x0800_2000]);
+        assert_eq!(profile.cycles(profile.samples), 6000);
+    }
+
+    #[test]
+    fn missing_header() {
+        assert!(matches!(
+            parse_profile("profile: 2 400v1"),
+            Err(ProfileParseError::NoHeader)
+        ));
+    }
+
+    #[test]
+    fn folds_and_costs_stacks() {
+        let profile = Profile {
+            cycles_per_sample: 1000,
+            samples: 6,
+            dropped: 0,
+            stacks: vec![
+                (vec![0x0800_1000, 0x0800_2000], 3),
+                (vec![0x0800_2000], 2),
+                (vec![0x0800_1000], 1),
+            ],
+        };
+
+        assert_eq!(
+            profile.folded_stacks_with(resolve).unwrap(),
+            ["main;update;inlined 3", "main 2", "update;inlined 1"]
+        );
+
+        let costs = profile.function_costs_with(resolve).unwrap();
+        let costs: Vec<_> = costs
+            .iter()
+            .map(|cost| {
+                (
+                    cost.function.as_str(),
+                    cost.self_samples,
+                    cost.total_samples,
+                )
+            })
+            .collect();
+
+        assert_eq!(costs, [("inlined", 4, 4), ("main", 2, 5), ("update", 0, 4)]);
+    }
+}
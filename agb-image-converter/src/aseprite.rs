@@ -1,9 +1,9 @@
 use std::path::Path;
 
-use asefile::{AsepriteFile, Tag};
+use asefile::{AsepriteFile, Slice, Tag};
 use image::DynamicImage;
 
-pub fn generate_from_file(filename: &Path) -> (Vec<DynamicImage>, Vec<Tag>) {
+pub fn generate_from_file(filename: &Path) -> (Vec<DynamicImage>, Vec<Tag>, Vec<Slice>) {
     let ase = AsepriteFile::read_file(filename).expect("Aseprite file should exist");
 
     let mut images = Vec::new();
@@ -19,5 +19,140 @@ pub fn generate_from_file(filename: &Path) -> (Vec<DynamicImage>, Vec<Tag>) {
         tags.push(ase.tag(tag).clone())
     }
 
-    (images, tags)
+    (images, tags, ase.slices().to_vec())
+}
+
+/// Cuts a frame up into the slices which are present in that frame, returning
+/// each slice's image along with where it is in the frame.
+pub fn slice_frame(
+    frame: &DynamicImage,
+    frame_index: u32,
+    slices: &[Slice],
+) -> Vec<(DynamicImage, i32, i32)> {
+    slices
+        .iter()
+        .filter_map(|slice| {
+            // a slice key lasts until the frame of the next one
+            let key = slice
+                .keys
+                .iter()
+                .rev()
+                .find(|key| key.from_frame <= frame_index)?;
+
+            let (x, y) = key.origin;
+            let (width, height) = key.size;
+
+            assert!(
+                x >= 0
+                    && y >= 0
+                    && x as u32 + width <= frame.width()
+                    && y as u32 + height <= frame.height(),
+                "Slice {} goes outside the frame",
+                slice.name
+            );
+
+            Some((frame.crop_imm(x as u32, y as u32, width, height), x, y))
+        })
+        .collect()
+}
+
+/// The index and position of each sprite in a metasprite.
+pub type MetaSpriteParts = Vec<(usize, i32, i32)>;
+
+/// Cuts every frame up into its slices, returning the image of each part along
+/// with a metasprite for each frame. Each part of a metasprite is the index its
+/// image will have in the sprites, counting from `first_index`, and its position.
+pub fn metasprites(
+    frames: &[DynamicImage],
+    slices: &[Slice],
+    first_index: usize,
+) -> (Vec<DynamicImage>, Vec<MetaSpriteParts>) {
+    let mut images = Vec::new();
+    let mut metasprites = Vec::new();
+
+    if slices.is_empty() {
+        return (images, metasprites);
+    }
+
+    for (frame_index, frame) in frames.iter().enumerate() {
+        let mut parts = Vec::new();
+
+        for (part, x, y) in slice_frame(frame, frame_index as u32, slices) {
+            parts.push((first_index + images.len(), x, y));
+            images.push(part);
+        }
+
+        metasprites.push(parts);
+    }
+
+    (images, metasprites)
+}
+
+#[cfg(test)]
+mod tests {
+    use asefile::SliceKey;
+    use image::RgbaImage;
+
+    use super::*;
+
+    fn slice(name: &str, keys: &[(u32, (i32, i32))]) -> Slice {
+        Slice {
+            name: name.to_string(),
+            keys: keys
+                .iter()
+                .map(|&(from_frame, origin)| SliceKey {
+                    from_frame,
+                    origin,
+                    size: (8, 8),
+                    slice9: None,
+                    pivot: None,
+                })
+                .collect(),
+            user_data: None,
+        }
+    }
+
+    #[test]
+    fn slices_follow_their_keys() {
+        let frame = DynamicImage::ImageRgba8(RgbaImage::new(16, 16));
+        let slices = [
+            slice("top", &[(0, (0, 0))]),
+            slice("moving", &[(0, (8, 0)), (2, (8, 8))]),
+            slice("late", &[(1, (0, 8))]),
+        ];
+
+        let positions = |frame_index| {
+            slice_frame(&frame, frame_index, &slices)
+                .into_iter()
+                .map(|(image, x, y)| (image.width(), x, y))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(positions(0), [(8, 0, 0), (8, 8, 0)]);
+        assert_eq!(positions(1), [(8, 0, 0), (8, 8, 0), (8, 0, 8)]);
+        assert_eq!(positions(2), [(8, 0, 0), (8, 8, 8), (8, 0, 8)]);
+    }
+
+    #[test]
+    fn metasprites_refer_to_their_parts() {
+        let frames = vec![DynamicImage::ImageRgba8(RgbaImage::new(16, 16)); 3];
+        let slices = [
+            slice("top", &[(0, (0, 0))]),
+            slice("moving", &[(0, (8, 0)), (2, (8, 8))]),
+        ];
+
+        let (images, parts) = metasprites(&frames, &slices, 3);
+
+        assert_eq!(images.len(), 6);
+        assert_eq!(
+            parts,
+            [
+                vec![(3, 0, 0), (4, 8, 0)],
+                vec![(5, 0, 0), (6, 8, 0)],
+                vec![(7, 0, 0), (8, 8, 8)],
+            ]
+        );
+
+        assert!(metasprites(&frames, &[], 3).1.is_empty());
+    }
 }
@@ -25,6 +25,8 @@ impl Colour {
         Colour { r, g, b, a }
     }
 
+    /// Converts to the 15 bit colour the GBA uses, rounding the same way as
+    /// `agb::display::Rgb15::from_rgb`.
     pub fn to_rgb15(self) -> u16 {
         let (r, g, b) = (self.r as u16, self.g as u16, self.b as u16);
         ((r >> 3) & 31) | (((g >> 3) & 31) << 5) | (((b >> 3) & 31) << 10)
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::{Colour, Colours};
+use crate::{Colour, Colours, TileOutput};
 
 pub(crate) trait Config {
     fn crate_prefix(&self) -> String;
@@ -11,5 +11,5 @@ pub(crate) trait Config {
 pub(crate) trait Image {
     fn filename(&self) -> String;
     fn colours(&self) -> Colours;
-    fn deduplicate(&self) -> bool;
+    fn output(&self) -> TileOutput;
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use crate::ByteString;
 use quote::quote;
 
@@ -19,7 +21,11 @@ struct LetterData {
     kerning_data: Vec<KerningData>,
 }
 
-pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
+pub fn load_font(
+    font_data: &[u8],
+    pixels_per_em: f32,
+    characters: Option<&BTreeSet<char>>,
+) -> TokenStream {
     let font = fontdue::Font::from_bytes(
         font_data,
         fontdue::FontSettings {
@@ -34,9 +40,22 @@ pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
     let line_height = line_metrics.new_line_size as i32;
     let mut ascent = line_metrics.ascent as i32;
 
+    if let Some(characters) = characters {
+        let missing: String = characters
+            .iter()
+            .filter(|c| !c.is_control() && font.lookup_glyph_index(**c) == 0)
+            .collect();
+
+        assert!(
+            missing.is_empty(),
+            "Font is missing glyphs for the following characters: {missing:?}"
+        );
+    }
+
     let mut letters: Vec<_> = font
         .chars()
         .iter()
+        .filter(|(c, _)| characters.is_none_or(|characters| characters.contains(c)))
         .map(|(&c, &index)| (c, index, font.rasterize(c, pixels_per_em)))
         .map(|(c, index, (metrics, bitmap))| {
             let width = metrics.width;
@@ -59,6 +78,9 @@ pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
             let mut kerning_data: Vec<_> = font
                 .chars()
                 .iter()
+                .filter(|(left_char, _)| {
+                    characters.is_none_or(|characters| characters.contains(left_char))
+                })
                 .filter_map(|(&left_char, &left_index)| {
                     let kerning = font.horizontal_kern_indexed(
                         left_index.into(),
@@ -0,0 +1,881 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde_json::Value;
+
+use crate::{
+    palette16::Palette16OptimisationResults,
+    tiled_loader::{
+        json_array, json_usize, parse_colour, properties_code, read_file, relative_to,
+        tile_properties_code, Property, PropertyValue, TileInfo, Tileset,
+    },
+};
+
+const RESERVED_NAMES: &[&str] = &["PALETTES", "TILES", "TILE_PROPERTIES", "LEVELS"];
+
+/// The name LDtk gives to the world in projects which don't use multiple worlds
+const DEFAULT_WORLD: &str = "World";
+
+/// A project loaded from an LDtk file. Only the tilesets which are actually used by a tile
+/// layer are loaded, and they must use 8x8 tiles.
+pub(crate) struct LdtkProject {
+    pub tilesets: Vec<Tileset>,
+    pub levels: Vec<Level>,
+    /// Every file which was read to load this project
+    pub files: Vec<PathBuf>,
+}
+
+pub(crate) struct Level {
+    pub identifier: String,
+    pub iid: String,
+    pub world: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize,
+    /// In the order LDtk stores them, so the top-most layer is first
+    pub layers: Vec<LevelLayer>,
+    pub neighbours: Vec<Neighbour>,
+    pub properties: Vec<Property>,
+}
+
+pub(crate) enum LevelLayer {
+    Tiles(TileLayer),
+    IntGrid(IntGridLayer),
+    Entities(EntityLayer),
+}
+
+pub(crate) struct TileLayer {
+    pub name: String,
+    pub tileset_uid: i64,
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Option<LevelTile>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LevelTile {
+    /// Before the project finishes loading, this is the id within the layer's tileset. After,
+    /// it is the index in the combination of every loaded tileset.
+    pub id: usize,
+    pub hflip: bool,
+    pub vflip: bool,
+}
+
+pub(crate) struct IntGridLayer {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub grid_size: usize,
+    pub values: Vec<u8>,
+    pub value_names: Vec<(u8, String)>,
+}
+
+pub(crate) struct EntityLayer {
+    pub name: String,
+    pub entities: Vec<Entity>,
+}
+
+pub(crate) struct Entity {
+    pub iid: String,
+    pub identifier: String,
+    /// The top left corner of the entity, regardless of its pivot
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub properties: Vec<Property>,
+}
+
+pub(crate) struct Neighbour {
+    pub level_iid: String,
+    /// The name of the matching `NeighbourDirection` variant
+    pub direction: &'static str,
+}
+
+impl LdtkProject {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = load_json(path)?;
+        Self::from_json(&json, path)
+    }
+
+    fn from_json(json: &Value, path: &Path) -> Result<Self, String> {
+        let mut files = vec![path.to_owned()];
+        let int_grid_values = int_grid_values(&json["defs"])?;
+
+        let mut levels = vec![];
+        match json["worlds"]
+            .as_array()
+            .filter(|worlds| !worlds.is_empty())
+        {
+            Some(worlds) => {
+                for world in worlds {
+                    let world_name = json_str(world, "identifier")?;
+                    for level in json_array(world, "levels")? {
+                        levels.push(Level::load(
+                            level,
+                            world_name,
+                            path,
+                            &int_grid_values,
+                            &mut files,
+                        )?);
+                    }
+                }
+            }
+            None => {
+                for level in json_array(json, "levels")? {
+                    levels.push(Level::load(
+                        level,
+                        DEFAULT_WORLD,
+                        path,
+                        &int_grid_values,
+                        &mut files,
+                    )?);
+                }
+            }
+        }
+
+        let used_tilesets: Vec<_> = levels
+            .iter()
+            .flat_map(|level| &level.layers)
+            .filter_map(|layer| match layer {
+                LevelLayer::Tiles(layer) => Some(layer.tileset_uid),
+                _ => None,
+            })
+            .collect();
+
+        let mut tilesets = vec![];
+        let mut offsets = HashMap::new();
+        for tileset in json_array(&json["defs"], "tilesets")? {
+            let uid = json_i64(tileset, "uid")?;
+            if !used_tilesets.contains(&uid) {
+                continue;
+            }
+
+            let tileset = load_tileset(tileset, path)?;
+            let offset = tilesets
+                .iter()
+                .map(|t: &Tileset| t.tile_count)
+                .sum::<usize>();
+            offsets.insert(uid, (offset, tileset.tile_count));
+            tilesets.push(tileset);
+        }
+
+        for layer in levels.iter_mut().flat_map(|level| &mut level.layers) {
+            let LevelLayer::Tiles(layer) = layer else {
+                continue;
+            };
+
+            let (offset, tile_count) = *offsets
+                .get(&layer.tileset_uid)
+                .ok_or_else(|| format!("Layer {} uses an unknown tileset", layer.name))?;
+
+            for tile in layer.tiles.iter_mut().flatten() {
+                if tile.id >= tile_count {
+                    return Err(format!(
+                        "Layer {} uses a tile outside its tileset",
+                        layer.name
+                    ));
+                }
+
+                tile.id += offset;
+            }
+        }
+
+        let project = LdtkProject {
+            tilesets,
+            levels,
+            files,
+        };
+        project.validate()?;
+
+        Ok(project)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut names = HashMap::new();
+        for level in &self.levels {
+            let name = crate::constant_name(&level.identifier);
+            if RESERVED_NAMES.contains(&name.as_str()) {
+                return Err(format!(
+                    "Level {} would be called {name}, which is reserved",
+                    level.identifier
+                ));
+            }
+
+            if let Some(other) = names.insert(name.clone(), &level.identifier) {
+                return Err(format!(
+                    "Levels {other} and {} would both be called {name}",
+                    level.identifier
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Level {
+    fn load(
+        json: &Value,
+        world: &str,
+        path: &Path,
+        int_grid_values: &HashMap<i64, Vec<(u8, String)>>,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Self, String> {
+        let identifier = json_str(json, "identifier")?;
+
+        // levels can be saved in separate files, in which case the layers are only in there
+        let external;
+        let layers_json = match json["externalRelPath"].as_str() {
+            Some(relative) => {
+                let level_path = relative_to(path, relative);
+                external = load_json(&level_path)?;
+                files.push(level_path);
+                &external
+            }
+            None => json,
+        };
+
+        let mut layers = vec![];
+        for layer in json_array(layers_json, "layerInstances")? {
+            load_layer(layer, int_grid_values, &mut layers)
+                .map_err(|e| format!("Level {identifier}: {e}"))?;
+        }
+
+        let neighbours = json_array(json, "__neighbours")?
+            .iter()
+            .map(|neighbour| {
+                Ok(Neighbour {
+                    level_iid: json_str(neighbour, "levelIid")?.to_string(),
+                    direction: neighbour_direction(json_str(neighbour, "dir")?)?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Level {
+            identifier: identifier.to_string(),
+            iid: json_str(json, "iid")?.to_string(),
+            world: world.to_string(),
+            x: json_i64(json, "worldX")? as i32,
+            y: json_i64(json, "worldY")? as i32,
+            width: json_usize(json, "pxWid")?,
+            height: json_usize(json, "pxHei")?,
+            layers,
+            neighbours,
+            properties: fields(json)?,
+        })
+    }
+}
+
+fn load_layer(
+    json: &Value,
+    int_grid_values: &HashMap<i64, Vec<(u8, String)>>,
+    layers: &mut Vec<LevelLayer>,
+) -> Result<(), String> {
+    let name = json_str(json, "__identifier")?;
+    let kind = json_str(json, "__type")?;
+    let width = json_usize(json, "__cWid")?;
+    let height = json_usize(json, "__cHei")?;
+    let grid_size = json_usize(json, "__gridSize")?;
+
+    if kind == "Entities" {
+        let entities = json_array(json, "entityInstances")?
+            .iter()
+            .map(entity)
+            .collect::<Result<_, String>>()?;
+
+        layers.push(LevelLayer::Entities(EntityLayer {
+            name: name.to_string(),
+            entities,
+        }));
+
+        return Ok(());
+    }
+
+    // tile layers come first so that they end up in the same order in the level, and IntGrid
+    // layers can have auto-layer tiles as well as their values
+    if let Some(tileset_uid) = json["__tilesetDefUid"].as_i64() {
+        if grid_size != 8 {
+            return Err(format!(
+                "Layer {name} must use an 8x8 grid, got {grid_size}x{grid_size}"
+            ));
+        }
+
+        let tiles_field = if kind == "Tiles" {
+            "gridTiles"
+        } else {
+            "autoLayerTiles"
+        };
+
+        let mut tiles = vec![None; width * height];
+        // tiles are in the order they are drawn, so later ones end up on top
+        for tile in json_array(json, tiles_field)? {
+            let (x, y) = json_point(tile, "px")?;
+            if x % 8 != 0 || y % 8 != 0 {
+                return Err(format!("Layer {name} has a tile which isn't on the grid"));
+            }
+
+            let (x, y) = (x as usize / 8, y as usize / 8);
+            if x >= width || y >= height {
+                return Err(format!("Layer {name} has a tile outside the level"));
+            }
+
+            let flip = json_i64(tile, "f")?;
+            tiles[x + y * width] = Some(LevelTile {
+                id: json_usize(tile, "t")?,
+                hflip: flip & 1 != 0,
+                vflip: flip & 2 != 0,
+            });
+        }
+
+        layers.push(LevelLayer::Tiles(TileLayer {
+            name: name.to_string(),
+            tileset_uid,
+            width,
+            height,
+            tiles,
+        }));
+    }
+
+    if kind == "IntGrid" {
+        let values = json_array(json, "intGridCsv")?
+            .iter()
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|value| u8::try_from(value).ok())
+                    .ok_or_else(|| format!("Layer {name} has IntGrid values which aren't 0-255"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.len() != width * height {
+            return Err(format!(
+                "Layer {name} has the wrong number of IntGrid values"
+            ));
+        }
+
+        let value_names = int_grid_values
+            .get(&json_i64(json, "layerDefUid")?)
+            .cloned()
+            .unwrap_or_default();
+
+        layers.push(LevelLayer::IntGrid(IntGridLayer {
+            name: name.to_string(),
+            width,
+            height,
+            grid_size,
+            values,
+            value_names,
+        }));
+    }
+
+    Ok(())
+}
+
+fn entity(json: &Value) -> Result<Entity, String> {
+    let (x, y) = json_point(json, "px")?;
+    let width = json_i64(json, "width")?;
+    let height = json_i64(json, "height")?;
+    let pivot = json_array(json, "__pivot")?;
+    let pivot = |index: usize| pivot.get(index).and_then(Value::as_f64).unwrap_or(0.0);
+
+    Ok(Entity {
+        iid: json_str(json, "iid")?.to_string(),
+        identifier: json_str(json, "__identifier")?.to_string(),
+        x: (x as f64 - pivot(0) * width as f64).round() as i32,
+        y: (y as f64 - pivot(1) * height as f64).round() as i32,
+        width: width as i32,
+        height: height as i32,
+        properties: fields(json)?,
+    })
+}
+
+/// Converts the custom fields of a level or entity into properties. Fields without a value
+/// are left out.
+fn fields(json: &Value) -> Result<Vec<Property>, String> {
+    let mut properties = vec![];
+
+    for field in json_array(json, "fieldInstances")? {
+        let name = json_str(field, "__identifier")?;
+        let kind = json_str(field, "__type")?;
+
+        if let Some(value) =
+            field_value(kind, &field["__value"]).map_err(|e| format!("Field {name}: {e}"))?
+        {
+            properties.push(Property {
+                name: name.to_string(),
+                value,
+            });
+        }
+    }
+
+    Ok(properties)
+}
+
+fn field_value(kind: &str, value: &Value) -> Result<Option<PropertyValue>, String> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    if let Some(kind) = kind
+        .strip_prefix("Array<")
+        .and_then(|kind| kind.strip_suffix('>'))
+    {
+        let mut members = vec![];
+        for (i, value) in value.as_array().into_iter().flatten().enumerate() {
+            if let Some(value) = field_value(kind, value)? {
+                members.push(Property {
+                    name: i.to_string(),
+                    value,
+                });
+            }
+        }
+
+        return Ok(Some(PropertyValue::Class(members)));
+    }
+
+    let invalid = || format!("Invalid value for type {kind}");
+    let string = || value.as_str().map(str::to_string).ok_or_else(invalid);
+
+    Ok(Some(match kind {
+        "Int" => PropertyValue::Int(value.as_i64().ok_or_else(invalid)? as i32),
+        "Float" => PropertyValue::Float(value.as_f64().ok_or_else(invalid)?),
+        "Bool" => PropertyValue::Bool(value.as_bool().ok_or_else(invalid)?),
+        "String" | "Multilines" | "FilePath" => PropertyValue::String(string()?),
+        "Color" => PropertyValue::Colour(parse_colour(&string()?)?),
+        "Point" => PropertyValue::Class(vec![
+            Property {
+                name: "cx".to_string(),
+                value: PropertyValue::Int(json_i64(value, "cx")? as i32),
+            },
+            Property {
+                name: "cy".to_string(),
+                value: PropertyValue::Int(json_i64(value, "cy")? as i32),
+            },
+        ]),
+        "EntityRef" => PropertyValue::String(json_str(value, "entityIid")?.to_string()),
+        // tile fields are mainly for the editor so there isn't anything useful to do with them
+        "Tile" => return Ok(None),
+        kind if kind.starts_with("LocalEnum.") || kind.starts_with("ExternalEnum.") => {
+            PropertyValue::String(string()?)
+        }
+        kind => return Err(format!("Unsupported field type {kind}")),
+    }))
+}
+
+fn load_tileset(json: &Value, path: &Path) -> Result<Tileset, String> {
+    let name = json_str(json, "identifier")?;
+    let image = json["relPath"]
+        .as_str()
+        .ok_or_else(|| format!("Tileset {name} doesn't have an image"))?;
+
+    let grid_size = json_usize(json, "tileGridSize")?;
+    if grid_size != 8 {
+        return Err(format!(
+            "Tileset {name} must use 8x8 tiles, got {grid_size}x{grid_size}"
+        ));
+    }
+
+    let mut tiles = BTreeMap::new();
+
+    // enum tags become flags, with the first one also used as the class of the tile
+    for tag in json_array(json, "enumTags")? {
+        let value = json_str(tag, "enumValueId")?;
+        for id in json_array(tag, "tileIds")? {
+            let id = id.as_u64().ok_or("Invalid tile id in enum tags")? as u32;
+
+            let tile = tile_info(&mut tiles, id);
+            if tile.class.is_empty() {
+                tile.class = value.to_string();
+            }
+
+            tile.properties.push(Property {
+                name: value.to_string(),
+                value: PropertyValue::Bool(true),
+            });
+        }
+    }
+
+    for data in json_array(json, "customData")? {
+        let id = json_usize(data, "tileId")? as u32;
+        tile_info(&mut tiles, id).properties.push(Property {
+            name: "data".to_string(),
+            value: PropertyValue::String(json_str(data, "data")?.to_string()),
+        });
+    }
+
+    let columns = json_usize(json, "__cWid")?;
+
+    Ok(Tileset {
+        first_gid: 0,
+        image: relative_to(path, image),
+        transparent_colour: None,
+        columns,
+        tile_count: columns * json_usize(json, "__cHei")?,
+        margin: json_usize(json, "padding")?,
+        spacing: json_usize(json, "spacing")?,
+        tiles: tiles.into_values().collect(),
+    })
+}
+
+fn tile_info(tiles: &mut BTreeMap<u32, TileInfo>, id: u32) -> &mut TileInfo {
+    tiles.entry(id).or_insert_with(|| TileInfo {
+        id,
+        class: String::new(),
+        properties: vec![],
+    })
+}
+
+/// The names of the values of every IntGrid layer, by the uid of the layer
+fn int_grid_values(defs: &Value) -> Result<HashMap<i64, Vec<(u8, String)>>, String> {
+    let mut values = HashMap::new();
+
+    for layer in json_array(defs, "layers")? {
+        let names = json_array(layer, "intGridValues")?
+            .iter()
+            .map(|value| {
+                let id = json_i64(value, "value")?;
+                let id = u8::try_from(id).map_err(|_| format!("IntGrid value {id} isn't 0-255"))?;
+                Ok((id, value["identifier"].as_str().unwrap_or("").to_string()))
+            })
+            .collect::<Result<_, String>>()?;
+
+        values.insert(json_i64(layer, "uid")?, names);
+    }
+
+    Ok(values)
+}
+
+fn neighbour_direction(direction: &str) -> Result<&'static str, String> {
+    Ok(match direction {
+        "n" => "North",
+        "s" => "South",
+        "e" => "East",
+        "w" => "West",
+        "ne" => "NorthEast",
+        "nw" => "NorthWest",
+        "se" => "SouthEast",
+        "sw" => "SouthWest",
+        ">" => "Above",
+        "<" => "Below",
+        "o" => "Overlap",
+        direction => return Err(format!("Unknown neighbour direction {direction}")),
+    })
+}
+
+/// Generates a `MapLevel` static for every level along with `LEVELS`. Expects `TILES` to be a
+/// `TileData` containing every tile of every tileset in order, with palettes assigned by
+/// `results`.
+pub(crate) fn generate_code(
+    project: &LdtkProject,
+    results: &Palette16OptimisationResults,
+    crate_prefix: &str,
+) -> TokenStream {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+    let tiled = quote!(#crate_prefix::display::tiled);
+    let vector = |x, y| quote!(#crate_prefix::fixnum::Vector2D::new(#x, #y));
+
+    let tile_properties = tile_properties_code(&project.tilesets, &crate_prefix);
+
+    let idents: Vec<_> = project
+        .levels
+        .iter()
+        .map(|level| format_ident!("{}", crate::constant_name(&level.identifier)))
+        .collect();
+
+    let levels = project.levels.iter().zip(&idents).map(|(level, ident)| {
+        let mut layers = vec![];
+        let mut int_grids = vec![];
+        let mut entity_layers = vec![];
+
+        for layer in &level.layers {
+            match layer {
+                LevelLayer::Tiles(layer) => {
+                    let name = &layer.name;
+                    let (width, height) = (layer.width, layer.height);
+                    let tiles = layer.tiles.iter().map(|tile| match tile {
+                        Some(tile) => {
+                            let palette = results.assignments[tile.id] as u8;
+                            let index = tile.id as u16;
+                            let (hflip, vflip) = (tile.hflip, tile.vflip);
+
+                            quote!(#tiled::TileSetting::new(#index, #hflip, #vflip, #palette))
+                        }
+                        None => quote!(#tiled::TileSetting::BLANK),
+                    });
+
+                    layers.push(quote! {
+                        #tiled::MapLayer::new(
+                            #name,
+                            &TILES.tiles,
+                            #width,
+                            #height,
+                            &[#(#tiles),*],
+                            TILE_PROPERTIES,
+                            #tiled::Properties::EMPTY,
+                        )
+                    });
+                }
+                LevelLayer::IntGrid(layer) => {
+                    let name = &layer.name;
+                    let (width, height, grid_size) = (layer.width, layer.height, layer.grid_size);
+                    let values = &layer.values;
+                    let value_names = layer
+                        .value_names
+                        .iter()
+                        .map(|(value, name)| quote!((#value, #name)));
+
+                    int_grids.push(quote! {
+                        #tiled::MapIntGridLayer::new(
+                            #name,
+                            #width,
+                            #height,
+                            #grid_size,
+                            &[#(#values),*],
+                            &[#(#value_names),*],
+                        )
+                    });
+                }
+                LevelLayer::Entities(layer) => {
+                    let name = &layer.name;
+                    let entities = layer.entities.iter().enumerate().map(|(id, entity)| {
+                        let id = id as u32;
+                        let iid = &entity.iid;
+                        let identifier = &entity.identifier;
+                        let position = vector(entity.x, entity.y);
+                        let size = vector(entity.width, entity.height);
+                        let properties = properties_code(&entity.properties, &crate_prefix);
+
+                        quote! {
+                            #tiled::MapObject::new(
+                                #id,
+                                #iid,
+                                #identifier,
+                                #position,
+                                #size,
+                                None,
+                                #properties,
+                            )
+                        }
+                    });
+
+                    entity_layers.push(quote! {
+                        #tiled::MapObjectLayer::new(
+                            #name,
+                            &[#(#entities),*],
+                            #tiled::Properties::EMPTY,
+                        )
+                    });
+                }
+            }
+        }
+
+        // neighbours in other worlds can't be reached, so they're left out
+        let neighbours = level.neighbours.iter().filter_map(|neighbour| {
+            let index = project
+                .levels
+                .iter()
+                .position(|level| level.iid == neighbour.level_iid)?;
+            let target = &idents[index];
+            let direction = format_ident!("{}", neighbour.direction);
+
+            Some(quote! {
+                #tiled::LevelNeighbour::new(#tiled::NeighbourDirection::#direction, &#target)
+            })
+        });
+
+        let identifier = &level.identifier;
+        let iid = &level.iid;
+        let world = &level.world;
+        let position = vector(level.x, level.y);
+        let size = vector(level.width as i32, level.height as i32);
+        let properties = properties_code(&level.properties, &crate_prefix);
+        let doc = format!("The level `{identifier}`");
+
+        quote! {
+            #[doc = #doc]
+            pub static #ident: #tiled::MapLevel = #tiled::MapLevel::new(
+                #identifier,
+                #iid,
+                #world,
+                #position,
+                #size,
+                &[#(#layers),*],
+                &[#(#int_grids),*],
+                &[#(#entity_layers),*],
+                &[#(#neighbours),*],
+                #properties,
+            );
+        }
+    });
+    let levels: Vec<_> = levels.collect();
+
+    quote! {
+        static TILE_PROPERTIES: &[#tiled::TileProperties] = #tile_properties;
+
+        #(#levels)*
+
+        /// Every level in the order they appear in the project
+        pub static LEVELS: &[&#tiled::MapLevel] = &[#(&#idents),*];
+    }
+}
+
+fn load_json(path: &Path) -> Result<Value, String> {
+    serde_json::from_str(&read_file(path)?).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn json_str<'a>(json: &'a Value, name: &str) -> Result<&'a str, String> {
+    json[name]
+        .as_str()
+        .ok_or_else(|| format!("Missing or invalid field `{name}`"))
+}
+
+fn json_i64(json: &Value, name: &str) -> Result<i64, String> {
+    json[name]
+        .as_i64()
+        .ok_or_else(|| format!("Missing or invalid field `{name}`"))
+}
+
+fn json_point(json: &Value, name: &str) -> Result<(i64, i64), String> {
+    match json_array(json, name)? {
+        [x, y] => x
+            .as_i64()
+            .zip(y.as_i64())
+            .ok_or_else(|| format!("Field `{name}` must be a pair of integers")),
+        _ => Err(format!("Field `{name}` must be a pair of integers")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r##"{
+        "defs": {
+            "layers": [
+                { "uid": 1, "intGridValues": [{ "value": 1, "identifier": "wall" }] }
+            ],
+            "tilesets": [
+                {
+                    "uid": 10, "identifier": "Unused", "relPath": "big.png", "tileGridSize": 16,
+                    "__cWid": 2, "__cHei": 2, "padding": 0, "spacing": 0,
+                    "enumTags": [], "customData": []
+                },
+                {
+                    "uid": 11, "identifier": "Tiles", "relPath": "tiles.png", "tileGridSize": 8,
+                    "__cWid": 2, "__cHei": 2, "padding": 0, "spacing": 0,
+                    "enumTags": [{ "enumValueId": "Solid", "tileIds": [1] }],
+                    "customData": [{ "tileId": 1, "data": "wall" }]
+                }
+            ]
+        },
+        "levels": [
+            {
+                "identifier": "Level_0", "iid": "a", "worldX": 0, "worldY": 0,
+                "pxWid": 16, "pxHei": 8,
+                "__neighbours": [{ "levelIid": "b", "dir": "e" }],
+                "fieldInstances": [
+                    { "__identifier": "music", "__type": "String", "__value": "town" },
+                    { "__identifier": "unset", "__type": "Int", "__value": null }
+                ],
+                "layerInstances": [
+                    {
+                        "__identifier": "Entities", "__type": "Entities",
+                        "__cWid": 2, "__cHei": 1, "__gridSize": 8,
+                        "entityInstances": [{
+                            "__identifier": "Player", "iid": "p", "px": [8, 8],
+                            "width": 8, "height": 16, "__pivot": [0.5, 1],
+                            "fieldInstances": [
+                                { "__identifier": "target", "__type": "Point", "__value": { "cx": 1, "cy": 0 } },
+                                { "__identifier": "items", "__type": "Array<LocalEnum.Item>", "__value": ["Key"] }
+                            ]
+                        }]
+                    },
+                    {
+                        "__identifier": "Collision", "__type": "IntGrid", "layerDefUid": 1,
+                        "__cWid": 2, "__cHei": 1, "__gridSize": 8, "__tilesetDefUid": 11,
+                        "intGridCsv": [1, 0],
+                        "autoLayerTiles": [
+                            { "px": [0, 0], "t": 0, "f": 0 },
+                            { "px": [0, 0], "t": 1, "f": 3 }
+                        ]
+                    }
+                ]
+            },
+            {
+                "identifier": "Level_1", "iid": "b", "worldX": 16, "worldY": 0,
+                "pxWid": 16, "pxHei": 8, "__neighbours": [], "fieldInstances": [],
+                "layerInstances": []
+            }
+        ]
+    }"##;
+
+    #[test]
+    fn loads_project() {
+        let json: Value = serde_json::from_str(PROJECT).unwrap();
+        let project = LdtkProject::from_json(&json, Path::new("maps/world.ldtk")).unwrap();
+
+        assert_eq!(project.tilesets.len(), 1);
+        assert_eq!(project.tilesets[0].image, Path::new("maps/tiles.png"));
+        assert_eq!(project.tilesets[0].tiles[0].class, "Solid");
+        assert_eq!(project.tilesets[0].tiles[0].properties.len(), 2);
+
+        let level = &project.levels[0];
+        assert_eq!(level.world, "World");
+        assert_eq!(level.neighbours[0].direction, "East");
+        assert_eq!(
+            level.properties,
+            [Property {
+                name: "music".to_string(),
+                value: PropertyValue::String("town".to_string())
+            }]
+        );
+
+        let LevelLayer::Entities(entities) = &level.layers[0] else {
+            panic!("expected an entity layer");
+        };
+        let player = &entities.entities[0];
+        assert_eq!((player.x, player.y), (4, -8));
+        assert_eq!(
+            player.properties[1].value,
+            PropertyValue::Class(vec![Property {
+                name: "0".to_string(),
+                value: PropertyValue::String("Key".to_string())
+            }])
+        );
+
+        let LevelLayer::Tiles(tiles) = &level.layers[1] else {
+            panic!("expected a tile layer");
+        };
+        assert_eq!(
+            tiles.tiles,
+            [
+                Some(LevelTile {
+                    id: 1,
+                    hflip: true,
+                    vflip: true
+                }),
+                None
+            ]
+        );
+
+        let LevelLayer::IntGrid(collision) = &level.layers[2] else {
+            panic!("expected an IntGrid layer");
+        };
+        assert_eq!(collision.values, [1, 0]);
+        assert_eq!(collision.value_names, [(1, "wall".to_string())]);
+    }
+
+    #[test]
+    fn rejects_clashing_level_names() {
+        let mut json: Value = serde_json::from_str(PROJECT).unwrap();
+        json["levels"][1]["identifier"] = "level_0".into();
+
+        assert!(LdtkProject::from_json(&json, Path::new("world.ldtk")).is_err());
+    }
+}
@@ -1,12 +1,12 @@
 use palette16::{Palette16OptimisationResults, Palette16Optimiser};
 use palette256::Palette256;
 use proc_macro::TokenStream;
-use proc_macro2::Literal;
+use proc_macro2::{Literal, Span};
 use syn::parse::{Parse, Parser};
 use syn::{parse_macro_input, punctuated::Punctuated, LitStr};
 use syn::{Expr, ExprLit, Lit, Token};
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
 use std::{iter, path::Path, str};
 
@@ -18,9 +18,12 @@ mod config;
 mod deduplicator;
 mod font_loader;
 mod image_loader;
+mod ldtk_loader;
 mod palette16;
 mod palette256;
 mod rust_generator;
+mod strings_loader;
+mod tiled_loader;
 
 use image_loader::Image;
 
@@ -32,11 +35,22 @@ pub(crate) enum Colours {
     Colours256,
 }
 
+/// What gets generated for each image in `include_background_gfx!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TileOutput {
+    /// Every tile of the image in order
+    Tiles,
+    /// Only the unique tiles, with a tile setting for every tile of the image
+    Deduplicated,
+    /// Like `Deduplicated`, but as a `TileMap` which also knows the size of the image
+    Map,
+}
+
 struct BackgroundGfxOption {
     module_name: String,
     file_name: String,
     colours: Colours,
-    deduplicate: bool,
+    output: TileOutput,
 }
 
 impl config::Image for BackgroundGfxOption {
@@ -50,8 +64,8 @@ impl config::Image for BackgroundGfxOption {
         self.colours
     }
 
-    fn deduplicate(&self) -> bool {
-        self.deduplicate
+    fn output(&self) -> TileOutput {
+        self.output
     }
 }
 
@@ -81,19 +95,21 @@ impl Parse for BackgroundGfxOption {
 
         let lookahead = input.lookahead1();
 
-        let deduplicate = if lookahead.peek(syn::Ident) {
-            let deduplicate: syn::Ident = input.parse()?;
+        let output = if lookahead.peek(syn::Ident) {
+            let output: syn::Ident = input.parse()?;
 
-            if deduplicate == "deduplicate" {
-                true
+            if output == "deduplicate" {
+                TileOutput::Deduplicated
+            } else if output == "map" {
+                TileOutput::Map
             } else {
                 return Err(syn::Error::new_spanned(
-                    deduplicate,
-                    "Must either be the literal deduplicate or missing",
+                    output,
+                    "Must either be the literal deduplicate, map or missing",
                 ));
             }
         } else {
-            false
+            TileOutput::Tiles
         };
 
         let file_name: syn::LitStr = input.parse()?;
@@ -102,7 +118,7 @@ impl Parse for BackgroundGfxOption {
             module_name: module_name.to_string(),
             file_name: file_name.value(),
             colours,
-            deduplicate,
+            output,
         })
     }
 }
@@ -299,10 +315,29 @@ impl ToTokens for ByteString<'_> {
     }
 }
 
+struct IncludeColoursInput {
+    colour_type: syn::Path,
+    filename: LitStr,
+}
+
+impl Parse for IncludeColoursInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let colour_type = input.parse()?;
+        let _: Token![,] = input.parse()?;
+        let filename = input.parse()?;
+
+        Ok(Self {
+            colour_type,
+            filename,
+        })
+    }
+}
+
 #[proc_macro]
 pub fn include_colours_inner(input: TokenStream) -> TokenStream {
-    let input_filename = parse_macro_input!(input as LitStr);
-    let input_filename = input_filename.value();
+    let input = parse_macro_input!(input as IncludeColoursInput);
+    let colour_type = input.colour_type;
+    let input_filename = input.filename.value();
 
     let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
     let input_filename = Path::new(&root).join(input_filename);
@@ -321,17 +356,48 @@ pub fn include_colours_inner(input: TokenStream) -> TokenStream {
     TokenStream::from(quote! {
         {
             const _: &[u8] = include_bytes!(#filename);
-            [#(#palette_data),*]
+            [#(#colour_type(#palette_data)),*]
         }
     })
 }
 
+struct IncludeAsepriteInput {
+    colours: Colours,
+    filenames: Punctuated<LitStr, Token![,]>,
+}
+
+impl Parse for IncludeAsepriteInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let colours = if input.peek(syn::LitInt) {
+            let num_colours: syn::LitInt = input.parse()?;
+            let _: Token![,] = input.parse()?;
+
+            match num_colours.base10_parse()? {
+                16 => Colours::Colours16,
+                256 => Colours::Colours256,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        num_colours,
+                        "Number of colours must be 16 or 256",
+                    ))
+                }
+            }
+        } else {
+            Colours::Colours16
+        };
+
+        Ok(Self {
+            colours,
+            filenames: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
 #[proc_macro]
 pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
     let out_dir_path = get_out_dir(&input.to_string());
 
-    let parser = Punctuated::<LitStr, syn::Token![,]>::parse_terminated;
-    let parsed = match parser.parse(input) {
+    let parsed = match syn::parse::<IncludeAsepriteInput>(input) {
         Ok(e) => e,
         Err(e) => return e.to_compile_error().into(),
     };
@@ -345,29 +411,26 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
     let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
 
     let filenames: Vec<PathBuf> = parsed
+        .filenames
         .iter()
         .map(|s| s.value())
         .map(|s| s.replace(OUT_DIR_TOKEN, &out_dir_path))
         .map(|s| Path::new(&root).join(&*s))
         .collect();
 
-    for filename in filenames.iter() {
-        let (frames, tag) = aseprite::generate_from_file(filename);
-
-        tags.push((tag, images.len()));
-
-        for frame in frames {
-            let width = frame.width();
-            let height = frame.height();
-            assert!(
-                valid_sprite_size(width, height),
-                "File {} contains sprites with size {}x{} which cannot be represented on the GameBoy Advance",
-                filename.display(),
-                width,
-                height
-            );
+    let mut add_sprite = |images: &mut Vec<Image>, sprite: image::DynamicImage, filename: &Path| {
+        let width = sprite.width();
+        let height = sprite.height();
+        assert!(
+            valid_sprite_size(width, height),
+            "File {} contains sprites with size {}x{} which cannot be represented on the GameBoy Advance",
+            filename.display(),
+            width,
+            height
+        );
 
-            let image = Image::load_from_dyn_image(frame);
+        let image = Image::load_from_dyn_image(sprite);
+        if let Colours::Colours16 = parsed.colours {
             add_to_optimiser(
                 &mut optimiser,
                 &image,
@@ -375,45 +438,127 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
                 height as usize,
                 Some(transparent_colour),
             );
-            images.push(image);
         }
-    }
+        images.push(image);
+    };
 
-    let optimised_results = optimiser
-        .optimise_palettes()
-        .expect("Failed to optimise palettes");
+    let mut metasprites: Vec<aseprite::MetaSpriteParts> = Vec::new();
 
-    let (palette_data, tile_data, assignments) = palette_tile_data(&optimised_results, &images);
+    for filename in filenames.iter() {
+        let (frames, tag, slices) = aseprite::generate_from_file(filename);
 
-    let palette_data = palette_data.iter().map(|colours| {
-        quote! {
-            Palette16::new([
-                #(#colours),*
-            ])
+        // with slices, the frames don't have to be sprite sizes since they're
+        // shown as metasprites, but they're included as normal if they are
+        let include_frames = slices.is_empty()
+            || frames
+                .iter()
+                .all(|frame| valid_sprite_size(frame.width(), frame.height()));
+
+        if include_frames {
+            tags.push((tag, images.len()));
+
+            for frame in &frames {
+                add_sprite(&mut images, frame.clone(), filename);
+            }
         }
-    });
 
-    let mut pre = 0;
-    let sprites = images
-        .iter()
-        .zip(assignments.iter())
-        .map(|(f, assignment)| {
-            let start: usize = pre;
-            let end: usize = pre + (f.width / 8) * (f.height / 8) * 32;
-            let data = ByteString(&tile_data[start..end]);
-            pre = end;
-            let width = f.width;
-            let height = f.height;
-            quote! {
-                unsafe {
-                        Sprite::new(
-                        &PALETTES[#assignment],
-                        align_bytes!(u16, #data),
-                        Size::from_width_height(#width, #height)
-                    )
+        let (parts, file_metasprites) = aseprite::metasprites(&frames, &slices, images.len());
+        for part in parts {
+            add_sprite(&mut images, part, filename);
+        }
+        metasprites.extend(file_metasprites);
+    }
+
+    let (palette_code, sprites) = match parsed.colours {
+        Colours::Colours16 => {
+            let optimised_results = optimiser
+                .optimise_palettes()
+                .expect("Failed to optimise palettes");
+
+            let (palette_data, tile_data, assignments) =
+                palette_tile_data(&optimised_results, &images);
+
+            let palette_data = palette_data.iter().map(|colours| {
+                quote! {
+                    Palette16::new([
+                        #(Rgb15(#colours)),*
+                    ])
                 }
-            }
-        });
+            });
+
+            let mut pre = 0;
+            let sprites: Vec<_> = images
+                .iter()
+                .zip(assignments.iter())
+                .map(|(f, assignment)| {
+                    let start: usize = pre;
+                    let end: usize = pre + (f.width / 8) * (f.height / 8) * 32;
+                    let data = ByteString(&tile_data[start..end]);
+                    pre = end;
+                    let width = f.width;
+                    let height = f.height;
+                    quote! {
+                        unsafe {
+                                Sprite::new(
+                                &PALETTES[#assignment],
+                                align_bytes!(u16, #data),
+                                Size::from_width_height(#width, #height)
+                            )
+                        }
+                    }
+                })
+                .collect();
+
+            let palette_code = quote! {
+                static PALETTES: &[Palette16] = &[
+                    #(#palette_data),*
+                ];
+            };
+
+            (palette_code, sprites)
+        }
+        Colours::Colours256 => {
+            let (palette_data, tile_data) = palette_256_tile_data(&images);
+
+            let palette_data = palette_data.iter().map(|colours| {
+                quote! {
+                    Palette16::new([
+                        #(Rgb15(#colours)),*
+                    ])
+                }
+            });
+
+            let mut pre = 0;
+            let sprites: Vec<_> = images
+                .iter()
+                .map(|f| {
+                    let start: usize = pre;
+                    let end: usize = pre + f.width * f.height;
+                    let data = ByteString(&tile_data[start..end]);
+                    pre = end;
+                    let width = f.width;
+                    let height = f.height;
+                    quote! {
+                        unsafe {
+                                Sprite::new_256(
+                                &PALETTE,
+                                align_bytes!(u16, #data),
+                                Size::from_width_height(#width, #height)
+                            )
+                        }
+                    }
+                })
+                .collect();
+
+            let palette_code = quote! {
+                static PALETTE: Palette256 = Palette256::new(&[
+                    #(#palette_data),*
+                ]);
+            };
+
+            (palette_code, sprites)
+        }
+    };
 
     let tags = tags.iter().flat_map(|(tag, num_images)| {
         tag.iter().map(move |tag| {
@@ -437,13 +582,23 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
         }
     });
 
+    let metasprites = metasprites.iter().map(|parts| {
+        let parts = parts.iter().map(|&(index, x, y)| {
+            quote! {
+                MetaSpritePart::new(&SPRITES[#index], #x, #y)
+            }
+        });
+
+        quote! {
+            MetaSprite::new(&[#(#parts),*])
+        }
+    });
+
     let module = quote! {
         #(#include_paths)*
 
 
-        static PALETTES: &[Palette16] = &[
-            #(#palette_data),*
-        ];
+        #palette_code
 
         static SPRITES: &[Sprite] = &[
             #(#sprites),*
@@ -455,6 +610,10 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
             ]
         );
 
+        static METASPRITES: &[MetaSprite] = &[
+            #(#metasprites),*
+        ];
+
     };
 
     TokenStream::from(module)
@@ -470,8 +629,6 @@ fn convert_image(
 ) -> proc_macro2::TokenStream {
     let image_filename = &parent.join(settings.filename());
     let image = Image::load_from_file(image_filename);
-    let deduplicate = settings.deduplicate();
-
     rust_generator::generate_code(
         variable_name,
         optimisation_results,
@@ -479,7 +636,7 @@ fn convert_image(
         &image_filename.to_string_lossy(),
         crate_prefix.to_owned(),
         assignment_offset,
-        deduplicate,
+        settings.output(),
     )
 }
 
@@ -551,6 +708,61 @@ fn palette_tile_data(
     (palette_data, tile_data, assignments)
 }
 
+/// Generates the palette and tile data for 256 colour sprites. Index 0 is
+/// transparent, and the rest of the colours are shared between all the images.
+fn palette_256_tile_data(images: &[Image]) -> (Vec<Vec<u16>>, Vec<u8>) {
+    let colours: BTreeSet<Colour> = images
+        .iter()
+        .flat_map(|image| {
+            (0..image.height).flat_map(move |y| (0..image.width).map(move |x| image.colour(x, y)))
+        })
+        .filter(|colour| !colour.is_transparent())
+        .collect();
+
+    assert!(
+        colours.len() < 256,
+        "256 colour sprites can use at most 255 colours, found {}",
+        colours.len()
+    );
+
+    let colours: Vec<Colour> = colours.into_iter().collect();
+
+    let palette_data = iter::once(0)
+        .chain(colours.iter().map(|colour| colour.to_rgb15()))
+        .collect::<Vec<_>>()
+        .chunks(16)
+        .map(|palette| {
+            palette
+                .iter()
+                .copied()
+                .chain(iter::repeat(0))
+                .take(16)
+                .collect()
+        })
+        .collect();
+
+    let mut tile_data = Vec::new();
+
+    for image in images {
+        for y in 0..image.height / 8 {
+            for x in 0..image.width / 8 {
+                for j in 0..8 {
+                    for i in 0..8 {
+                        let colour = image.colour(x * 8 + i, y * 8 + j);
+                        tile_data.push(if colour.is_transparent() {
+                            0
+                        } else {
+                            colours.binary_search(&colour).unwrap() as u8 + 1
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    (palette_data, tile_data)
+}
+
 fn collapse_to_4bpp(tile_data: &[u8]) -> Vec<u8> {
     tile_data
         .chunks(2)
@@ -642,8 +854,11 @@ pub fn include_font(input: TokenStream) -> TokenStream {
     };
 
     let all_args: Vec<_> = parsed.into_iter().collect();
-    if all_args.len() != 2 {
-        panic!("Include_font requires 2 arguments, got {}", all_args.len());
+    if all_args.len() < 2 {
+        panic!(
+            "Include_font requires at least 2 arguments, got {}",
+            all_args.len()
+        );
     }
 
     let filename = match flatten_group(&all_args[0]) {
@@ -673,18 +888,357 @@ pub fn include_font(input: TokenStream) -> TokenStream {
 
     let file_content = std::fs::read(&path).expect("Failed to read ttf file");
 
-    let rendered = font_loader::load_font(&file_content, font_size);
+    // Any further arguments are the path to a string table followed by the languages in
+    // that table whose characters should be included in the font.
+    let string_args: Vec<_> = all_args[2..]
+        .iter()
+        .map(|arg| match flatten_group(arg) {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(str_lit),
+                ..
+            }) => str_lit.value(),
+            _ => panic!("Expected literal strings for the string table and languages"),
+        })
+        .collect();
+
+    let (characters, strings_include) = match string_args.split_first() {
+        Some((strings_filename, languages)) => {
+            let strings_path = Path::new(&root).join(strings_filename);
+            let table = load_string_table(&strings_path);
+            let characters = table
+                .characters(languages)
+                .unwrap_or_else(|e| panic!("{}: {e}", strings_path.display()));
+
+            let strings_path = strings_path.to_string_lossy().into_owned();
+            (
+                Some(characters),
+                Some(quote!(let _ = include_bytes!(#strings_path);)),
+            )
+        }
+        None => (None, None),
+    };
+
+    let rendered = font_loader::load_font(&file_content, font_size, characters.as_ref());
 
     let include_path = path.to_string_lossy();
 
     quote!({
         let _ = include_bytes!(#include_path);
+        #strings_include
 
         #rendered
     })
     .into()
 }
 
+struct IncludeStringsInput {
+    module_name: syn::Ident,
+    as_pub: bool,
+    crate_prefix: String,
+    file_name: String,
+}
+
+impl Parse for IncludeStringsInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+
+        let crate_prefix: syn::Ident = if lookahead.peek(Token![crate]) {
+            let _: Token![crate] = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            format_ident!("crate")
+        } else {
+            format_ident!("agb")
+        };
+
+        let lookahead = input.lookahead1();
+
+        let as_pub = if lookahead.peek(Token![pub]) {
+            let _: Token![pub] = input.parse()?;
+            true
+        } else {
+            false
+        };
+
+        let module_name: syn::Ident = input.parse()?;
+        let _: Token![,] = input.parse()?;
+        let file_name: syn::LitStr = input.parse()?;
+
+        Ok(Self {
+            module_name,
+            as_pub,
+            crate_prefix: crate_prefix.to_string(),
+            file_name: file_name.value(),
+        })
+    }
+}
+
+#[proc_macro]
+pub fn include_strings(input: TokenStream) -> TokenStream {
+    let config = parse_macro_input!(input as IncludeStringsInput);
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(
+        config
+            .file_name
+            .replace(OUT_DIR_TOKEN, &get_out_dir(&config.file_name)),
+    );
+
+    let table = load_string_table(&path);
+    let table_code = match strings_loader::generate_code(&table, &config.crate_prefix) {
+        Ok(code) => code,
+        Err(e) => {
+            return syn::Error::new(Span::call_site(), format!("{}: {e}", path.display()))
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let include_path = path.to_string_lossy();
+    let module_name = config.module_name;
+    let visibility = if config.as_pub { quote!(pub) } else { quote!() };
+
+    quote! {
+        #visibility mod #module_name {
+            const _: &[u8] = include_bytes!(#include_path);
+
+            #table_code
+        }
+    }
+    .into()
+}
+
+struct IncludeMapInput {
+    module_name: syn::Ident,
+    as_pub: bool,
+    crate_prefix: String,
+    transparent_colour: Colour,
+    file_name: String,
+}
+
+impl Parse for IncludeMapInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+
+        let crate_prefix: syn::Ident = if lookahead.peek(Token![crate]) {
+            let _: Token![crate] = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            format_ident!("crate")
+        } else {
+            format_ident!("agb")
+        };
+
+        let lookahead = input.lookahead1();
+
+        let as_pub = if lookahead.peek(Token![pub]) {
+            let _: Token![pub] = input.parse()?;
+            true
+        } else {
+            false
+        };
+
+        let module_name: syn::Ident = input.parse()?;
+        let _: Token![,] = input.parse()?;
+
+        let first: syn::LitStr = input.parse()?;
+        let (transparent_colour, file_name) = if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            let file_name: syn::LitStr = input.parse()?;
+            let transparent_colour = first
+                .value()
+                .parse()
+                .map_err(|msg| syn::Error::new_spanned(first, msg))?;
+            (transparent_colour, file_name)
+        } else {
+            (Colour::from_rgb(255, 0, 255, 0), first)
+        };
+
+        Ok(Self {
+            module_name,
+            as_pub,
+            crate_prefix: crate_prefix.to_string(),
+            transparent_colour,
+            file_name: file_name.value(),
+        })
+    }
+}
+
+#[proc_macro]
+pub fn include_tiled_map(input: TokenStream) -> TokenStream {
+    let config = parse_macro_input!(input as IncludeMapInput);
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(
+        config
+            .file_name
+            .replace(OUT_DIR_TOKEN, &get_out_dir(&config.file_name)),
+    );
+
+    let map = tiled_loader::TiledMap::load(&path).unwrap_or_else(|e| panic!("{e}"));
+    assert!(
+        !map.tilesets.is_empty(),
+        "{} doesn't use any tilesets",
+        path.display()
+    );
+
+    let (optimisation_results, tiles_code) = map_tiles_code(
+        &map.tilesets,
+        config.transparent_colour,
+        &config.crate_prefix,
+    );
+    let map_code = tiled_loader::generate_code(&map, &optimisation_results, &config.crate_prefix);
+
+    let include_paths = map
+        .files
+        .iter()
+        .chain(map.tilesets.iter().map(|tileset| &tileset.image))
+        .map(|path| {
+            let path = path.to_string_lossy();
+            quote!(
+                const _: &[u8] = include_bytes!(#path);
+            )
+        });
+
+    let module_name = config.module_name;
+    let visibility = if config.as_pub { quote!(pub) } else { quote!() };
+
+    quote! {
+        #visibility mod #module_name {
+            #(#include_paths)*
+
+            #tiles_code
+
+            #map_code
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn include_ldtk(input: TokenStream) -> TokenStream {
+    let config = parse_macro_input!(input as IncludeMapInput);
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(
+        config
+            .file_name
+            .replace(OUT_DIR_TOKEN, &get_out_dir(&config.file_name)),
+    );
+
+    let project = ldtk_loader::LdtkProject::load(&path).unwrap_or_else(|e| panic!("{e}"));
+    assert!(
+        !project.tilesets.is_empty(),
+        "{} doesn't have any tile layers",
+        path.display()
+    );
+
+    let (optimisation_results, tiles_code) = map_tiles_code(
+        &project.tilesets,
+        config.transparent_colour,
+        &config.crate_prefix,
+    );
+    let project_code =
+        ldtk_loader::generate_code(&project, &optimisation_results, &config.crate_prefix);
+
+    let include_paths = project
+        .files
+        .iter()
+        .chain(project.tilesets.iter().map(|tileset| &tileset.image))
+        .map(|path| {
+            let path = path.to_string_lossy();
+            quote!(
+                const _: &[u8] = include_bytes!(#path);
+            )
+        });
+
+    let module_name = config.module_name;
+    let visibility = if config.as_pub { quote!(pub) } else { quote!() };
+
+    quote! {
+        #visibility mod #module_name {
+            #(#include_paths)*
+
+            #tiles_code
+
+            #project_code
+        }
+    }
+    .into()
+}
+
+/// Generates `PALETTES` and `TILES` for a map where `TILES` contains every tile of every
+/// tileset one after the other, so the tile ids match those used by the map.
+fn map_tiles_code(
+    tilesets: &[tiled_loader::Tileset],
+    transparent_colour: Colour,
+    crate_prefix: &str,
+) -> (Palette16OptimisationResults, proc_macro2::TokenStream) {
+    let tiles: Vec<_> = tilesets
+        .iter()
+        .flat_map(|tileset| tileset.tile_images())
+        .collect();
+    let tiles = Image::from_colour_data(
+        tiles
+            .iter()
+            .flat_map(|tile| (0..8).flat_map(move |y| (0..8).map(move |x| tile.colour(x, y))))
+            .collect(),
+    );
+
+    let mut optimiser = Palette16Optimiser::new(Some(transparent_colour));
+    add_to_optimiser(&mut optimiser, &tiles, 8, 8, Some(transparent_colour));
+    let optimisation_results = optimiser
+        .optimise_palettes()
+        .expect("Failed to optimise palettes");
+
+    let first_image = tilesets[0].image.to_string_lossy().into_owned();
+    let palette_code = rust_generator::generate_palette_code(&optimisation_results, crate_prefix);
+    let tiles_code = rust_generator::generate_code(
+        "TILES",
+        &optimisation_results,
+        &tiles,
+        &first_image,
+        crate_prefix.to_owned(),
+        Some(0),
+        TileOutput::Tiles,
+    );
+
+    (
+        optimisation_results,
+        quote! {
+            #palette_code
+
+            #tiles_code
+        },
+    )
+}
+
+/// Converts a name from a file into something suitable as the name of a constant
+fn constant_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if name.chars().next().is_none_or(|c| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn load_string_table(path: &Path) -> strings_loader::StringTable {
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read string table {}: {e}", path.display()));
+
+    strings_loader::StringTable::parse(&content)
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
 fn valid_sprite_size(width: u32, height: u32) -> bool {
     match (width, height) {
         (8, 8) => true,
@@ -717,6 +1271,8 @@ fn get_out_dir(raw_input: &str) -> String {
 mod tests {
     use asefile::AnimationDirection;
 
+    use super::*;
+
     #[test]
     // These directions defined in agb and have these values. This is important
     // when outputting code for agb. If more animation directions are added then
@@ -726,4 +1282,30 @@ mod tests {
         assert_eq!(AnimationDirection::Reverse as usize, 1);
         assert_eq!(AnimationDirection::PingPong as usize, 2);
     }
+
+    #[test]
+    fn sprite_256_colours_are_shared_and_zero_is_transparent() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let blue = Colour::from_rgb(0, 0, 255, 255);
+        let transparent = Colour::from_rgb(255, 0, 255, 0);
+
+        let mut first = vec![transparent; 64];
+        first[1] = red;
+        first[63] = blue;
+        let second = vec![blue; 64];
+
+        let (palettes, tile_data) = palette_256_tile_data(&[
+            Image::from_colour_data(first),
+            Image::from_colour_data(second),
+        ]);
+
+        assert_eq!(palettes.len(), 1);
+        assert_eq!(palettes[0][0], 0);
+
+        assert_eq!(tile_data.len(), 128);
+        assert_eq!(tile_data[0], 0);
+        assert_eq!(palettes[0][tile_data[1] as usize], red.to_rgb15());
+        assert_eq!(palettes[0][tile_data[63] as usize], blue.to_rgb15());
+        assert!(tile_data[64..].iter().all(|&pixel| pixel == tile_data[63]));
+    }
 }
@@ -1,7 +1,7 @@
 use crate::deduplicator::{DeduplicatedData, Transformation};
 use crate::palette16::Palette16OptimisationResults;
 use crate::{add_image_256_to_tile_data, add_image_to_tile_data, collapse_to_4bpp};
-use crate::{image_loader::Image, ByteString};
+use crate::{image_loader::Image, ByteString, TileOutput};
 
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
@@ -25,7 +25,7 @@ pub(crate) fn generate_palette_code(
 
         quote! {
             #crate_prefix::display::palette16::Palette16::new([
-                #(#colours),*
+                #(#crate_prefix::display::Rgb15(#colours)),*
             ])
         }
     });
@@ -42,12 +42,14 @@ pub(crate) fn generate_code(
     image_filename: &str,
     crate_prefix: String,
     assignment_offset: Option<usize>,
-    deduplicate: bool,
+    output: TileOutput,
 ) -> TokenStream {
     let crate_prefix = format_ident!("{}", crate_prefix);
     let output_variable_name = format_ident!("{}", output_variable_name);
+    // the deduplicated image is a single column of tiles, so remember the original size
+    let (image_width, image_height) = (image.width, image.height);
 
-    let (image, dedup_data) = if deduplicate {
+    let (image, dedup_data) = if output != TileOutput::Tiles {
         let (new_image, dedup_data) =
             crate::deduplicator::deduplicate_image(image, assignment_offset.is_some());
 
@@ -120,9 +122,22 @@ pub(crate) fn generate_code(
         quote! { #crate_prefix::display::tiled::TileFormat::EightBpp }
     };
 
+    let (output_type, output_value) = if output == TileOutput::Map {
+        let (width, height) = (image_width / 8, image_height / 8);
+        (
+            quote!(#crate_prefix::display::tile_data::TileMap),
+            quote!(#crate_prefix::display::tile_data::TileMap::new(TILE_SET, TILE_SETTINGS, #width, #height)),
+        )
+    } else {
+        (
+            quote!(#crate_prefix::display::tile_data::TileData),
+            quote!(#crate_prefix::display::tile_data::TileData::new(TILE_SET, TILE_SETTINGS)),
+        )
+    };
+
     quote! {
         #[allow(non_upper_case_globals)]
-        pub static #output_variable_name: #crate_prefix::display::tile_data::TileData = {
+        pub static #output_variable_name: #output_type = {
             const _: &[u8] = include_bytes!(#image_filename);
 
             const TILE_DATA: &[u8] = {
@@ -145,7 +160,7 @@ pub(crate) fn generate_code(
                 #(#tile_settings),*
             ];
 
-            #crate_prefix::display::tile_data::TileData::new(TILE_SET, TILE_SETTINGS)
+            #output_value
         };
     }
 }
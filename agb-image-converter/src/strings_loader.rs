@@ -0,0 +1,432 @@
+use std::collections::{BTreeSet, HashSet};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// A table of translated strings loaded from a csv file. The first row is the header and
+/// must start with `key` followed by one column per language code. Every subsequent row
+/// contains the key followed by the translation in each language.
+pub(crate) struct StringTable {
+    pub languages: Vec<String>,
+    pub keys: Vec<String>,
+    /// Indexed by `language * keys.len() + key`
+    pub strings: Vec<String>,
+}
+
+impl StringTable {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut rows = parse_csv(content)?.into_iter();
+
+        let header = rows
+            .next()
+            .ok_or_else(|| "String table is empty".to_string())?;
+        if header.first().map(|key| key.trim()) != Some("key") {
+            return Err("First column of the header must be `key`".to_string());
+        }
+
+        let languages: Vec<String> = header[1..].iter().map(|l| l.trim().to_string()).collect();
+        if languages.is_empty() {
+            return Err("String table must contain at least one language".to_string());
+        }
+
+        let mut seen_languages = HashSet::new();
+        for language in &languages {
+            if !is_valid_identifier(language) {
+                return Err(format!("Invalid language code `{language}`"));
+            }
+
+            if !seen_languages.insert(language.to_ascii_uppercase()) {
+                return Err(format!("Duplicate language `{language}`"));
+            }
+        }
+
+        let mut keys = vec![];
+        let mut translations = vec![];
+        let mut seen_keys = HashSet::new();
+
+        for row in rows {
+            if row.iter().all(|cell| cell.is_empty()) {
+                continue;
+            }
+
+            let key = row[0].trim().to_string();
+            if !is_valid_identifier(&key) {
+                return Err(format!("Invalid key `{key}`"));
+            }
+
+            if !seen_keys.insert(key.to_ascii_uppercase()) {
+                return Err(format!("Duplicate key `{key}`"));
+            }
+
+            if row.len() != languages.len() + 1 {
+                return Err(format!(
+                    "Key `{key}` has {} translations but there are {} languages",
+                    row.len() - 1,
+                    languages.len()
+                ));
+            }
+
+            let mut argument_count = None;
+            for (language, translation) in languages.iter().zip(&row[1..]) {
+                let count = count_arguments(translation, plural_forms(language))
+                    .map_err(|e| format!("Key `{key}` in language `{language}`: {e}"))?;
+
+                match argument_count {
+                    None => argument_count = Some(count),
+                    Some(expected) if expected != count => {
+                        return Err(format!(
+                            "Key `{key}` uses {count} arguments in language `{language}` but {expected} in `{}`",
+                            languages[0]
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+
+            keys.push(key);
+            translations.push(row[1..].to_vec());
+        }
+
+        let strings = (0..languages.len())
+            .flat_map(|language| translations.iter().map(move |row| row[language].clone()))
+            .collect();
+
+        Ok(Self {
+            languages,
+            keys,
+            strings,
+        })
+    }
+
+    /// All the characters which could be output when displaying the given languages, or
+    /// every language if `languages` is empty. Placeholders are excluded, but the digits and
+    /// minus sign are included since these are needed to display numeric arguments.
+    pub fn characters(&self, languages: &[String]) -> Result<BTreeSet<char>, String> {
+        let mut characters: BTreeSet<char> = "0123456789-".chars().collect();
+
+        for (language_index, language) in self.languages.iter().enumerate() {
+            if !languages.is_empty() && !languages.contains(language) {
+                continue;
+            }
+
+            let start = language_index * self.keys.len();
+            for string in &self.strings[start..start + self.keys.len()] {
+                collect_characters(string, &mut characters);
+            }
+        }
+
+        for language in languages {
+            if !self.languages.contains(language) {
+                return Err(format!("Unknown language `{language}`"));
+            }
+        }
+
+        Ok(characters)
+    }
+}
+
+/// Parses a format string, returning the number of arguments it requires.
+///
+/// `{n}` is replaced by argument `n`, `{n:form|form|...}` selects a plural form based on
+/// argument `n` with `#` being replaced by the argument itself, and `{{` and `}}` output
+/// literal braces. Every plural placeholder must have exactly `plural_forms` forms.
+fn count_arguments(format: &str, plural_forms: usize) -> Result<usize, String> {
+    let mut count = 0;
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '}' => return Err("Unmatched `}`".to_string()),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') => return Err("Nested `{` in placeholder".to_string()),
+                        Some(c) => placeholder.push(c),
+                        None => return Err("Unterminated placeholder".to_string()),
+                    }
+                }
+
+                let (index, forms) = match placeholder.split_once(':') {
+                    Some((index, forms)) => (index, Some(forms)),
+                    None => (placeholder.as_str(), None),
+                };
+                let index: usize = index
+                    .parse()
+                    .map_err(|_| format!("Invalid argument index `{index}`"))?;
+
+                if index >= 10 {
+                    return Err(format!("Argument index {index} must be less than 10"));
+                }
+
+                if let Some(forms) = forms {
+                    let form_count = forms.split('|').count();
+                    if form_count != plural_forms {
+                        return Err(format!(
+                            "Argument {index} has {form_count} plural forms but the language needs {plural_forms}"
+                        ));
+                    }
+                }
+
+                count = count.max(index + 1);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(count)
+}
+
+fn collect_characters(format: &str, characters: &mut BTreeSet<char>) {
+    let mut chars = format.chars().peekable();
+    let mut in_placeholder = false;
+    let mut in_plural_form = false;
+
+    while let Some(c) = chars.next() {
+        match (c, in_placeholder) {
+            ('{' | '}', false) if chars.peek() == Some(&c) => {
+                chars.next();
+                characters.insert(c);
+            }
+            ('{', false) => in_placeholder = true,
+            ('}', true) => {
+                in_placeholder = false;
+                in_plural_form = false;
+            }
+            (':', true) => in_plural_form = true,
+            ('|' | '#', true) => {}
+            (c, true) => {
+                if in_plural_form {
+                    characters.insert(c);
+                }
+            }
+            (c, false) => {
+                characters.insert(c);
+            }
+        }
+    }
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if cell.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut cell)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => cell.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quoted value".to_string());
+    }
+
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// The plural rule used for a given language code, referring to the variants of
+/// `agb::localisation::PluralRule`.
+fn plural_rule(language: &str) -> &'static str {
+    match language
+        .to_ascii_lowercase()
+        .split(['_', '-'])
+        .next()
+        .unwrap()
+    {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" => "Invariant",
+        "fr" | "pt" => "OneIncludesZero",
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" => "EastSlavic",
+        "pl" => "Polish",
+        "cs" | "sk" => "Czech",
+        _ => "OneOther",
+    }
+}
+
+/// The number of forms a plural placeholder needs in the given language.
+fn plural_forms(language: &str) -> usize {
+    match plural_rule(language) {
+        "Invariant" => 1,
+        "OneOther" | "OneIncludesZero" => 2,
+        _ => 3,
+    }
+}
+
+pub(crate) fn generate_code(
+    table: &StringTable,
+    crate_prefix: &str,
+) -> Result<TokenStream, String> {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+
+    let language_constants = table
+        .languages
+        .iter()
+        .enumerate()
+        .map(|(i, language)| {
+            let name = format_ident!("{}", language.to_ascii_uppercase());
+            let index = u8::try_from(i).map_err(|_| {
+                format!(
+                    "String table has {} languages, but the most it can have is {}",
+                    table.languages.len(),
+                    u32::from(u8::MAX) + 1
+                )
+            })?;
+            Ok(quote! {
+                pub const #name: #crate_prefix::localisation::LanguageId = #crate_prefix::localisation::LanguageId::new(#index);
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let languages = table.languages.iter().map(|language| {
+        let rule = format_ident!("{}", plural_rule(language));
+        quote! {
+            #crate_prefix::localisation::Language::new(#language, #crate_prefix::localisation::PluralRule::#rule)
+        }
+    });
+
+    let key_constants = table
+        .keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let name = format_ident!("{}", key.to_ascii_uppercase());
+            let index = u16::try_from(i).map_err(|_| {
+                format!(
+                    "String table has {} keys, but the most it can have is {}",
+                    table.keys.len(),
+                    u32::from(u16::MAX) + 1
+                )
+            })?;
+            Ok(quote! {
+                pub const #name: #crate_prefix::localisation::StringId = #crate_prefix::localisation::StringId::new(#index);
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let strings = &table.strings;
+    let num_keys = table.keys.len();
+
+    Ok(quote! {
+        pub mod languages {
+            #(#language_constants)*
+        }
+
+        pub mod keys {
+            #(#key_constants)*
+        }
+
+        pub static TABLE: #crate_prefix::localisation::StringTable = #crate_prefix::localisation::StringTable::new(
+            &[#(#languages),*],
+            &[#(#strings),*],
+            #num_keys,
+        );
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_csv() {
+        let table = StringTable::parse(
+            "key,en,fr\r\ngreeting,\"Hello, {0}!\",\"Bonjour, {0} !\"\napples,\"{0:# apple|# apples}\",\"{0:# pomme|# pommes}\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(table.languages, ["en", "fr"]);
+        assert_eq!(table.keys, ["greeting", "apples"]);
+        assert_eq!(
+            table.strings,
+            [
+                "Hello, {0}!",
+                "{0:# apple|# apples}",
+                "Bonjour, {0} !",
+                "{0:# pomme|# pommes}"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_tables_too_big_for_their_ids() {
+        let table = |languages: usize, keys: usize| StringTable {
+            languages: (0..languages).map(|i| format!("l{i}")).collect(),
+            keys: (0..keys).map(|i| format!("k{i}")).collect(),
+            strings: vec![String::new(); languages * keys],
+        };
+
+        assert!(generate_code(&table(256, 1), "agb").is_ok());
+        assert!(generate_code(&table(257, 1), "agb").is_err());
+        assert!(generate_code(&table(1, 65536), "agb").is_ok());
+        assert!(generate_code(&table(1, 65537), "agb").is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_arguments() {
+        assert!(StringTable::parse("key,en,fr\ngreeting,Hello {0},Bonjour\n").is_err());
+        assert!(StringTable::parse("key,en\ngreeting,Hello {0\n").is_err());
+        assert!(StringTable::parse("key,en\ngreeting,Hello }\n").is_err());
+    }
+
+    #[test]
+    fn checks_plural_forms_for_each_language() {
+        assert!(StringTable::parse("key,en,ja\napples,{0:# apple|# apples},{0:#個}\n").is_ok());
+        assert!(StringTable::parse("key,en\napples,{0:# apples}\n").is_err());
+        assert!(StringTable::parse("key,ja\napples,{0:#個|#個}\n").is_err());
+        assert!(StringTable::parse("key,ru\napples,{0:# яблоко|# яблока}\n").is_err());
+        assert!(StringTable::parse("key,ru\napples,{0:# яблоко|# яблока|# яблок}\n").is_ok());
+    }
+
+    #[test]
+    fn collects_characters_for_selected_languages() {
+        let table = StringTable::parse(
+            "key,en,ja\nyes,{{Yes}},はい\napples,{0:# apple|# apples},{0:#個}\n",
+        )
+        .unwrap();
+
+        let characters = table.characters(&["ja".to_string()]).unwrap();
+        assert!(characters.contains(&'は'));
+        assert!(characters.contains(&'個'));
+        assert!(!characters.contains(&'Y'));
+        assert!(!characters.contains(&'#'));
+
+        let characters = table.characters(&[]).unwrap();
+        assert!(characters.contains(&'p'));
+        assert!(characters.contains(&'{'));
+        assert!(!characters.contains(&'|'));
+
+        assert!(table.characters(&["de".to_string()]).is_err());
+    }
+}
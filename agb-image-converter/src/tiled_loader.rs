@@ -0,0 +1,1172 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use roxmltree::Node;
+use serde_json::Value;
+
+use crate::{colour::Colour, image_loader::Image, palette16::Palette16OptimisationResults};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+
+/// The number of different tiles a background can use, which is as many as a
+/// `TileSetting` can refer to.
+const MAX_TILES: usize = 1024;
+
+const RESERVED_NAMES: &[&str] = &[
+    "WIDTH",
+    "HEIGHT",
+    "PALETTES",
+    "TILES",
+    "PROPERTIES",
+    "TILE_PROPERTIES",
+    "TILE_LAYERS",
+    "OBJECT_LAYERS",
+];
+
+/// A map loaded from a Tiled tmx or tmj file. Only the features of Tiled which can be
+/// represented on the Game Boy Advance are supported, so the map must be orthogonal, finite
+/// and use 8x8 tiles.
+pub(crate) struct TiledMap {
+    pub width: usize,
+    pub height: usize,
+    pub tilesets: Vec<Tileset>,
+    pub layers: Vec<Layer>,
+    pub properties: Vec<Property>,
+    /// Every file which was read to load this map
+    pub files: Vec<PathBuf>,
+}
+
+pub(crate) struct Tileset {
+    pub first_gid: u32,
+    pub image: PathBuf,
+    pub transparent_colour: Option<Colour>,
+    pub columns: usize,
+    pub tile_count: usize,
+    pub margin: usize,
+    pub spacing: usize,
+    pub tiles: Vec<TileInfo>,
+}
+
+pub(crate) struct TileInfo {
+    pub id: u32,
+    pub class: String,
+    pub properties: Vec<Property>,
+}
+
+pub(crate) enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+pub(crate) struct TileLayer {
+    pub name: String,
+    pub tiles: Vec<MapTile>,
+    pub properties: Vec<Property>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct MapTile {
+    pub gid: u32,
+    pub hflip: bool,
+    pub vflip: bool,
+}
+
+pub(crate) struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<Object>,
+    pub properties: Vec<Property>,
+}
+
+pub(crate) struct Object {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub tile: Option<MapTile>,
+    pub properties: Vec<Property>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Property {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum PropertyValue {
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(String),
+    Colour(Colour),
+    Object(u32),
+    Class(Vec<Property>),
+}
+
+impl MapTile {
+    const EMPTY: Self = MapTile {
+        gid: 0,
+        hflip: false,
+        vflip: false,
+    };
+
+    fn from_raw(raw: u32) -> Result<Self, String> {
+        if raw & FLIPPED_DIAGONALLY != 0 {
+            return Err(
+                "Rotated tiles cannot be represented on the Game Boy Advance, only flipped ones"
+                    .to_string(),
+            );
+        }
+
+        Ok(MapTile {
+            gid: raw & GID_MASK,
+            hflip: raw & FLIPPED_HORIZONTALLY != 0,
+            vflip: raw & FLIPPED_VERTICALLY != 0,
+        })
+    }
+}
+
+impl TiledMap {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension == "tmj" || extension == "json");
+
+        let content = read_file(path)?;
+        let mut map = if is_json {
+            let json: Value =
+                serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))?;
+            Self::from_json(&json, path)?
+        } else {
+            let document = roxmltree::Document::parse(&content)
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+            Self::from_xml(document.root_element(), path)?
+        };
+
+        map.files.insert(0, path.to_owned());
+        map.validate()?;
+
+        Ok(map)
+    }
+
+    fn from_xml(map: Node, path: &Path) -> Result<Self, String> {
+        check_map_settings(
+            map.attribute("orientation").unwrap_or("orthogonal"),
+            map.attribute("infinite") == Some("1"),
+            xml_usize(map, "tilewidth")?,
+            xml_usize(map, "tileheight")?,
+        )?;
+
+        let width = xml_usize(map, "width")?;
+        let height = xml_usize(map, "height")?;
+
+        let mut files = vec![];
+        let mut tilesets = vec![];
+        for tileset in map.children().filter(|n| n.has_tag_name("tileset")) {
+            let first_gid = xml_usize(tileset, "firstgid")? as u32;
+            match tileset.attribute("source") {
+                Some(source) => {
+                    let source = relative_to(path, source);
+                    tilesets.push(load_tileset(&source, first_gid)?);
+                    files.push(source);
+                }
+                None => tilesets.push(xml_tileset(tileset, first_gid, path)?),
+            }
+        }
+
+        let mut layers = vec![];
+        xml_layers(map, width * height, &mut layers)?;
+
+        Ok(Self {
+            width,
+            height,
+            tilesets,
+            layers,
+            properties: xml_properties(map)?,
+            files,
+        })
+    }
+
+    fn from_json(map: &Value, path: &Path) -> Result<Self, String> {
+        check_map_settings(
+            map["orientation"].as_str().unwrap_or("orthogonal"),
+            map["infinite"].as_bool().unwrap_or(false),
+            json_usize(map, "tilewidth")?,
+            json_usize(map, "tileheight")?,
+        )?;
+
+        let width = json_usize(map, "width")?;
+        let height = json_usize(map, "height")?;
+
+        let mut files = vec![];
+        let mut tilesets = vec![];
+        for tileset in json_array(map, "tilesets")? {
+            let first_gid = json_usize(tileset, "firstgid")? as u32;
+            match tileset["source"].as_str() {
+                Some(source) => {
+                    let source = relative_to(path, source);
+                    tilesets.push(load_tileset(&source, first_gid)?);
+                    files.push(source);
+                }
+                None => tilesets.push(json_tileset(tileset, first_gid, path)?),
+            }
+        }
+
+        let mut layers = vec![];
+        json_layers(json_array(map, "layers")?, width * height, &mut layers)?;
+
+        Ok(Self {
+            width,
+            height,
+            tilesets,
+            layers,
+            properties: json_properties(map)?,
+            files,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut names = std::collections::HashSet::new();
+
+        for layer in &self.layers {
+            let (name, tiles): (_, Box<dyn Iterator<Item = &MapTile>>) = match layer {
+                Layer::Tiles(layer) => (&layer.name, Box::new(layer.tiles.iter())),
+                Layer::Objects(layer) => (
+                    &layer.name,
+                    Box::new(layer.objects.iter().filter_map(|o| o.tile.as_ref())),
+                ),
+            };
+
+            if RESERVED_NAMES.contains(&crate::constant_name(name).as_str()) {
+                return Err(format!(
+                    "Layer `{name}` has the same name as one of the generated statics"
+                ));
+            }
+
+            if !names.insert(crate::constant_name(name)) {
+                return Err(format!("Multiple layers have the name `{name}`"));
+            }
+
+            for tile in tiles {
+                if tile.gid != 0 && self.tile_index(tile.gid).is_none() {
+                    return Err(format!(
+                        "Layer `{name}` uses tile {} which isn't in any tileset",
+                        tile.gid
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The index of the tile in the combination of every tileset in this map, in the
+    /// order that they appear.
+    pub fn tile_index(&self, gid: u32) -> Option<usize> {
+        let mut offset = 0;
+        for tileset in &self.tilesets {
+            if (tileset.first_gid..tileset.first_gid + tileset.tile_count as u32).contains(&gid) {
+                return Some(offset + (gid - tileset.first_gid) as usize);
+            }
+
+            offset += tileset.tile_count;
+        }
+
+        None
+    }
+}
+
+impl Tileset {
+    /// Splits the tileset image into 8x8 tiles, with any pixels which are the transparent
+    /// colour of the tileset made fully transparent.
+    pub fn tile_images(&self) -> Vec<Image> {
+        let image = Image::load_from_file(&self.image);
+
+        (0..self.tile_count)
+            .map(|tile| {
+                let x = self.margin + (tile % self.columns) * (8 + self.spacing);
+                let y = self.margin + (tile / self.columns) * (8 + self.spacing);
+
+                assert!(
+                    x + 8 <= image.width && y + 8 <= image.height,
+                    "Tileset image {} is too small for {} tiles",
+                    self.image.display(),
+                    self.tile_count
+                );
+
+                let colours = (0..64)
+                    .map(|i| {
+                        let colour = image.colour(x + i % 8, y + i / 8);
+                        if Some(colour) == self.transparent_colour {
+                            Colour::from_rgb(colour.r, colour.g, colour.b, 0)
+                        } else {
+                            colour
+                        }
+                    })
+                    .collect();
+
+                Image::from_colour_data(colours)
+            })
+            .collect()
+    }
+}
+
+/// Generates the statics for every layer, object layer and the custom properties of the map.
+/// Expects `TILES` to be a `TileData` containing every tile of every tileset in order, with
+/// palettes assigned by `results`.
+pub(crate) fn generate_code(
+    map: &TiledMap,
+    results: &Palette16OptimisationResults,
+    crate_prefix: &str,
+) -> TokenStream {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+    let tiled = quote!(#crate_prefix::display::tiled);
+
+    let tile_setting = |tile: &MapTile| {
+        if tile.gid == 0 {
+            return quote!(#tiled::TileSetting::BLANK);
+        }
+
+        let index = map.tile_index(tile.gid).unwrap();
+        let palette = results.assignments[index] as u8;
+        let index = tile_id(index);
+        let (hflip, vflip) = (tile.hflip, tile.vflip);
+
+        quote!(#tiled::TileSetting::new(#index, #hflip, #vflip, #palette))
+    };
+
+    let tile_properties = tile_properties_code(&map.tilesets, &crate_prefix);
+
+    let width = map.width;
+    let height = map.height;
+
+    let mut tile_layers = vec![];
+    let mut object_layers = vec![];
+    let layers = map.layers.iter().map(|layer| match layer {
+        Layer::Tiles(layer) => {
+            let name = &layer.name;
+            let ident = format_ident!("{}", crate::constant_name(name));
+            let tiles = layer.tiles.iter().map(tile_setting);
+            let properties = properties_code(&layer.properties, &crate_prefix);
+            tile_layers.push(ident.clone());
+
+            quote! {
+                pub static #ident: #tiled::MapLayer = #tiled::MapLayer::new(
+                    #name,
+                    &TILES.tiles,
+                    #width,
+                    #height,
+                    &[#(#tiles),*],
+                    TILE_PROPERTIES,
+                    #properties,
+                );
+            }
+        }
+        Layer::Objects(layer) => {
+            let name = &layer.name;
+            let ident = format_ident!("{}", crate::constant_name(name));
+            let objects = layer.objects.iter().map(|object| {
+                let id = object.id;
+                let name = &object.name;
+                let class = &object.class;
+                let (x, y) = (object.x.round() as i32, object.y.round() as i32);
+                let (width, height) = (object.width.round() as i32, object.height.round() as i32);
+                let tile = match &object.tile {
+                    Some(tile) => {
+                        let tile = tile_setting(tile);
+                        quote!(Some(#tile))
+                    }
+                    None => quote!(None),
+                };
+                let properties = properties_code(&object.properties, &crate_prefix);
+
+                quote! {
+                    #tiled::MapObject::new(
+                        #id,
+                        #name,
+                        #class,
+                        #crate_prefix::fixnum::Vector2D::new(#x, #y),
+                        #crate_prefix::fixnum::Vector2D::new(#width, #height),
+                        #tile,
+                        #properties,
+                    )
+                }
+            });
+            let properties = properties_code(&layer.properties, &crate_prefix);
+            object_layers.push(ident.clone());
+
+            quote! {
+                pub static #ident: #tiled::MapObjectLayer = #tiled::MapObjectLayer::new(
+                    #name,
+                    &[#(#objects),*],
+                    #properties,
+                );
+            }
+        }
+    });
+    let layers: Vec<_> = layers.collect();
+
+    let properties = properties_code(&map.properties, &crate_prefix);
+
+    quote! {
+        /// The width of the map in tiles
+        pub const WIDTH: usize = #width;
+        /// The height of the map in tiles
+        pub const HEIGHT: usize = #height;
+
+        /// The custom properties of the map
+        pub static PROPERTIES: #tiled::Properties = #properties;
+
+        static TILE_PROPERTIES: &[#tiled::TileProperties] = #tile_properties;
+
+        #(#layers)*
+
+        /// Every tile layer in the order they appear in the map
+        pub static TILE_LAYERS: &[&#tiled::MapLayer] = &[#(&#tile_layers),*];
+        /// Every object layer in the order they appear in the map
+        pub static OBJECT_LAYERS: &[&#tiled::MapObjectLayer] = &[#(&#object_layers),*];
+    }
+}
+
+/// The class and properties of every tile in `tilesets` which has any, sorted by their index
+/// in the combination of all the tilesets.
+pub(crate) fn tile_properties_code(
+    tilesets: &[Tileset],
+    crate_prefix: &proc_macro2::Ident,
+) -> TokenStream {
+    let tiled = quote!(#crate_prefix::display::tiled);
+
+    let mut offset = 0;
+    let mut tile_properties = vec![];
+    for tileset in tilesets {
+        let mut tiles: Vec<_> = tileset
+            .tiles
+            .iter()
+            .filter(|tile| !tile.class.is_empty() || !tile.properties.is_empty())
+            .collect();
+        tiles.sort_by_key(|tile| tile.id);
+
+        for tile in tiles {
+            let tile_id = tile_id(offset + tile.id as usize);
+            let class = &tile.class;
+            let properties = properties_code(&tile.properties, crate_prefix);
+
+            tile_properties.push(quote! {
+                #tiled::TileProperties::new(#tile_id, #class, #properties)
+            });
+        }
+
+        offset += tileset.tile_count;
+    }
+
+    quote!(&[#(#tile_properties),*])
+}
+
+/// The id used in the generated code for the tile at `index` in the combination of every
+/// tileset, which has to be one that a `TileSetting` can refer to.
+fn tile_id(index: usize) -> u16 {
+    assert!(
+        index < MAX_TILES,
+        "Tile {index} is used, but the tilesets of a map can only have {MAX_TILES} tiles between them"
+    );
+
+    index as u16
+}
+
+pub(crate) fn properties_code(
+    properties: &[Property],
+    crate_prefix: &proc_macro2::Ident,
+) -> TokenStream {
+    let tiled = quote!(#crate_prefix::display::tiled);
+
+    let properties = properties.iter().map(|property| {
+        let name = &property.name;
+        let value = match &property.value {
+            PropertyValue::Bool(value) => quote!(Bool(#value)),
+            PropertyValue::Int(value) => quote!(Int(#value)),
+            PropertyValue::Float(value) => {
+                let raw = (value * 256.0).round() as i32;
+                quote!(Float(#crate_prefix::fixnum::Num::from_raw(#raw)))
+            }
+            PropertyValue::String(value) => quote!(String(#value)),
+            PropertyValue::Colour(value) => {
+                let value = value.to_rgb15();
+                quote!(Colour(#value))
+            }
+            PropertyValue::Object(value) => quote!(Object(#value)),
+            PropertyValue::Class(value) => {
+                let value = properties_code(value, crate_prefix);
+                quote!(Class(#value))
+            }
+        };
+
+        quote!(#tiled::Property::new(#name, #tiled::PropertyValue::#value))
+    });
+
+    quote!(#tiled::Properties::new(&[#(#properties),*]))
+}
+
+fn check_map_settings(
+    orientation: &str,
+    infinite: bool,
+    tile_width: usize,
+    tile_height: usize,
+) -> Result<(), String> {
+    if orientation != "orthogonal" {
+        return Err(format!(
+            "Only orthogonal maps are supported, got {orientation}"
+        ));
+    }
+
+    if infinite {
+        return Err("Infinite maps are not supported".to_string());
+    }
+
+    if tile_width != 8 || tile_height != 8 {
+        return Err(format!(
+            "Maps must use 8x8 tiles, got {tile_width}x{tile_height}"
+        ));
+    }
+
+    Ok(())
+}
+
+fn load_tileset(path: &Path, first_gid: u32) -> Result<Tileset, String> {
+    let content = read_file(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension == "tsj" || extension == "json");
+
+    if is_json {
+        let json: Value =
+            serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))?;
+        json_tileset(&json, first_gid, path)
+    } else {
+        let document =
+            roxmltree::Document::parse(&content).map_err(|e| format!("{}: {e}", path.display()))?;
+        xml_tileset(document.root_element(), first_gid, path)
+    }
+}
+
+fn xml_tileset(tileset: Node, first_gid: u32, path: &Path) -> Result<Tileset, String> {
+    let image = tileset
+        .children()
+        .find(|n| n.has_tag_name("image"))
+        .ok_or_else(|| "Only tilesets based on a single image are supported".to_string())?;
+
+    let tiles = tileset
+        .children()
+        .filter(|n| n.has_tag_name("tile"))
+        .map(|tile| {
+            Ok(TileInfo {
+                id: xml_usize(tile, "id")? as u32,
+                class: xml_class(tile),
+                properties: xml_properties(tile)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    check_tile_size(
+        xml_usize(tileset, "tilewidth")?,
+        xml_usize(tileset, "tileheight")?,
+    )?;
+
+    Ok(Tileset {
+        first_gid,
+        image: relative_to(path, xml_attribute(image, "source")?),
+        transparent_colour: image.attribute("trans").map(parse_colour).transpose()?,
+        columns: xml_usize(tileset, "columns")?,
+        tile_count: xml_usize(tileset, "tilecount")?,
+        margin: xml_usize_or(tileset, "margin", 0)?,
+        spacing: xml_usize_or(tileset, "spacing", 0)?,
+        tiles,
+    })
+}
+
+fn json_tileset(tileset: &Value, first_gid: u32, path: &Path) -> Result<Tileset, String> {
+    let image = tileset["image"]
+        .as_str()
+        .ok_or_else(|| "Only tilesets based on a single image are supported".to_string())?;
+
+    let tiles = match tileset.get("tiles") {
+        Some(_) => json_array(tileset, "tiles")?
+            .iter()
+            .map(|tile| {
+                Ok(TileInfo {
+                    id: json_usize(tile, "id")? as u32,
+                    class: json_class(tile),
+                    properties: json_properties(tile)?,
+                })
+            })
+            .collect::<Result<_, String>>()?,
+        None => vec![],
+    };
+
+    check_tile_size(
+        json_usize(tileset, "tilewidth")?,
+        json_usize(tileset, "tileheight")?,
+    )?;
+
+    Ok(Tileset {
+        first_gid,
+        image: relative_to(path, image),
+        transparent_colour: tileset["transparentcolor"]
+            .as_str()
+            .map(parse_colour)
+            .transpose()?,
+        columns: json_usize(tileset, "columns")?,
+        tile_count: json_usize(tileset, "tilecount")?,
+        margin: tileset["margin"].as_u64().unwrap_or(0) as usize,
+        spacing: tileset["spacing"].as_u64().unwrap_or(0) as usize,
+        tiles,
+    })
+}
+
+fn check_tile_size(tile_width: usize, tile_height: usize) -> Result<(), String> {
+    if tile_width != 8 || tile_height != 8 {
+        return Err(format!(
+            "Tilesets must use 8x8 tiles, got {tile_width}x{tile_height}"
+        ));
+    }
+
+    Ok(())
+}
+
+fn xml_layers(parent: Node, num_tiles: usize, layers: &mut Vec<Layer>) -> Result<(), String> {
+    for node in parent.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "layer" => {
+                let name = xml_attribute(node, "name")?.to_string();
+                let data = node
+                    .children()
+                    .find(|n| n.has_tag_name("data"))
+                    .ok_or_else(|| format!("Layer `{name}` has no data"))?;
+
+                let raw_tiles = match data.attribute("encoding") {
+                    _ if data.attribute("compression").is_some() => {
+                        return Err(format!(
+                            "Layer `{name}` is compressed which isn't supported, use csv or uncompressed base64"
+                        ));
+                    }
+                    Some("csv") => parse_csv_tiles(data.text().unwrap_or(""))?,
+                    Some("base64") => decode_base64_tiles(data.text().unwrap_or(""))?,
+                    Some(encoding) => {
+                        return Err(format!("Layer `{name}` has unknown encoding {encoding}"))
+                    }
+                    None => data
+                        .children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|tile| xml_usize_or(tile, "gid", 0).map(|gid| gid as u32))
+                        .collect::<Result<_, _>>()?,
+                };
+
+                layers.push(Layer::Tiles(tile_layer(
+                    name,
+                    &raw_tiles,
+                    num_tiles,
+                    xml_properties(node)?,
+                )?));
+            }
+            "objectgroup" => {
+                let objects = node
+                    .children()
+                    .filter(|n| n.has_tag_name("object"))
+                    .map(|object| {
+                        Ok(Object {
+                            id: xml_usize(object, "id")? as u32,
+                            name: object.attribute("name").unwrap_or("").to_string(),
+                            class: xml_class(object),
+                            x: xml_f64_or(object, "x", 0.0)?,
+                            y: xml_f64_or(object, "y", 0.0)?,
+                            width: xml_f64_or(object, "width", 0.0)?,
+                            height: xml_f64_or(object, "height", 0.0)?,
+                            tile: object
+                                .attribute("gid")
+                                .map(|gid| parse_u32(gid).and_then(MapTile::from_raw))
+                                .transpose()?,
+                            properties: xml_properties(object)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                layers.push(Layer::Objects(ObjectLayer {
+                    name: xml_attribute(node, "name")?.to_string(),
+                    objects,
+                    properties: xml_properties(node)?,
+                }));
+            }
+            "group" => xml_layers(node, num_tiles, layers)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn json_layers(json: &[Value], num_tiles: usize, layers: &mut Vec<Layer>) -> Result<(), String> {
+    for layer in json {
+        let name = layer["name"].as_str().unwrap_or("").to_string();
+
+        match layer["type"].as_str() {
+            Some("tilelayer") => {
+                if !layer["compression"].as_str().unwrap_or("").is_empty() {
+                    return Err(format!(
+                        "Layer `{name}` is compressed which isn't supported, use csv or uncompressed base64"
+                    ));
+                }
+
+                let raw_tiles = match &layer["data"] {
+                    Value::String(data) => decode_base64_tiles(data)?,
+                    Value::Array(data) => data
+                        .iter()
+                        .map(|gid| {
+                            gid.as_u64()
+                                .map(|gid| gid as u32)
+                                .ok_or_else(|| format!("Invalid tile in layer `{name}`"))
+                        })
+                        .collect::<Result<_, _>>()?,
+                    _ => return Err(format!("Layer `{name}` has no data")),
+                };
+
+                let properties = json_properties(layer)?;
+                layers.push(Layer::Tiles(tile_layer(
+                    name, &raw_tiles, num_tiles, properties,
+                )?));
+            }
+            Some("objectgroup") => {
+                let objects = json_array(layer, "objects")?
+                    .iter()
+                    .map(|object| {
+                        Ok(Object {
+                            id: json_usize(object, "id")? as u32,
+                            name: object["name"].as_str().unwrap_or("").to_string(),
+                            class: json_class(object),
+                            x: object["x"].as_f64().unwrap_or(0.0),
+                            y: object["y"].as_f64().unwrap_or(0.0),
+                            width: object["width"].as_f64().unwrap_or(0.0),
+                            height: object["height"].as_f64().unwrap_or(0.0),
+                            tile: object["gid"]
+                                .as_u64()
+                                .map(|gid| MapTile::from_raw(gid as u32))
+                                .transpose()?,
+                            properties: json_properties(object)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    objects,
+                    properties: json_properties(layer)?,
+                }));
+            }
+            Some("group") => json_layers(json_array(layer, "layers")?, num_tiles, layers)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn tile_layer(
+    name: String,
+    raw_tiles: &[u32],
+    num_tiles: usize,
+    properties: Vec<Property>,
+) -> Result<TileLayer, String> {
+    if raw_tiles.len() != num_tiles {
+        return Err(format!(
+            "Layer `{name}` has {} tiles but the map has {num_tiles}",
+            raw_tiles.len()
+        ));
+    }
+
+    let tiles = raw_tiles
+        .iter()
+        .map(|&raw| match raw {
+            0 => Ok(MapTile::EMPTY),
+            raw => MapTile::from_raw(raw),
+        })
+        .collect::<Result<_, String>>()
+        .map_err(|e| format!("Layer `{name}`: {e}"))?;
+
+    Ok(TileLayer {
+        name,
+        tiles,
+        properties,
+    })
+}
+
+fn xml_properties(node: Node) -> Result<Vec<Property>, String> {
+    let Some(properties) = node.children().find(|n| n.has_tag_name("properties")) else {
+        return Ok(vec![]);
+    };
+
+    properties
+        .children()
+        .filter(|n| n.has_tag_name("property"))
+        .map(|property| {
+            let name = xml_attribute(property, "name")?.to_string();
+            let value = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or("");
+
+            let value = match property.attribute("type").unwrap_or("string") {
+                "class" => PropertyValue::Class(xml_properties(property)?),
+                kind => parse_property(kind, value).map_err(|e| format!("{name}: {e}"))?,
+            };
+
+            Ok(Property { name, value })
+        })
+        .collect()
+}
+
+fn json_properties(json: &Value) -> Result<Vec<Property>, String> {
+    let Some(properties) = json.get("properties").and_then(Value::as_array) else {
+        return Ok(vec![]);
+    };
+
+    properties
+        .iter()
+        .map(|property| {
+            let name = property["name"].as_str().unwrap_or("").to_string();
+            let value = &property["value"];
+
+            let value = match property["type"].as_str().unwrap_or("string") {
+                "class" => PropertyValue::Class(json_class_value(value)?),
+                kind => {
+                    let value = match value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    parse_property(kind, &value).map_err(|e| format!("{name}: {e}"))?
+                }
+            };
+
+            Ok(Property { name, value })
+        })
+        .collect()
+}
+
+/// Class properties in json are stored as an object of member name to value, without
+/// any type information so the types are guessed from the json values.
+fn json_class_value(value: &Value) -> Result<Vec<Property>, String> {
+    let Some(members) = value.as_object() else {
+        return Ok(vec![]);
+    };
+
+    members
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::Bool(value) => PropertyValue::Bool(*value),
+                Value::Number(value) if value.is_i64() => {
+                    PropertyValue::Int(value.as_i64().unwrap() as i32)
+                }
+                Value::Number(value) => PropertyValue::Float(value.as_f64().unwrap_or(0.0)),
+                Value::String(value) => PropertyValue::String(value.clone()),
+                Value::Object(_) => PropertyValue::Class(json_class_value(value)?),
+                _ => return Err(format!("Unsupported value for class member {name}")),
+            };
+
+            Ok(Property {
+                name: name.clone(),
+                value,
+            })
+        })
+        .collect()
+}
+
+fn parse_property(kind: &str, value: &str) -> Result<PropertyValue, String> {
+    Ok(match kind {
+        "bool" => PropertyValue::Bool(value == "true"),
+        "int" => PropertyValue::Int(
+            value
+                .parse()
+                .map_err(|_| format!("Invalid integer {value}"))?,
+        ),
+        "float" => PropertyValue::Float(
+            value
+                .parse()
+                .map_err(|_| format!("Invalid float {value}"))?,
+        ),
+        "color" if value.is_empty() => PropertyValue::Colour(Colour::from_rgb(0, 0, 0, 0)),
+        "color" => PropertyValue::Colour(parse_colour(value)?),
+        "object" => PropertyValue::Object(parse_u32(value)?),
+        "string" | "file" => PropertyValue::String(value.to_string()),
+        kind => return Err(format!("Unsupported property type {kind}")),
+    })
+}
+
+/// Parses a colour in Tiled's `#AARRGGBB` or `#RRGGBB` format
+pub(crate) fn parse_colour(colour: &str) -> Result<Colour, String> {
+    let colour = colour.trim_start_matches('#');
+    let component = |start: usize| {
+        u8::from_str_radix(&colour[start..start + 2], 16)
+            .map_err(|_| format!("Invalid colour {colour}"))
+    };
+
+    match colour.len() {
+        6 => Ok(Colour::from_rgb(
+            component(0)?,
+            component(2)?,
+            component(4)?,
+            255,
+        )),
+        8 => Ok(Colour::from_rgb(
+            component(2)?,
+            component(4)?,
+            component(6)?,
+            component(0)?,
+        )),
+        _ => Err(format!("Invalid colour {colour}")),
+    }
+}
+
+fn parse_csv_tiles(data: &str) -> Result<Vec<u32>, String> {
+    data.split(',')
+        .map(str::trim)
+        .filter(|gid| !gid.is_empty())
+        .map(parse_u32)
+        .collect()
+}
+
+fn decode_base64_tiles(data: &str) -> Result<Vec<u32>, String> {
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in data.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return Err("Invalid base64 tile data".to_string()),
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    if bytes.len() % 4 != 0 {
+        return Err("Base64 tile data is not a whole number of tiles".to_string());
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|gid| u32::from_le_bytes(gid.try_into().unwrap()))
+        .collect())
+}
+
+fn xml_class(node: Node) -> String {
+    node.attribute("class")
+        .or_else(|| node.attribute("type"))
+        .unwrap_or("")
+        .to_string()
+}
+
+fn json_class(json: &Value) -> String {
+    json["class"]
+        .as_str()
+        .or_else(|| json["type"].as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn xml_attribute<'a>(node: Node<'a, '_>, name: &str) -> Result<&'a str, String> {
+    node.attribute(name)
+        .ok_or_else(|| format!("Missing attribute `{name}` on `{}`", node.tag_name().name()))
+}
+
+fn xml_usize(node: Node, name: &str) -> Result<usize, String> {
+    xml_attribute(node, name)?
+        .parse()
+        .map_err(|_| format!("Attribute `{name}` must be a positive integer"))
+}
+
+fn xml_usize_or(node: Node, name: &str, default: usize) -> Result<usize, String> {
+    node.attribute(name)
+        .map_or(Ok(default), |_| xml_usize(node, name))
+}
+
+fn xml_f64_or(node: Node, name: &str, default: f64) -> Result<f64, String> {
+    node.attribute(name).map_or(Ok(default), |value| {
+        value
+            .parse()
+            .map_err(|_| format!("Attribute `{name}` must be a number"))
+    })
+}
+
+pub(crate) fn json_usize(json: &Value, name: &str) -> Result<usize, String> {
+    json[name]
+        .as_u64()
+        .map(|value| value as usize)
+        .ok_or_else(|| format!("Missing or invalid field `{name}`"))
+}
+
+pub(crate) fn json_array<'a>(json: &'a Value, name: &str) -> Result<&'a [Value], String> {
+    json[name]
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| format!("Missing or invalid field `{name}`"))
+}
+
+fn parse_u32(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid tile id {value}"))
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+pub(crate) fn relative_to(file: &Path, relative: &str) -> PathBuf {
+    file.parent().unwrap_or(Path::new("")).join(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="8" tileheight="8" infinite="0">
+ <properties>
+  <property name="music" value="town"/>
+ </properties>
+ <tileset firstgid="1" name="tiles" tilewidth="8" tileheight="8" tilecount="4" columns="2">
+  <image source="tiles.png" width="16" height="16"/>
+  <tile id="1" type="wall">
+   <properties>
+    <property name="solid" type="bool" value="true"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,2,
+2147483651,0
+</data>
+ </layer>
+ <objectgroup id="2" name="spawns">
+  <object id="1" name="player" type="spawn" x="4" y="12.5">
+   <properties>
+    <property name="health" type="int" value="3"/>
+   </properties>
+  </object>
+ </objectgroup>
+</map>
+"#;
+
+    #[test]
+    fn loads_tmx() {
+        let document = roxmltree::Document::parse(TMX).unwrap();
+        let map = TiledMap::from_xml(document.root_element(), Path::new("maps/level.tmx")).unwrap();
+
+        assert_eq!((map.width, map.height), (2, 2));
+        assert_eq!(map.tilesets[0].image, Path::new("maps/tiles.png"));
+        assert_eq!(map.tilesets[0].tiles[0].class, "wall");
+        assert_eq!(
+            map.properties,
+            [Property {
+                name: "music".to_string(),
+                value: PropertyValue::String("town".to_string())
+            }]
+        );
+
+        let Layer::Tiles(ground) = &map.layers[0] else {
+            panic!("expected a tile layer");
+        };
+        assert_eq!(
+            ground.tiles[2],
+            MapTile {
+                gid: 3,
+                hflip: true,
+                vflip: false
+            }
+        );
+
+        let Layer::Objects(spawns) = &map.layers[1] else {
+            panic!("expected an object layer");
+        };
+        assert_eq!(spawns.objects[0].class, "spawn");
+        assert_eq!(spawns.objects[0].y, 12.5);
+        assert_eq!(map.tile_index(3), Some(2));
+    }
+
+    #[test]
+    fn loads_tmj() {
+        let json: Value = serde_json::from_str(
+            r##"{
+                "orientation": "orthogonal", "infinite": false, "width": 2, "height": 1,
+                "tilewidth": 8, "tileheight": 8,
+                "tilesets": [{
+                    "firstgid": 1, "image": "tiles.png", "tilewidth": 8, "tileheight": 8,
+                    "columns": 2, "tilecount": 2, "transparentcolor": "#ff00ff"
+                }],
+                "layers": [{
+                    "type": "group", "name": "group", "layers": [
+                        { "type": "tilelayer", "name": "ground", "data": "AQAAAAIAAEA=", "encoding": "base64" }
+                    ]
+                }]
+            }"##,
+        )
+        .unwrap();
+
+        let map = TiledMap::from_json(&json, Path::new("level.tmj")).unwrap();
+        assert_eq!(
+            map.tilesets[0].transparent_colour,
+            Some(Colour::from_rgb(255, 0, 255, 255))
+        );
+
+        let Layer::Tiles(ground) = &map.layers[0] else {
+            panic!("expected a tile layer");
+        };
+        assert_eq!(
+            ground.tiles,
+            [
+                MapTile {
+                    gid: 1,
+                    hflip: false,
+                    vflip: false
+                },
+                MapTile {
+                    gid: 2,
+                    hflip: false,
+                    vflip: true
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_rotated_tiles() {
+        assert!(MapTile::from_raw(FLIPPED_DIAGONALLY | 1).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "can only have 1024 tiles")]
+    fn rejects_too_many_tiles() {
+        assert_eq!(tile_id(MAX_TILES - 1), 1023);
+        tile_id(MAX_TILES);
+    }
+}
@@ -9,7 +9,7 @@ use agb::{
             RegularBackgroundSize, RegularBackgroundTiles, TileFormat, TileSet, TileSetting,
             VRAM_MANAGER,
         },
-        HEIGHT, WIDTH,
+        Rgb15, HEIGHT, WIDTH,
     },
     input::Button,
 };
@@ -256,8 +256,11 @@ fn handle_collision(
 
 // Below is the data for the sprites
 
-static CHICKEN_PALETTE: Palette16 =
-    Palette16::new([0x7C1E, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+static CHICKEN_PALETTE: Palette16 = {
+    let mut colours = [Rgb15::BLACK; 16];
+    colours[0] = Rgb15(0x7C1E);
+    Palette16::new(colours)
+};
 
 static CHICKEN_SPRITES: &[Sprite] = unsafe {
     &[
@@ -444,4 +447,4 @@ static MAP_MAP: [u16; 1024] = [
     0x0000, 0x0000, 0x0000, 0x0000,
 ];
 
-static MAP_PALETTE: [u16; 2] = [0x0000, 0x6A2F];
+static MAP_PALETTE: [Rgb15; 2] = [Rgb15(0x0000), Rgb15(0x6A2F)];
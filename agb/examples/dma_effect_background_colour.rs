@@ -9,6 +9,7 @@ use agb::{
     display::{
         example_logo,
         tiled::{RegularBackgroundSize, RegularBackgroundTiles, TileFormat, VRAM_MANAGER},
+        Rgb15,
     },
     interrupt::VBlank,
 };
@@ -28,11 +29,13 @@ fn main(mut gba: agb::Gba) -> ! {
 
     let vblank = VBlank::get();
 
-    let colours: Box<[_]> = (0..160).map(|i| ((i * 0xffff) / 160) as u16).collect();
+    let colours: Box<[_]> = (0..160)
+        .map(|i| Rgb15(((i * 0xffff) / 160) as u16))
+        .collect();
 
     let mut dma = gba.dma.dma().dma0;
 
-    let background_colour = 0x732b; // generated using `https://agbrs.dev/colour`
+    let background_colour = Rgb15(0x732b); // generated using `https://agbrs.dev/colour`
     let background_colour_index = VRAM_MANAGER
         .find_colour_index_16(0, background_colour)
         .expect("Should contain colour 0x732b");
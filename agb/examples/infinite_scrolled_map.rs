@@ -11,7 +11,7 @@ use agb::{
 };
 use agb_fixnum::vec2;
 
-include_background_gfx!(big_map, "2ce8f4", big_map => deduplicate "examples/big_map.png");
+include_background_gfx!(big_map, "2ce8f4", big_map => map "examples/big_map.png");
 
 #[agb::entry]
 fn main(mut gba: agb::Gba) -> ! {
@@ -39,11 +39,8 @@ fn main(mut gba: agb::Gba) -> ! {
         current_pos += input.vector();
 
         infinite_scrolled.set_pos(current_pos, |p| {
-            (
-                &big_map::big_map.tiles,
-                big_map::big_map.tile_settings
-                    [p.x.rem_euclid(60) as usize + p.y.rem_euclid(40) as usize * 60],
-            )
+            let size = big_map::big_map.size();
+            big_map::big_map.tile((p.x.rem_euclid(size.x), p.y.rem_euclid(size.y)))
         });
 
         let mut bg_iter = gfx.iter();
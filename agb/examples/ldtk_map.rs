@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        tiled::{
+            MapLevel, NeighbourDirection, RegularBackgroundSize, RegularBackgroundTiles,
+            VRAM_MANAGER,
+        },
+        Priority,
+    },
+    include_ldtk,
+    input::ButtonController,
+};
+
+include_ldtk!(rooms, "2ce8f4", "examples/maps/rooms.ldtk");
+
+fn draw_level(bg: &mut RegularBackgroundTiles, level: &MapLevel) {
+    // levels can be different sizes, so don't leave any of the last one behind
+    bg.clear();
+
+    let layer = level.layer("Collision").unwrap();
+
+    for y in 0..layer.height() as i32 {
+        for x in 0..layer.width() as i32 {
+            bg.set_tile((x, y), layer.tileset(), layer.tile_setting((x, y)));
+        }
+    }
+}
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let vblank = agb::interrupt::VBlank::get();
+
+    let mut input = ButtonController::new();
+
+    VRAM_MANAGER.set_background_palettes(rooms::PALETTES);
+
+    let mut bg = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        rooms::TILES.tiles.format(),
+    );
+
+    let mut level = &rooms::LEVEL_0;
+    draw_level(&mut bg, level);
+
+    let player = level
+        .entity_layer("Entities")
+        .unwrap()
+        .objects_with_class("Player")
+        .next()
+        .unwrap();
+    let mut position = player.position();
+
+    loop {
+        input.update();
+
+        // Walking off the edge of the level moves to the neighbouring one
+        let new_position = position + input.vector();
+        if new_position.x >= level.size().x {
+            if let Some(next) = level.neighbours_in(NeighbourDirection::East).next() {
+                level = next;
+                position.x = 0;
+                draw_level(&mut bg, level);
+            }
+        } else if new_position.x < 0 {
+            if let Some(next) = level.neighbours_in(NeighbourDirection::West).next() {
+                level = next;
+                position.x = next.size().x - 1;
+                draw_level(&mut bg, level);
+            }
+        } else if level
+            .int_grid("Collision")
+            .unwrap()
+            .value_at_pixel(new_position)
+            == 0
+        {
+            position = new_position;
+        }
+
+        let mut bg_iter = gfx.iter();
+        bg.show(&mut bg_iter);
+
+        vblank.wait_for_vblank();
+        bg.commit();
+        bg_iter.commit();
+    }
+}
@@ -0,0 +1,108 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        tiled::{
+            DynamicTile, RegularBackgroundSize, RegularBackgroundTiles, TileFormat, VRAM_MANAGER,
+        },
+        Font, Priority, Rgb15,
+    },
+    include_font, include_strings,
+    input::{Button, ButtonController},
+};
+
+use core::fmt::Write;
+
+include_strings!(strings, "examples/strings/strings.csv");
+
+// Only the glyphs used by the string table end up in the ROM
+static FONT: Font = include_font!(
+    "examples/font/ark-pixel-10px-proportional-ja.ttf",
+    10,
+    "examples/strings/strings.csv"
+);
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let vblank = agb::interrupt::VBlank::get();
+    let mut input = ButtonController::new();
+
+    VRAM_MANAGER.set_background_palette_raw(
+        &[
+            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]
+        .map(Rgb15),
+    );
+
+    let background_tile = DynamicTile::new().fill_with(0);
+
+    let mut bg = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        TileFormat::FourBpp,
+    );
+
+    let languages = [
+        strings::languages::EN,
+        strings::languages::FR,
+        strings::languages::JA,
+    ];
+    let mut language = 0;
+    let mut apples = 0;
+
+    loop {
+        input.update();
+
+        if input.is_just_pressed(Button::A) {
+            language = (language + 1) % languages.len();
+            strings::TABLE.set_language(languages[language]);
+        }
+
+        if input.is_just_pressed(Button::UP) {
+            apples += 1;
+        } else if input.is_just_pressed(Button::DOWN) && apples > 0 {
+            apples -= 1;
+        }
+
+        for y in 0..20u16 {
+            for x in 0..30u16 {
+                bg.set_tile(
+                    (x, y),
+                    &background_tile.tile_set(),
+                    background_tile.tile_setting(),
+                );
+            }
+        }
+
+        let mut renderer = FONT.render_text((0u16, 3u16));
+        let mut writer = renderer.writer(1, 2, &mut bg);
+
+        let table = &strings::TABLE;
+        writeln!(&mut writer, "{}", table.get(strings::keys::TITLE)).unwrap();
+        writeln!(
+            &mut writer,
+            "{}",
+            table.format(strings::keys::GREETING, &["agb".into()])
+        )
+        .unwrap();
+        writeln!(
+            &mut writer,
+            "{}",
+            table.format(strings::keys::APPLES, &[apples.into()])
+        )
+        .unwrap();
+        writeln!(&mut writer, "{}", table.get(strings::keys::CHANGE_LANGUAGE)).unwrap();
+
+        writer.commit();
+
+        let mut bg_iter = gfx.iter();
+        bg.commit();
+        bg.show(&mut bg_iter);
+
+        vblank.wait_for_vblank();
+        bg_iter.commit();
+    }
+}
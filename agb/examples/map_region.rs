@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        tiled::{RegularBackgroundSize, RegularBackgroundTiles, VRAM_MANAGER},
+        Priority,
+    },
+    fixnum::{vec2, Rect, Vector2D},
+    include_background_gfx,
+    input::ButtonController,
+};
+
+include_background_gfx!(big_map, "2ce8f4", big_map => map "examples/big_map.png");
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let vblank = agb::interrupt::VBlank::get();
+
+    let mut input = ButtonController::new();
+
+    let map = &big_map::big_map;
+
+    VRAM_MANAGER.set_background_palettes(big_map::PALETTES);
+
+    let mut bg = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        map.tiles.format(),
+    );
+
+    // Show one screen's worth of the map at a time, moving a whole screen with the d-pad
+    let screen_size = vec2(30, 20);
+    let mut screen = vec2(0, 0);
+    map.blit_to(&mut bg, Rect::new(screen, screen_size), (0, 0));
+
+    loop {
+        input.update();
+
+        let direction: Vector2D<i32> = input.just_pressed_vector();
+        let new_screen = screen + vec2(direction.x * screen_size.x, direction.y * screen_size.y);
+        if new_screen != screen
+            && Rect::new(vec2(0, 0), map.size() - screen_size).contains_point(new_screen)
+        {
+            screen = new_screen;
+            map.blit_to(&mut bg, Rect::new(screen, screen_size), (0, 0));
+        }
+
+        let mut bg_iter = gfx.iter();
+        bg.show(&mut bg_iter);
+
+        vblank.wait_for_vblank();
+        bg.commit();
+        bg_iter.commit();
+    }
+}
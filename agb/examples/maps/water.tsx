@@ -0,0 +1,9 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="water" tilewidth="8" tileheight="8" tilecount="8" columns="8">
+ <image source="../water_tiles.png" width="64" height="8"/>
+ <tile id="3" type="wall">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
+</tileset>
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::object::{Graphics, MetaObject, MetaSprite, MetaSpritePart, Tag},
+    include_aseprite,
+    input::{Button, ButtonController},
+};
+
+static GRAPHICS: &Graphics = include_aseprite!("examples/gfx/boss.aseprite");
+static BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+// Three copies of the boss stacked on top of each other, with the middle one
+// shifted to the right
+static TOWER: MetaSprite = MetaSprite::new(&[
+    MetaSpritePart::new(BOSS.sprite(0), 0, 0),
+    MetaSpritePart::new(BOSS.sprite(0), 16, 32),
+    MetaSpritePart::new(BOSS.sprite(0), 0, 64),
+]);
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let (mut oam, mut loader) = gba.display.object.get_unmanaged();
+    let vblank = agb::interrupt::VBlank::get();
+
+    let mut input = ButtonController::new();
+
+    let mut tower = MetaObject::new(&TOWER, &mut loader);
+    tower.set_position((100, 40)).show();
+
+    loop {
+        input.update();
+
+        // The parts swap places when the whole thing is flipped
+        if input.is_just_pressed(Button::A) {
+            tower.set_hflip(!tower.hflip());
+        }
+        if input.is_just_pressed(Button::B) {
+            tower.set_vflip(!tower.vflip());
+        }
+
+        tower.set_position(tower.position() + input.vector());
+
+        vblank.wait_for_vblank();
+        tower.write(&mut oam.iter());
+    }
+}
@@ -0,0 +1,79 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        tiled::{
+            InfiniteScrolledMap, MetatileSet, MetatileSize, MetatileSource, RegularBackgroundSize,
+            RegularBackgroundTiles, VRAM_MANAGER,
+        },
+        Priority,
+    },
+    fixnum::{vec2, Vector2D},
+    include_background_gfx,
+    input::ButtonController,
+};
+
+include_background_gfx!(big_map, "2ce8f4", big_map => map "examples/big_map.png");
+
+// Every 32x32 block of the big map becomes a metatile, with the first row marked as solid
+static METATILES: MetatileSet =
+    MetatileSet::new(&big_map::big_map, MetatileSize::Size32x32, &[1; 15]);
+
+/// A world which picks a metatile for each position as it is needed rather than storing them
+struct Patchwork;
+
+impl MetatileSource for Patchwork {
+    fn metatile_set(&self) -> &'static MetatileSet {
+        &METATILES
+    }
+
+    fn metatile(&self, pos: Vector2D<i32>) -> Option<u16> {
+        // Make sure the camera doesn't start on a solid metatile
+        if pos == vec2(3, 2) {
+            return Some(20);
+        }
+
+        let hash =
+            (pos.x as u32).wrapping_mul(0x9e37_79b9) ^ (pos.y as u32).wrapping_mul(0x85eb_ca6b);
+        Some((hash >> 16) as u16 % METATILES.len() as u16)
+    }
+}
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let vblank = agb::interrupt::VBlank::get();
+
+    let mut input = ButtonController::new();
+
+    VRAM_MANAGER.set_background_palettes(big_map::PALETTES);
+
+    let bg = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        METATILES.tileset().format(),
+    );
+
+    let mut infinite_scrolled = InfiniteScrolledMap::new(bg);
+    let mut current_pos = vec2(0, 0);
+
+    loop {
+        input.update();
+
+        // Don't allow the camera to move onto solid metatiles
+        let new_pos = current_pos + input.vector();
+        if Patchwork.attributes_at_pixel(new_pos + vec2(120, 80)) == 0 {
+            current_pos = new_pos;
+        }
+
+        infinite_scrolled.set_pos_metatiles(current_pos, &Patchwork);
+
+        let mut bg_iter = gfx.iter();
+        infinite_scrolled.show(&mut bg_iter);
+
+        vblank.wait_for_vblank();
+        infinite_scrolled.commit();
+        bg_iter.commit();
+    }
+}
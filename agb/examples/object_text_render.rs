@@ -5,7 +5,7 @@ use agb::{
     display::{
         object::{ChangeColour, ObjectTextRender, PaletteVram, Size, TextAlignment},
         palette16::Palette16,
-        Font, HEIGHT, WIDTH,
+        Font, Rgb15, HEIGHT, WIDTH,
     },
     include_font,
     input::Button,
@@ -25,9 +25,9 @@ fn entry(gba: agb::Gba) -> ! {
 fn main(mut gba: agb::Gba) -> ! {
     let (mut unmanaged, _sprites) = gba.display.object.get_unmanaged();
 
-    let mut palette = [0x0; 16];
-    palette[1] = 0xFF_FF;
-    palette[2] = 0x00_FF;
+    let mut palette = [Rgb15::BLACK; 16];
+    palette[1] = Rgb15::WHITE;
+    palette[2] = Rgb15(0x00_FF);
     let palette = Palette16::new(palette);
     let palette = PaletteVram::new(&palette).unwrap();
 
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        example_logo,
+        palette_effects::{ColourTransform, PaletteEffects, PaletteTarget},
+        tiled::{RegularBackgroundSize, RegularBackgroundTiles, TileFormat},
+        Rgb15,
+    },
+    input::{Button, ButtonController},
+    interrupt::VBlank,
+};
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+
+    let mut map = RegularBackgroundTiles::new(
+        agb::display::Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        TileFormat::FourBpp,
+    );
+
+    example_logo::display_logo_basic(&mut map);
+    map.commit();
+
+    let vblank = VBlank::get();
+    let mut input = ButtonController::new();
+
+    let mut effects = PaletteEffects::new();
+    effects.add_cycle(PaletteTarget::Background, 1..16, 6);
+    effects.fade_from_colour(PaletteTarget::Background, Rgb15::BLACK, 60);
+
+    let mut greyscale = false;
+
+    loop {
+        input.update();
+
+        // A fades to white and back, B toggles greyscale
+        if input.is_just_pressed(Button::A) {
+            effects.fade_to_colour(PaletteTarget::Background, Rgb15::WHITE, 30);
+        } else if input.is_just_released(Button::A) {
+            effects.fade_from_colour(PaletteTarget::Background, Rgb15::WHITE, 30);
+        }
+
+        if input.is_just_pressed(Button::B) {
+            greyscale = !greyscale;
+            effects.set_transform(
+                PaletteTarget::Background,
+                ColourTransform::new().with_greyscale(greyscale),
+            );
+        }
+
+        effects.update();
+
+        vblank.wait_for_vblank();
+        effects.commit();
+
+        let mut bg_iter = gfx.iter();
+        map.show(&mut bg_iter);
+        bg_iter.commit();
+    }
+}
@@ -6,7 +6,7 @@ use agb::{
         tiled::{
             DynamicTile, RegularBackgroundSize, RegularBackgroundTiles, TileFormat, VRAM_MANAGER,
         },
-        Font, Priority,
+        Font, Priority, Rgb15,
     },
     include_font,
 };
@@ -20,10 +20,13 @@ fn main(mut gba: agb::Gba) -> ! {
     let mut gfx = gba.display.video.tiled();
     let vblank = agb::interrupt::VBlank::get();
 
-    VRAM_MANAGER.set_background_palette_raw(&[
-        0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
-        0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
-    ]);
+    VRAM_MANAGER.set_background_palette_raw(
+        &[
+            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]
+        .map(Rgb15),
+    );
 
     let background_tile = DynamicTile::new().fill_with(0);
 
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        tiled::{InfiniteScrolledMap, RegularBackgroundSize, RegularBackgroundTiles, VRAM_MANAGER},
+        Priority,
+    },
+    include_tiled_map,
+    input::ButtonController,
+};
+
+include_tiled_map!(water, "2ce8f4", "examples/maps/water.tmx");
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let vblank = agb::interrupt::VBlank::get();
+
+    let mut input = ButtonController::new();
+
+    VRAM_MANAGER.set_background_palettes(water::PALETTES);
+
+    let bg = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        water::TILES.tiles.format(),
+    );
+
+    let mut infinite_scrolled = InfiniteScrolledMap::new(bg);
+
+    let player = water::SPAWNS.object("player").unwrap();
+    let mut current_pos = player.position();
+
+    loop {
+        input.update();
+
+        // Don't allow the camera to move onto any walls
+        let new_pos = current_pos + input.vector();
+        if !water::WATER.tile_properties(new_pos / 8).flag("solid") {
+            current_pos = new_pos;
+        }
+
+        infinite_scrolled.set_pos(current_pos, |p| water::WATER.tile(p));
+
+        let mut bg_iter = gfx.iter();
+        infinite_scrolled.show(&mut bg_iter);
+
+        vblank.wait_for_vblank();
+        infinite_scrolled.commit();
+        bg_iter.commit();
+    }
+}
@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+use agb::{
+    display::{
+        example_logo,
+        tiled::{RegularBackgroundSize, RegularBackgroundTiles, TileFormat},
+        transition::{
+            Fade, FadeColour, IrisWipe, MosaicDissolve, Transition, TransitionDirection,
+            TransitionStatus,
+        },
+        window::WinIn,
+        Priority,
+    },
+    input::{Button, ButtonController},
+    interrupt::VBlank,
+};
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    let mut gfx = gba.display.video.tiled();
+    let mut blend = gba.display.blend.get();
+    let mut windows = gba.display.window.get();
+    let mut dmas = gba.dma.dma();
+
+    let mut map = RegularBackgroundTiles::new(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        TileFormat::FourBpp,
+    );
+    map.set_mosaic(true);
+
+    example_logo::display_logo_basic(&mut map);
+    map.commit();
+
+    let vblank = VBlank::get();
+    let mut input = ButtonController::new();
+
+    let mut show_background = || {
+        let mut bg_iter = gfx.iter();
+        map.show(&mut bg_iter);
+        bg_iter.commit();
+    };
+
+    loop {
+        input.update();
+
+        // A fades out and back in, B does an iris wipe and L dissolves with mosaic
+        if input.is_just_pressed(Button::A) {
+            for direction in [TransitionDirection::Out, TransitionDirection::In] {
+                let mut fade = Fade::new(FadeColour::Black, direction, 30);
+                while fade.update() == TransitionStatus::Running {
+                    vblank.wait_for_vblank();
+                    fade.apply(&mut blend);
+                    show_background();
+                }
+            }
+        }
+
+        if input.is_just_pressed(Button::B) {
+            for direction in [TransitionDirection::Out, TransitionDirection::In] {
+                let mut wipe = IrisWipe::new((120, 80), direction, 40);
+                let mut _transfer = None;
+                while wipe.update() == TransitionStatus::Running {
+                    vblank.wait_for_vblank();
+                    _transfer = None;
+                    _transfer = Some(wipe.apply(&mut windows, &mut dmas.dma0));
+                    show_background();
+                }
+            }
+
+            windows.win_in(WinIn::Win0).disable();
+            windows.win_out().disable();
+            windows.commit();
+        }
+
+        if input.is_just_pressed(Button::L) {
+            for direction in [TransitionDirection::Out, TransitionDirection::In] {
+                let mut dissolve = MosaicDissolve::new(FadeColour::White, direction, 30);
+                while !dissolve.is_finished() {
+                    dissolve.update();
+                    vblank.wait_for_vblank();
+                    dissolve.apply(&mut blend);
+                    show_background();
+                }
+            }
+        }
+
+        vblank.wait_for_vblank();
+        show_background();
+    }
+}
@@ -52,6 +52,14 @@ pub struct BlockAllocator {
     inner: UnsafeCell<BlockAllocatorInner>,
 }
 
+/// How much of the area managed by a [`BlockAllocator`] is in use, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockAllocatorUsage {
+    pub total: usize,
+    pub free: usize,
+    pub largest_free: usize,
+}
+
 unsafe impl Sync for BlockAllocator {}
 
 impl BlockAllocator {
@@ -87,6 +95,22 @@ impl BlockAllocator {
     ) -> Option<NonNull<u8>> {
         self.with_inner(|inner| inner.grow(ptr, layout, new_layout))
     }
+
+    pub unsafe fn usage(&self) -> BlockAllocatorUsage {
+        self.with_inner(|inner| inner.usage())
+    }
+
+    pub unsafe fn reclaim_end(&self) {
+        self.with_inner(|inner| inner.reclaim_end());
+    }
+
+    pub unsafe fn reset(&self) {
+        self.with_inner(|inner| inner.reset());
+    }
+
+    pub unsafe fn extend_start(&self, old_start: usize) {
+        self.with_inner(|inner| inner.extend_start(old_start));
+    }
 }
 
 impl BlockAllocatorInner {
@@ -102,7 +126,99 @@ impl BlockAllocatorInner {
     /// Requests a brand new block from the inner bump allocator
     fn new_block(&mut self, layout: Layout) -> Option<NonNull<u8>> {
         let overall_layout = Block::either_layout(layout);
-        self.inner_allocator.alloc(overall_layout)
+        let tip = self
+            .inner_allocator
+            .tip()
+            .map_or(self.inner_allocator.start(), |tip| tip.as_ptr() as usize);
+
+        let block = self.inner_allocator.alloc(overall_layout)?;
+
+        // free the space skipped to align the block so that it can be used later
+        let gap = block.as_ptr() as usize - tip;
+        if gap != 0 {
+            unsafe { self.dealloc(tip as *mut u8, Layout::from_size_align_unchecked(gap, 8)) };
+        }
+
+        Some(block)
+    }
+
+    fn usage(&self) -> BlockAllocatorUsage {
+        let start = self.inner_allocator.start();
+        let end = self.inner_allocator.end();
+        let tip = self
+            .inner_allocator
+            .tip()
+            .map_or(start, |tip| tip.as_ptr() as usize);
+
+        let mut free = end - tip;
+        let mut largest_free = free;
+
+        let mut block = self.state.first_free_block;
+        while let Some(current_block) = block {
+            let current = unsafe { current_block.as_ref() };
+            free += current.size;
+
+            // a free block right before the tip can be joined with the rest of the space
+            let size = if current_block.as_ptr() as usize + current.size == tip {
+                current.size + end - tip
+            } else {
+                current.size
+            };
+            largest_free = largest_free.max(size);
+
+            block = current.next;
+        }
+
+        BlockAllocatorUsage {
+            total: end - start,
+            free,
+            largest_free,
+        }
+    }
+
+    /// Gives the last free block back to the bump allocator if it ends at the
+    /// tip, so that the space can be used for larger allocations.
+    unsafe fn reclaim_end(&mut self) {
+        let Some(tip) = self.inner_allocator.tip() else {
+            return;
+        };
+
+        let mut list_ptr = &mut self.state.first_free_block;
+
+        while let Some(mut current_block) = *list_ptr {
+            let current = current_block.as_mut();
+
+            if current.next.is_none() {
+                if current_block.as_ptr().cast::<u8>().add(current.size) == tip.as_ptr() {
+                    *list_ptr = None;
+                    self.inner_allocator.set_tip(current_block.0.cast());
+                }
+
+                return;
+            }
+
+            list_ptr = &mut current.next;
+        }
+    }
+
+    /// Forgets about every allocation, so the next one is made at the start.
+    unsafe fn reset(&mut self) {
+        self.state.first_free_block = None;
+        self.inner_allocator.clear_tip();
+    }
+
+    /// Frees the space between the start and `old_start`, for when the start
+    /// has been moved earlier while there are allocations.
+    unsafe fn extend_start(&mut self, old_start: usize) {
+        let start = self.inner_allocator.start();
+        if self.inner_allocator.tip().is_none() || old_start <= start {
+            return;
+        }
+
+        self.dealloc(
+            start as *mut u8,
+            Layout::from_size_align_unchecked(old_start - start, 8),
+        );
     }
 
     /// Merges blocks together to create a normalised list
@@ -159,6 +275,30 @@ impl BlockAllocatorInner {
         let mut examination_block_ptr = reference_to_block_pointer.unwrap().0;
         let examination_block = examination_block_ptr.as_mut();
 
+        // Blocks are only aligned to 8 bytes, so larger alignments can need
+        // a gap at the start of the block which stays free.
+        let address = examination_block_ptr.as_ptr() as usize;
+        let gap = address.next_multiple_of(wanted_layout.align()) - address;
+        let remaining = examination_block.size.checked_sub(gap)?;
+
+        if remaining != wanted_layout.size() && remaining < extended_layout.size() {
+            return None;
+        }
+
+        if gap != 0 {
+            let aligned_block_ptr: *mut Block =
+                examination_block_ptr.as_ptr().cast::<u8>().add(gap).cast();
+            *aligned_block_ptr = Block {
+                size: remaining,
+                next: examination_block.next,
+            };
+
+            examination_block.size = gap;
+            examination_block.next = NonNull::new(aligned_block_ptr).map(SendNonNull);
+
+            return Self::allocate_into_block(&mut examination_block.next, wanted_layout);
+        }
+
         if examination_block.size == wanted_layout.size() {
             *reference_to_block_pointer = examination_block.next;
             Some(examination_block_ptr.cast())
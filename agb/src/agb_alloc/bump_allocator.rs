@@ -25,6 +25,22 @@ impl BumpAllocatorInner {
         self.current_ptr.map(|x| x.0)
     }
 
+    pub fn set_tip(&mut self, tip: NonNull<u8>) {
+        self.current_ptr = Some(SendNonNull(tip));
+    }
+
+    pub fn clear_tip(&mut self) {
+        self.current_ptr = None;
+    }
+
+    pub fn start(&self) -> usize {
+        (self.start_end.start)()
+    }
+
+    pub fn end(&self) -> usize {
+        (self.start_end.end)()
+    }
+
     pub fn alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
         let current_ptr = &mut self.current_ptr;
 
@@ -42,7 +58,7 @@ impl BumpAllocatorInner {
         let resulting_ptr = ptr + amount_to_add;
         let new_current_ptr = resulting_ptr + layout.size();
 
-        if new_current_ptr >= (self.start_end.end)() {
+        if new_current_ptr > (self.start_end.end)() {
             return None;
         }
 
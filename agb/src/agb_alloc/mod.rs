@@ -148,6 +148,7 @@ mod test {
     use alloc::boxed::Box;
     use alloc::vec;
     use alloc::vec::Vec;
+    use core::alloc::Layout;
 
     #[test_case]
     fn test_box(_gba: &mut crate::Gba) {
@@ -284,6 +285,142 @@ mod test {
         }
     }
 
+    const AREA_SIZE: usize = 512;
+
+    static mut AREA: [u8; AREA_SIZE + 64] = [0; AREA_SIZE + 64];
+
+    fn area_start() -> usize {
+        (core::ptr::addr_of_mut!(AREA) as usize).next_multiple_of(64)
+    }
+
+    /// An allocator for a small area of its own, aligned to 64 bytes
+    fn test_allocator() -> BlockAllocator {
+        unsafe {
+            BlockAllocator::new(StartEnd {
+                start: area_start,
+                end: || area_start() + AREA_SIZE,
+            })
+        }
+    }
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test_case]
+    fn freed_blocks_coalesce(_gba: &mut crate::Gba) {
+        let allocator = test_allocator();
+
+        unsafe {
+            let a = allocator.alloc(layout(64, 8)).unwrap();
+            let b = allocator.alloc(layout(64, 8)).unwrap();
+            let c = allocator.alloc(layout(64, 8)).unwrap();
+            let _end = allocator.alloc(layout(8, 8)).unwrap();
+
+            allocator.dealloc(a.as_ptr(), layout(64, 8));
+            allocator.dealloc(c.as_ptr(), layout(64, 8));
+            assert_eq!(allocator.usage().free, AREA_SIZE - 64 - 8);
+            assert_eq!(allocator.usage().largest_free, AREA_SIZE - 3 * 64 - 8);
+
+            // freeing the middle one joins all three together
+            allocator.dealloc(b.as_ptr(), layout(64, 8));
+            assert_eq!(allocator.alloc(layout(3 * 64, 8)), Some(a));
+        }
+    }
+
+    #[test_case]
+    fn alignment_gaps_coalesce_with_their_neighbours(_gba: &mut crate::Gba) {
+        let allocator = test_allocator();
+
+        unsafe {
+            let first = allocator.alloc(layout(8, 8)).unwrap();
+            assert_eq!(first.as_ptr() as usize, area_start());
+
+            let aligned = allocator.alloc(layout(64, 64)).unwrap();
+            assert_eq!(aligned.as_ptr() as usize, area_start() + 64);
+
+            // the gap joins with the space before and after it once both are freed
+            allocator.dealloc(first.as_ptr(), layout(8, 8));
+            allocator.dealloc(aligned.as_ptr(), layout(64, 64));
+
+            let usage = allocator.usage();
+            assert_eq!(usage.free, AREA_SIZE);
+            assert_eq!(usage.largest_free, AREA_SIZE);
+            assert_eq!(allocator.alloc(layout(128, 8)), Some(first));
+        }
+    }
+
+    #[test_case]
+    fn fragmented_space_is_reused(_gba: &mut crate::Gba) {
+        let allocator = test_allocator();
+        let small = layout(32, 8);
+
+        unsafe {
+            let blocks: [_; 8] = core::array::from_fn(|_| allocator.alloc(small).unwrap());
+            for block in blocks.iter().step_by(2) {
+                allocator.dealloc(block.as_ptr(), small);
+            }
+
+            let usage = allocator.usage();
+            assert_eq!(usage.free, AREA_SIZE - 4 * 32);
+            assert_eq!(usage.largest_free, AREA_SIZE - 8 * 32);
+
+            // none of the holes are big enough, so this goes after the others
+            let aligned = allocator.alloc(layout(64, 64)).unwrap();
+            assert_eq!(aligned.as_ptr() as usize, area_start() + 8 * 32);
+
+            // while smaller allocations fill the holes
+            assert_eq!(allocator.alloc(small), Some(blocks[0]));
+            let half = allocator.alloc(layout(16, 8)).unwrap();
+            assert_eq!(half, blocks[2]);
+            assert_eq!(allocator.alloc(layout(16, 8)), Some(blocks[2].add(16)));
+
+            allocator.dealloc(aligned.as_ptr(), layout(64, 64));
+            allocator.dealloc(blocks[0].as_ptr(), small);
+            allocator.dealloc(half.as_ptr(), layout(16, 8));
+            allocator.dealloc(blocks[2].as_ptr().add(16), layout(16, 8));
+            for block in blocks.iter().skip(1).step_by(2) {
+                allocator.dealloc(block.as_ptr(), small);
+            }
+
+            let usage = allocator.usage();
+            assert_eq!(usage.free, AREA_SIZE);
+            assert_eq!(usage.largest_free, AREA_SIZE);
+        }
+    }
+
+    #[test_case]
+    fn aligned_allocations_can_reuse_freed_space(_gba: &mut crate::Gba) {
+        let allocator = test_allocator();
+
+        let small = Layout::from_size_align(8, 8).unwrap();
+        let aligned = Layout::from_size_align(64, 64).unwrap();
+
+        unsafe {
+            let first = allocator.alloc(small).unwrap();
+            let freed = allocator
+                .alloc(Layout::from_size_align(128, 8).unwrap())
+                .unwrap();
+            let _last = allocator.alloc(small).unwrap();
+            allocator.dealloc(freed.as_ptr(), Layout::from_size_align(128, 8).unwrap());
+
+            let reused = allocator.alloc(aligned).unwrap();
+            assert_eq!(reused.as_ptr() as usize % 64, 0);
+            assert!(reused.as_ptr() > first.as_ptr() && reused.as_ptr() < freed.as_ptr().add(128));
+
+            // the space skipped before the aligned allocation is still free
+            assert_eq!(allocator.alloc(small), Some(freed));
+
+            // and so is the space skipped when bump allocating
+            allocator.dealloc(reused.as_ptr(), aligned);
+            let bumped = allocator
+                .alloc(Layout::from_size_align(128, 64).unwrap())
+                .unwrap();
+            assert_eq!(bumped.as_ptr() as usize % 64, 0);
+            assert_eq!(allocator.usage().free, AREA_SIZE - 3 * small.size() - 128,);
+        }
+    }
+
     #[test_case]
     fn growth_works(_gba: &mut crate::Gba) {
         let mut growing_vector = Vec::with_capacity(1);
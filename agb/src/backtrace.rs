@@ -8,7 +8,7 @@ pub struct Frames {
 
 #[inline(never)]
 pub(crate) fn unwind_exception() -> Frames {
-    let mut frame_pointer = unsafe {
+    let frame_pointer = unsafe {
         let mut frame_pointer: u32 = 0;
         let ptr = &mut frame_pointer as *mut _;
         #[cfg(target_feature = "thumb-mode")]
@@ -29,8 +29,34 @@ pub(crate) fn unwind_exception() -> Frames {
     };
 
     let mut frames = Vec::new();
+    walk_frames(frame_pointer, usize::MAX, |address| frames.push(address));
+
+    Frames { frames }
+}
+
+impl Frames {
+    pub(crate) fn new(frames: Vec<u32>) -> Self {
+        Self { frames }
+    }
+}
+
+fn is_valid_frame_pointer(frame_pointer: u32) -> bool {
+    const EWRAM: core::ops::Range<u32> = 0x0200_0000..0x0204_0000;
+    const IWRAM: core::ops::Range<u32> = 0x0300_0000..0x0300_8000;
+
+    frame_pointer & 3 == 0 && (EWRAM.contains(&frame_pointer) || IWRAM.contains(&frame_pointer))
+}
+
+/// Follows the chain of frame pointers starting at `frame_pointer`, calling `f`
+/// with the address that each function was called from. This stops after
+/// `max_frames` frames, or if the frame pointer doesn't point into ram, which
+/// can happen if the code it came from doesn't use frame pointers.
+pub(crate) fn walk_frames(mut frame_pointer: u32, max_frames: usize, mut f: impl FnMut(u32)) {
+    for _ in 0..max_frames {
+        if !is_valid_frame_pointer(frame_pointer) {
+            break;
+        }
 
-    loop {
         let sp = unsafe { *(frame_pointer as *const u32) };
         let lr = unsafe { *((frame_pointer as *const u32).add(1)) };
 
@@ -46,12 +72,10 @@ pub(crate) fn unwind_exception() -> Frames {
         // need to subtract instruction_size here since the link register points
         // to the _next_ instruction to execute, not the one that is being
         // branched from which is the one we care about in the stack trace.
-        frames.push(lr - instruction_size);
+        f(lr.wrapping_sub(instruction_size));
 
         frame_pointer = sp;
     }
-
-    Frames { frames }
 }
 
 impl core::fmt::Display for Frames {
@@ -0,0 +1,162 @@
+//! Where debug messages, such as the ones from [`println!`](crate::println),
+//! panics and tests, get written to.
+//!
+//! Emulators each have their own way for games to write messages. agb detects
+//! which one it is running in the first time a message is written, and uses
+//! [`MgbaOutput`] for mgba, [`NoCashOutput`] for No$GBA or [`NoOutput`]
+//! otherwise, such as on real hardware. You can replace this with your own
+//! [`DebugOutput`] using [`set_output`].
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::{debug_output, mgba::DebugLevel};
+//!
+//! # fn foo() {
+//! let _ = debug_output::print(format_args!("Hello from any emulator"), DebugLevel::Info);
+//! # }
+//! ```
+
+use core::{
+    cell::Cell,
+    fmt::{self, Write},
+};
+
+use critical_section::Mutex;
+
+use crate::{
+    memory_mapped::MemoryMapped,
+    mgba::{DebugLevel, Mgba},
+};
+
+/// Something that debug messages can be written to.
+pub trait DebugOutput: Sync {
+    /// Writes a single message at the given level.
+    fn print(&self, output: fmt::Arguments, level: DebugLevel) -> fmt::Result;
+
+    /// Whether messages written here can be seen by anyone. The
+    /// [`logger`](crate::logger) keeps messages in RAM instead when they can't.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+/// Writes messages to mgba's log, keeping their level. Messages are thrown
+/// away when not running in mgba.
+pub struct MgbaOutput;
+
+impl DebugOutput for MgbaOutput {
+    fn print(&self, output: fmt::Arguments, level: DebugLevel) -> fmt::Result {
+        // mgba only writes to its log once the handshake has been done
+        match Mgba::new() {
+            Some(mut mgba) => mgba.print(output, level),
+            None => Ok(()),
+        }
+    }
+}
+
+const NO_CASH_ID: *const u8 = 0x04FF_FA00 as *const u8;
+const NO_CASH_CHAR_OUT: MemoryMapped<u8> = unsafe { MemoryMapped::new(0x04FF_FA1C) };
+
+fn is_running_in_no_cash() -> bool {
+    b"no$gba"
+        .iter()
+        .enumerate()
+        .all(|(i, &byte)| unsafe { NO_CASH_ID.add(i).read_volatile() } == byte)
+}
+
+/// Writes messages to No$GBA's debug message window. No$GBA doesn't have log
+/// levels, so messages which aren't [`DebugLevel::Info`] start with their level.
+pub struct NoCashOutput;
+
+struct NoCashWriter;
+
+impl Write for NoCashWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            NO_CASH_CHAR_OUT.set(byte);
+        }
+
+        Ok(())
+    }
+}
+
+impl DebugOutput for NoCashOutput {
+    fn print(&self, output: fmt::Arguments, level: DebugLevel) -> fmt::Result {
+        let prefix = match level {
+            DebugLevel::Fatal => "[FATAL] ",
+            DebugLevel::Error => "[ERROR] ",
+            DebugLevel::Warning => "[WARN] ",
+            DebugLevel::Info => "",
+            DebugLevel::Debug => "[DEBUG] ",
+        };
+
+        writeln!(NoCashWriter, "{prefix}{output}")
+    }
+}
+
+/// Throws messages away, for when there's nowhere to write them.
+pub struct NoOutput;
+
+impl DebugOutput for NoOutput {
+    fn print(&self, _output: fmt::Arguments, _level: DebugLevel) -> fmt::Result {
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
+}
+
+static OUTPUT: Mutex<Cell<Option<&'static dyn DebugOutput>>> = Mutex::new(Cell::new(None));
+
+/// Works out which emulator the game is running in, and returns the output to
+/// use for it.
+#[must_use]
+pub fn detect() -> &'static dyn DebugOutput {
+    if Mgba::new().is_some() {
+        &MgbaOutput
+    } else if is_running_in_no_cash() {
+        &NoCashOutput
+    } else {
+        &NoOutput
+    }
+}
+
+/// Replaces where debug messages are written to.
+pub fn set_output(output: &'static dyn DebugOutput) {
+    critical_section::with(|cs| OUTPUT.borrow(cs).set(Some(output)));
+}
+
+/// Where debug messages are currently written to, which is [detected](detect)
+/// the first time this is called unless it has been [set](set_output).
+#[must_use]
+pub fn output() -> &'static dyn DebugOutput {
+    critical_section::with(|cs| {
+        let output = OUTPUT.borrow(cs);
+
+        output.get().unwrap_or_else(|| {
+            let detected = detect();
+            output.set(Some(detected));
+            detected
+        })
+    })
+}
+
+/// Writes a message to the current [output].
+pub fn print(output: fmt::Arguments, level: DebugLevel) -> fmt::Result {
+    self::output().print(output, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn tests_run_in_mgba(_gba: &mut crate::Gba) {
+        assert!(!is_running_in_no_cash());
+        assert!(detect().is_enabled());
+        assert!(output().is_enabled());
+        assert!(!NoOutput.is_enabled());
+    }
+}
@@ -1,12 +1,12 @@
 use crate::memory_mapped::MemoryMapped2DArray;
 
 use super::{
-    set_graphics_mode, set_graphics_settings, DisplayMode, GraphicsSettings, HEIGHT, WIDTH,
+    set_graphics_mode, set_graphics_settings, DisplayMode, GraphicsSettings, Rgb15, HEIGHT, WIDTH,
 };
 
 use core::marker::PhantomData;
 
-const BITMAP_MODE_3: MemoryMapped2DArray<u16, { WIDTH as usize }, { HEIGHT as usize }> =
+const BITMAP_MODE_3: MemoryMapped2DArray<Rgb15, { WIDTH as usize }, { HEIGHT as usize }> =
     unsafe { MemoryMapped2DArray::new(0x600_0000) };
 
 #[non_exhaustive]
@@ -25,13 +25,13 @@ impl Bitmap3<'_> {
 
     /// Draws point to screen at (x, y) coordinates with colour and panics if
     /// (x, y) is out of the bounds of the screen.
-    pub fn draw_point(&mut self, x: i32, y: i32, colour: u16) {
+    pub fn draw_point(&mut self, x: i32, y: i32, colour: Rgb15) {
         let x = x.try_into().unwrap();
         let y = y.try_into().unwrap();
         BITMAP_MODE_3.set(x, y, colour);
     }
 
-    pub fn clear(&mut self, colour: u16) {
+    pub fn clear(&mut self, colour: Rgb15) {
         for y in 0..(HEIGHT as usize) {
             for x in 0..(WIDTH as usize) {
                 BITMAP_MODE_3.set(x, y, colour);
@@ -0,0 +1,188 @@
+use crate::fixnum::Num;
+
+/// A colour as the GBA stores it, with 5 bits each for red, green and blue.
+/// Bit 15 is unused.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::Rgb15;
+///
+/// const SKY: Rgb15 = Rgb15::from_hex(0x2ce8f4);
+/// const DUSK: Rgb15 = Rgb15::from_rgb(0xd0, 0x60, 0x40);
+///
+/// # fn foo() {
+/// let evening = SKY.lerp(DUSK, agb::fixnum::num!(0.5));
+/// # }
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Rgb15(pub u16);
+
+impl Rgb15 {
+    /// Black, which is all components 0.
+    pub const BLACK: Rgb15 = Rgb15(0);
+    /// White, which is all components 31.
+    pub const WHITE: Rgb15 = Rgb15(0x7fff);
+
+    /// Creates a colour from its raw BGR555 value.
+    #[must_use]
+    pub const fn new(value: u16) -> Self {
+        Self(value)
+    }
+
+    /// Creates a colour from 5 bit red, green and blue components. Any higher
+    /// bits are ignored.
+    #[must_use]
+    pub const fn from_components(r: u8, g: u8, b: u8) -> Self {
+        Self((r as u16 & 31) | ((g as u16 & 31) << 5) | ((b as u16 & 31) << 10))
+    }
+
+    /// Creates a colour from 8 bit red, green and blue components, such as the
+    /// ones an image editor shows. The lowest 3 bits of each are lost.
+    #[must_use]
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::from_components(r >> 3, g >> 3, b >> 3)
+    }
+
+    /// Creates a colour from a hex code in the form `0xRRGGBB`.
+    #[must_use]
+    pub const fn from_hex(hex: u32) -> Self {
+        Self::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// The red component, from 0 to 31.
+    #[must_use]
+    pub const fn r(self) -> u8 {
+        (self.0 & 31) as u8
+    }
+
+    /// The green component, from 0 to 31.
+    #[must_use]
+    pub const fn g(self) -> u8 {
+        ((self.0 >> 5) & 31) as u8
+    }
+
+    /// The blue component, from 0 to 31.
+    #[must_use]
+    pub const fn b(self) -> u8 {
+        ((self.0 >> 10) & 31) as u8
+    }
+
+    /// The red, green and blue components scaled up to 8 bits, so that 31
+    /// becomes 255.
+    #[must_use]
+    pub const fn to_rgb(self) -> (u8, u8, u8) {
+        const fn expand(component: u8) -> u8 {
+            (component << 3) | (component >> 2)
+        }
+
+        (expand(self.r()), expand(self.g()), expand(self.b()))
+    }
+
+    fn map_components(self, f: impl Fn(i32) -> i32) -> Self {
+        let component = |c: u8| f(i32::from(c)).clamp(0, 31) as u8;
+
+        Self::from_components(
+            component(self.r()),
+            component(self.g()),
+            component(self.b()),
+        )
+    }
+
+    /// Interpolates between this colour and `other`, where an `amount` of 0
+    /// gives this colour and 1 gives `other`. The amount is clamped between 0
+    /// and 1.
+    #[must_use]
+    pub fn lerp(self, other: Rgb15, amount: Num<i32, 8>) -> Self {
+        let amount = amount.to_raw().clamp(0, 256);
+        let mix = |from: u8, to: u8| {
+            ((i32::from(from) * (256 - amount) + i32::from(to) * amount + 128) >> 8) as u8
+        };
+
+        Self::from_components(
+            mix(self.r(), other.r()),
+            mix(self.g(), other.g()),
+            mix(self.b(), other.b()),
+        )
+    }
+
+    /// Moves the colour towards white for a positive `amount` and towards black
+    /// for a negative one, where 1 is fully white and -1 is fully black.
+    #[must_use]
+    pub fn adjust_brightness(self, amount: Num<i32, 8>) -> Self {
+        if amount >= 0.into() {
+            self.lerp(Self::WHITE, amount)
+        } else {
+            self.lerp(Self::BLACK, -amount)
+        }
+    }
+
+    /// How bright the colour looks, from 0 to 31. Green counts for the most
+    /// and blue for the least, as that is how our eyes see them.
+    #[must_use]
+    pub fn luminance(self) -> u8 {
+        ((u32::from(self.r()) * 77 + u32::from(self.g()) * 151 + u32::from(self.b()) * 28) >> 8)
+            as u8
+    }
+
+    /// The shade of grey with the same [luminance](Self::luminance).
+    #[must_use]
+    pub fn greyscale(self) -> Self {
+        let grey = self.luminance();
+        Self::from_components(grey, grey, grey)
+    }
+
+    /// Scales how far the colour is from grey. An `amount` of 0 gives the
+    /// [greyscale](Self::greyscale) colour, 1 leaves the colour as it is and
+    /// values above 1 make it more colourful.
+    #[must_use]
+    pub fn adjust_saturation(self, amount: Num<i32, 8>) -> Self {
+        let grey = i32::from(self.luminance());
+        let amount = amount.to_raw().max(0);
+
+        self.map_components(|c| grey + (((c - grey) * amount + 128) >> 8))
+    }
+}
+
+impl From<u16> for Rgb15 {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Rgb15> for u16 {
+    fn from(value: Rgb15) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixnum::num;
+
+    use super::*;
+
+    #[test_case]
+    fn conversions(_gba: &mut crate::Gba) {
+        assert_eq!(Rgb15::from_hex(0xffffff), Rgb15::WHITE);
+        assert_eq!(Rgb15::from_rgb(0xf8, 0, 0x08), Rgb15(0x041f));
+        assert_eq!(Rgb15::from_components(1, 2, 3).to_rgb(), (8, 16, 24));
+        assert_eq!(Rgb15::WHITE.to_rgb(), (255, 255, 255));
+    }
+
+    #[test_case]
+    fn colour_maths(_gba: &mut crate::Gba) {
+        let red = Rgb15::from_components(31, 0, 0);
+        let blue = Rgb15::from_components(0, 0, 31);
+
+        assert_eq!(red.lerp(blue, num!(0.)), red);
+        assert_eq!(red.lerp(blue, num!(1.)), blue);
+        assert_eq!(red.lerp(blue, num!(0.5)), Rgb15::from_components(16, 0, 16));
+
+        assert_eq!(red.adjust_brightness(num!(-2.)), Rgb15::BLACK);
+        assert_eq!(Rgb15::WHITE.greyscale(), Rgb15::WHITE);
+        assert_eq!(red.adjust_saturation(num!(1.)), red);
+        assert_eq!(red.adjust_saturation(num!(0.)), red.greyscale());
+    }
+}
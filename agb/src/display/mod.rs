@@ -13,11 +13,17 @@ use self::{
 
 /// Graphics mode 3. Bitmap mode that provides a 16-bit colour framebuffer.
 pub(crate) mod bitmap3;
+mod colour;
+pub use colour::Rgb15;
+mod mosaic;
+pub use mosaic::Mosaic;
+
 /// Test logo of agb.
 pub mod example_logo;
 pub mod object;
 /// Palette type.
 pub mod palette16;
+pub mod palette_effects;
 /// Data produced by agb-image-converter
 pub mod tile_data;
 /// Graphics mode 0. Four regular backgrounds.
@@ -27,12 +33,13 @@ pub mod video;
 
 pub mod affine;
 pub mod blend;
+pub mod transition;
 pub mod window;
 
 pub mod font;
 pub use font::{Font, FontLetter};
 
-const DISPLAY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0000) };
+pub(crate) const DISPLAY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0000) };
 pub(crate) const DISPLAY_STATUS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0004) };
 const VCOUNT: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0006) };
 
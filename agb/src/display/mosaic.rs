@@ -0,0 +1,83 @@
+use crate::memory_mapped::MemoryMapped;
+
+const MOSAIC: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_004C) };
+
+/// The size of the blocks used by the mosaic effect, which makes backgrounds
+/// and objects look pixelated by repeating one pixel across a whole block.
+/// The effect only applies to backgrounds and objects that have mosaic turned
+/// on with `set_mosaic`.
+///
+/// A size of 1 means no mosaic, and the largest size is 16.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Mosaic {
+    background: (u8, u8),
+    object: (u8, u8),
+}
+
+impl Default for Mosaic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mosaic {
+    /// A mosaic with a size of 1, which doesn't change anything.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            background: (1, 1),
+            object: (1, 1),
+        }
+    }
+
+    const fn clamp(size: u8) -> u8 {
+        if size < 1 {
+            1
+        } else if size > 16 {
+            16
+        } else {
+            size
+        }
+    }
+
+    /// Sets the width and height of the blocks for backgrounds.
+    #[must_use]
+    pub const fn with_background_size(self, width: u8, height: u8) -> Self {
+        Self {
+            background: (Self::clamp(width), Self::clamp(height)),
+            ..self
+        }
+    }
+
+    /// Sets the width and height of the blocks for objects.
+    #[must_use]
+    pub const fn with_object_size(self, width: u8, height: u8) -> Self {
+        Self {
+            object: (Self::clamp(width), Self::clamp(height)),
+            ..self
+        }
+    }
+
+    /// The width and height of the blocks for backgrounds.
+    #[must_use]
+    pub const fn background_size(&self) -> (u8, u8) {
+        self.background
+    }
+
+    /// The width and height of the blocks for objects.
+    #[must_use]
+    pub const fn object_size(&self) -> (u8, u8) {
+        self.object
+    }
+
+    /// Writes the mosaic size to the display registers. This should be done
+    /// during vblank.
+    pub fn commit(&self) {
+        let value = u16::from(self.background.0 - 1)
+            | (u16::from(self.background.1 - 1) << 4)
+            | (u16::from(self.object.0 - 1) << 8)
+            | (u16::from(self.object.1 - 1) << 12);
+
+        MOSAIC.set(value);
+    }
+}
@@ -9,20 +9,25 @@
 //! harder to integrate into your games depending on how they are architectured.
 
 mod affine;
+mod affine_object;
 mod font;
 mod managed;
+mod metasprite;
 mod sprites;
 mod unmanaged;
 
 pub use sprites::{
-    include_aseprite, DynamicSprite, Graphics, PaletteVram, Size, Sprite, SpriteLoader, SpriteVram,
-    Tag, TagMap,
+    include_aseprite, DynamicSprite, Graphics, LoaderError, Palette256, PaletteVram, Size, Sprite,
+    SpriteLoader, SpriteVram, SpriteVramArea, Tag, TagMap, VramUsage,
 };
 
-pub use affine::AffineMatrixInstance;
+pub use affine::{AffineMatrixCache, AffineMatrixInstance};
+pub use affine_object::AffineObject;
 pub use managed::{OamManaged, Object};
+pub use metasprite::{MetaObject, MetaSprite, MetaSpritePart};
 pub use unmanaged::{
-    AffineMode, GraphicsMode, OamIterator, OamSlot, OamUnmanaged, ObjectUnmanaged,
+    AffineMode, GraphicsMode, OamBatch, OamBatchReport, OamIterator, OamSlot, OamUnmanaged,
+    ObjectUnmanaged,
 };
 
 pub use font::{ChangeColour, ObjectTextRender, TextAlignment};
@@ -1,6 +1,6 @@
 use core::cell::Cell;
 
-use alloc::rc::Rc;
+use alloc::{rc::Rc, vec::Vec};
 
 use crate::display::affine::AffineMatrixObject;
 
@@ -44,6 +44,71 @@ impl AffineMatrixInstance {
     pub(crate) fn vram(self) -> AffineMatrixVram {
         self.location
     }
+
+    /// The matrix this instance was created from.
+    #[must_use]
+    pub fn matrix(&self) -> AffineMatrixObject {
+        self.location.0.matrix
+    }
+
+    fn is_unused(&self) -> bool {
+        Rc::strong_count(&self.location.0) == 1
+    }
+}
+
+/// Gives out [`AffineMatrixInstance`]s, handing back the same instance for
+/// matrices that are equal. Objects sharing an instance share one of the
+/// hardware affine slots, so using a cache keeps you within the limit when
+/// lots of objects are transformed the same way.
+///
+/// Instances which are no longer used by anything other than the cache are
+/// removed when new matrices are added.
+#[derive(Debug, Default)]
+pub struct AffineMatrixCache {
+    matrices: Vec<AffineMatrixInstance>,
+}
+
+impl AffineMatrixCache {
+    /// Creates an empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an instance for `matrix`, reusing an existing one if there is
+    /// one for an equal matrix.
+    pub fn get(&mut self, matrix: AffineMatrixObject) -> AffineMatrixInstance {
+        if let Some(instance) = self
+            .matrices
+            .iter()
+            .find(|instance| instance.matrix() == matrix)
+        {
+            return instance.clone();
+        }
+
+        self.remove_unused();
+
+        let instance = AffineMatrixInstance::new(matrix);
+        self.matrices.push(instance.clone());
+        instance
+    }
+
+    /// Removes the instances which are only held by the cache.
+    pub fn remove_unused(&mut self) {
+        self.matrices.retain(|instance| !instance.is_unused());
+    }
+
+    /// The number of different matrices in the cache.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.matrices.len()
+    }
+
+    /// Whether the cache has no matrices in it.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.matrices.is_empty()
+    }
 }
 
 impl AffineMatrixVram {
@@ -86,4 +151,27 @@ mod tests {
             core::mem::size_of::<Option<AffineMatrixInstance>>()
         );
     }
+
+    #[test_case]
+    fn cache_shares_equal_matrices(_gba: &mut crate::Gba) {
+        use crate::{display::affine::AffineMatrix, fixnum::num};
+
+        let mut cache = AffineMatrixCache::new();
+
+        let rotation = AffineMatrix::from_rotation::<8>(num!(0.25)).to_object_wrapping();
+        let first = cache.get(rotation);
+        let second = cache.get(rotation);
+        let identity = cache.get(AffineMatrixObject::default());
+
+        assert!(Rc::ptr_eq(&first.location.0, &second.location.0));
+        assert_eq!(cache.len(), 2);
+
+        drop((first, second));
+        cache.get(AffineMatrix::from_rotation::<8>(num!(0.5)).to_object_wrapping());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.get(AffineMatrixObject::default()).matrix(),
+            identity.matrix()
+        );
+    }
 }
@@ -0,0 +1,309 @@
+use agb_fixnum::{Num, Vector2D};
+
+use crate::display::{affine::AffineMatrix, Priority};
+
+use super::{AffineMatrixCache, AffineMode, GraphicsMode, ObjectUnmanaged, SpriteVram};
+
+type Number = Num<i32, 8>;
+
+/// An object which can be scaled and rotated about any point on its sprite,
+/// for use with the [`OamUnmanaged`](super::OamUnmanaged) system.
+///
+/// The hardware rotates objects about the centre of their sprite and clips them
+/// to a box the size of the sprite, or twice the size in double mode. This
+/// works out the matrix, chooses double mode when the transformed sprite would
+/// be clipped otherwise and moves the object so that the pivot stays where you
+/// put it. Even in double mode, parts of a sprite scaled up by more than double
+/// will be cut off.
+///
+/// Matrices come from an [`AffineMatrixCache`], so objects transformed the same
+/// way share a hardware affine slot.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{AffineMatrixCache, AffineObject, Graphics}, fixnum::num, include_aseprite};
+/// static GRAPHICS: &Graphics = include_aseprite!("examples/gfx/boss.aseprite");
+///
+/// # fn foo(gba: &mut agb::Gba) {
+/// let (mut oam, mut loader) = gba.display.object.get_unmanaged();
+/// let mut matrices = AffineMatrixCache::new();
+///
+/// let mut object = AffineObject::new(loader.get_vram_sprite(&GRAPHICS.sprites()[0]));
+/// // spin around the top left corner, which stays at (120, 80)
+/// object
+///     .set_pivot((num!(0.), num!(0.)))
+///     .set_position((num!(120.), num!(80.)))
+///     .set_rotation(num!(0.125))
+///     .set_scale((num!(2.), num!(2.)))
+///     .show();
+///
+/// object.update(&mut matrices);
+/// oam.iter().next().unwrap().set(object.object());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AffineObject {
+    object: ObjectUnmanaged,
+    size: Vector2D<Number>,
+    position: Vector2D<Number>,
+    pivot: Vector2D<Number>,
+    scale: Vector2D<Number>,
+    rotation: Number,
+    visible: bool,
+}
+
+impl AffineObject {
+    /// Creates a hidden object showing `sprite`, with no scaling or rotation
+    /// and the pivot in the middle of the sprite.
+    #[must_use]
+    pub fn new(sprite: SpriteVram) -> Self {
+        let size = Self::sprite_size(&sprite);
+
+        Self {
+            object: ObjectUnmanaged::new(sprite),
+            size,
+            position: Vector2D::new(0.into(), 0.into()),
+            pivot: size / 2,
+            scale: Vector2D::new(1.into(), 1.into()),
+            rotation: 0.into(),
+            visible: false,
+        }
+    }
+
+    fn sprite_size(sprite: &SpriteVram) -> Vector2D<Number> {
+        let (width, height) = sprite.size().to_width_height();
+        Vector2D::new(Number::new(width as i32), Number::new(height as i32))
+    }
+
+    /// Changes the sprite being shown. The pivot stays the same distance from
+    /// the top left of the sprite.
+    pub fn set_sprite(&mut self, sprite: SpriteVram) -> &mut Self {
+        self.size = Self::sprite_size(&sprite);
+        self.object.set_sprite(sprite);
+
+        self
+    }
+
+    /// Sets where the pivot is drawn on the screen.
+    /// Use [position](Self::position) to get the value.
+    pub fn set_position(&mut self, position: impl Into<Vector2D<Number>>) -> &mut Self {
+        self.position = position.into();
+
+        self
+    }
+
+    /// Where the pivot is drawn on the screen.
+    #[must_use]
+    pub fn position(&self) -> Vector2D<Number> {
+        self.position
+    }
+
+    /// Sets the point the sprite is scaled and rotated about, relative to the
+    /// top left of the sprite. Use [pivot](Self::pivot) to get the value.
+    pub fn set_pivot(&mut self, pivot: impl Into<Vector2D<Number>>) -> &mut Self {
+        self.pivot = pivot.into();
+
+        self
+    }
+
+    /// The point the sprite is scaled and rotated about.
+    #[must_use]
+    pub fn pivot(&self) -> Vector2D<Number> {
+        self.pivot
+    }
+
+    /// Sets how much the sprite is stretched in each direction, where 1 is its
+    /// normal size. Negative values flip the sprite. Neither value can be 0.
+    /// Use [scale](Self::scale) to get the value.
+    pub fn set_scale(&mut self, scale: impl Into<Vector2D<Number>>) -> &mut Self {
+        let scale = scale.into();
+        assert!(
+            scale.x != 0.into() && scale.y != 0.into(),
+            "an affine object can't be scaled to nothing"
+        );
+        self.scale = scale;
+
+        self
+    }
+
+    /// How much the sprite is stretched in each direction.
+    #[must_use]
+    pub fn scale(&self) -> Vector2D<Number> {
+        self.scale
+    }
+
+    /// Sets the rotation in turns, so 1 is a full turn.
+    /// Use [rotation](Self::rotation) to get the value.
+    pub fn set_rotation(&mut self, rotation: Number) -> &mut Self {
+        self.rotation = rotation;
+
+        self
+    }
+
+    /// The rotation in turns.
+    #[must_use]
+    pub fn rotation(&self) -> Number {
+        self.rotation
+    }
+
+    /// Shows the object the next time it is [updated](Self::update).
+    pub fn show(&mut self) -> &mut Self {
+        self.visible = true;
+
+        self
+    }
+
+    /// Hides the object the next time it is [updated](Self::update).
+    pub fn hide(&mut self) -> &mut Self {
+        self.visible = false;
+
+        self
+    }
+
+    /// Whether the object is shown.
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Sets the priority of the object relative to the backgrounds priority.
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        self.object.set_priority(priority);
+
+        self
+    }
+
+    /// Sets the graphics mode of the object.
+    pub fn set_graphics_mode(&mut self, mode: GraphicsMode) -> &mut Self {
+        self.object.set_graphics_mode(mode);
+
+        self
+    }
+
+    /// Maps a point relative to the centre of the sprite to where it is drawn
+    /// relative to the centre of the object.
+    fn forward_matrix(&self) -> AffineMatrix {
+        AffineMatrix::from_rotation(-self.rotation) * AffineMatrix::from_scale(self.scale)
+    }
+
+    fn transform(matrix: &AffineMatrix, point: Vector2D<Number>) -> Vector2D<Number> {
+        Vector2D::new(
+            matrix.a * point.x + matrix.b * point.y,
+            matrix.c * point.x + matrix.d * point.y,
+        )
+    }
+
+    /// The affine mode needed to show the whole of the transformed sprite, as
+    /// long as it fits in double mode.
+    #[must_use]
+    pub fn affine_mode(&self) -> AffineMode {
+        let forward = self.forward_matrix();
+        let half_size = self.size / 2;
+
+        let half_width = forward.a.abs() * half_size.x + forward.b.abs() * half_size.y;
+        let half_height = forward.c.abs() * half_size.x + forward.d.abs() * half_size.y;
+
+        if half_width > half_size.x || half_height > half_size.y {
+            AffineMode::AffineDouble
+        } else {
+            AffineMode::Affine
+        }
+    }
+
+    /// Works out the matrix, affine mode and position of the underlying object.
+    /// Call this after making changes and before writing the object to oam.
+    pub fn update(&mut self, matrices: &mut AffineMatrixCache) -> &mut Self {
+        if !self.visible {
+            self.object.hide();
+            return self;
+        }
+
+        let forward = self.forward_matrix();
+        let inverse = AffineMatrix::from_scale(Vector2D::new(
+            Number::new(1) / self.scale.x,
+            Number::new(1) / self.scale.y,
+        )) * AffineMatrix::from_rotation(self.rotation);
+
+        let mode = self.affine_mode();
+        let half_box = match mode {
+            AffineMode::Affine => self.size / 2,
+            AffineMode::AffineDouble => self.size,
+        };
+
+        let centre = self.position + Self::transform(&forward, self.size / 2 - self.pivot);
+
+        self.object
+            .set_affine_matrix(matrices.get(inverse.to_object_wrapping()))
+            .set_position((centre - half_box).floor())
+            .show_affine(mode);
+
+        self
+    }
+
+    /// The object to write to oam, which is up to date as of the last call to
+    /// [update](Self::update).
+    #[must_use]
+    pub fn object(&self) -> &ObjectUnmanaged {
+        &self.object
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{display::object::Graphics, fixnum::num, include_aseprite};
+
+    use super::*;
+
+    static GRAPHICS: &Graphics = include_aseprite!("examples/gfx/objects.aseprite");
+
+    #[test_case]
+    fn pivot_and_double_size(gba: &mut crate::Gba) {
+        let (_oam, mut loader) = gba.display.object.get_unmanaged();
+        let mut matrices = AffineMatrixCache::new();
+
+        let sprite = &GRAPHICS.sprites()[0];
+        let (width, height) = sprite.size().to_width_height();
+        let (width, height) = (width as i32, height as i32);
+
+        let mut object = AffineObject::new(loader.get_vram_sprite(sprite));
+        object
+            .set_pivot((num!(0.), num!(0.)))
+            .set_position((num!(50.), num!(60.)))
+            .show()
+            .update(&mut matrices);
+
+        assert_eq!(object.affine_mode(), AffineMode::Affine);
+        assert_eq!(object.object().position(), Vector2D::new(50, 60));
+
+        // half a turn around the top left swings the sprite up and to the left
+        object.set_rotation(num!(0.5)).update(&mut matrices);
+        assert_eq!(object.affine_mode(), AffineMode::Affine);
+        assert_eq!(
+            object.object().position(),
+            Vector2D::new(50 - width, 60 - height)
+        );
+
+        // doubling the size around the centre needs double mode
+        object
+            .set_rotation(num!(0.))
+            .set_pivot((Number::new(width) / 2, Number::new(height) / 2))
+            .set_scale((num!(2.), num!(2.)))
+            .update(&mut matrices);
+        assert_eq!(object.affine_mode(), AffineMode::AffineDouble);
+        assert_eq!(
+            object.object().position(),
+            Vector2D::new(50 - width, 60 - height)
+        );
+
+        let mut other = AffineObject::new(loader.get_vram_sprite(sprite));
+        other
+            .set_scale((num!(2.), num!(2.)))
+            .show()
+            .update(&mut matrices);
+
+        // both objects now share the same matrix, and the old ones are unused
+        matrices.remove_unused();
+        assert_eq!(matrices.len(), 1);
+    }
+}
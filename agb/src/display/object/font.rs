@@ -123,16 +123,16 @@ fn is_private_use(c: char) -> bool {
 /// # #![no_main]
 /// use agb::display::object::{ObjectTextRender, PaletteVram, ChangeColour, Size};
 /// use agb::display::palette16::Palette16;
-/// use agb::display::Font;
+/// use agb::display::{Font, Rgb15};
 ///
 /// use core::fmt::Write;
 ///
 /// static EXAMPLE_FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
 ///
 /// # fn foo() {
-/// let mut palette = [0x0; 16];
-/// palette[1] = 0xFF_FF;
-/// palette[2] = 0x00_FF;
+/// let mut palette = [Rgb15::BLACK; 16];
+/// palette[1] = Rgb15::WHITE;
+/// palette[2] = Rgb15(0x00_FF);
 /// let palette = Palette16::new(palette);
 /// let palette = PaletteVram::new(&palette).unwrap();
 /// let mut writer = ObjectTextRender::new(&EXAMPLE_FONT, Size::S16x16, palette);
@@ -210,7 +210,7 @@ impl BufferedRender<'_> {
 /// #![no_main]
 /// use agb::display::object::{ObjectTextRender, PaletteVram, TextAlignment, Size};
 /// use agb::display::palette16::Palette16;
-/// use agb::display::{Font, WIDTH};
+/// use agb::display::{Font, Rgb15, WIDTH};
 ///
 /// use core::fmt::Write;
 ///
@@ -221,8 +221,8 @@ impl BufferedRender<'_> {
 ///     let (mut unmanaged, _) = gba.display.object.get_unmanaged();
 ///     let vblank = agb::interrupt::VBlank::get();
 ///
-///     let mut palette = [0x0; 16];
-///     palette[1] = 0xFF_FF;
+///     let mut palette = [Rgb15::BLACK; 16];
+///     palette[1] = Rgb15::WHITE;
 ///     let palette = Palette16::new(palette);
 ///     let palette = PaletteVram::new(&palette).unwrap();
 ///
@@ -168,6 +168,7 @@ impl OamManaged<'_> {
     pub fn commit(&self) {
         // safety: commit is not reentrant
         let unmanaged = unsafe { &mut *self.unmanaged.get() };
+        unmanaged.commit_sprite_vram();
 
         for (object, slot) in unsafe { self.object_store.iter() }
             .map(|item| unsafe { &*item.object.get() })
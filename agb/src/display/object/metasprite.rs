@@ -0,0 +1,350 @@
+use agb_fixnum::Vector2D;
+use alloc::vec::Vec;
+
+use crate::display::Priority;
+
+use super::{GraphicsMode, OamIterator, ObjectUnmanaged, Sprite, SpriteLoader};
+
+/// One of the sprites that makes up a [`MetaSprite`], along with where it goes.
+pub struct MetaSpritePart {
+    sprite: &'static Sprite,
+    offset: Vector2D<i32>,
+}
+
+impl MetaSpritePart {
+    /// Creates a part which draws `sprite` with its top left corner at `x`, `y`
+    /// relative to the position of the metasprite.
+    #[must_use]
+    pub const fn new(sprite: &'static Sprite, x: i32, y: i32) -> Self {
+        Self {
+            sprite,
+            offset: Vector2D { x, y },
+        }
+    }
+
+    /// The sprite used by this part.
+    #[must_use]
+    pub fn sprite(&self) -> &'static Sprite {
+        self.sprite
+    }
+
+    /// Where the top left of this part is relative to the position of the
+    /// metasprite.
+    #[must_use]
+    pub fn offset(&self) -> Vector2D<i32> {
+        self.offset
+    }
+}
+
+/// A sprite made of several hardware sprites which are moved, flipped and
+/// prioritised together. This lets you have sprites larger than 64x64, or build
+/// characters out of parts.
+///
+/// [`include_aseprite!`](crate::include_aseprite) creates a metasprite for each
+/// frame of a file with slices in it, where each slice is one part. These are
+/// available from [`Graphics::metasprites`](super::Graphics::metasprites). You
+/// can also make your own:
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, MetaSprite, MetaSpritePart}, include_aseprite};
+/// static GRAPHICS: &Graphics = include_aseprite!("examples/gfx/boss.aseprite");
+///
+/// // two of the same sprite, one above the other
+/// static TOTEM: MetaSprite = MetaSprite::new(&[
+///     MetaSpritePart::new(&GRAPHICS.sprites()[0], 0, 0),
+///     MetaSpritePart::new(&GRAPHICS.sprites()[0], 0, 32),
+/// ]);
+/// ```
+///
+/// Use a [`MetaObject`] to show one.
+pub struct MetaSprite {
+    parts: &'static [MetaSpritePart],
+}
+
+impl MetaSprite {
+    /// Creates a metasprite from its parts. Parts that come first are drawn on
+    /// top of the ones that come after them.
+    #[must_use]
+    pub const fn new(parts: &'static [MetaSpritePart]) -> Self {
+        Self { parts }
+    }
+
+    /// The parts that make up this metasprite.
+    #[must_use]
+    pub fn parts(&self) -> &'static [MetaSpritePart] {
+        self.parts
+    }
+
+    /// The top left and bottom right of the area covered by the parts,
+    /// relative to the position of the metasprite.
+    #[must_use]
+    pub fn bounds(&self) -> (Vector2D<i32>, Vector2D<i32>) {
+        self.parts.iter().fold(
+            (
+                Vector2D::new(i32::MAX, i32::MAX),
+                Vector2D::new(i32::MIN, i32::MIN),
+            ),
+            |(top_left, bottom_right), part| {
+                let (width, height) = part.sprite.size().to_width_height();
+                (
+                    Vector2D::new(top_left.x.min(part.offset.x), top_left.y.min(part.offset.y)),
+                    Vector2D::new(
+                        bottom_right.x.max(part.offset.x + width as i32),
+                        bottom_right.y.max(part.offset.y + height as i32),
+                    ),
+                )
+            },
+        )
+    }
+}
+
+/// An object showing a [`MetaSprite`] for use with the
+/// [`OamUnmanaged`](super::OamUnmanaged) system. It has one
+/// [`ObjectUnmanaged`] for each part, which are kept in the right place
+/// relative to each other.
+///
+/// Flipping a metasprite flips the whole thing, so the parts swap places as
+/// well as being flipped themselves.
+pub struct MetaObject {
+    metasprite: &'static MetaSprite,
+    objects: Vec<ObjectUnmanaged>,
+    position: Vector2D<i32>,
+    hflip: bool,
+    vflip: bool,
+}
+
+impl MetaObject {
+    /// Creates a hidden metaobject showing `metasprite`, loading the sprites
+    /// for its parts with `loader`.
+    #[must_use]
+    pub fn new(metasprite: &'static MetaSprite, loader: &mut SpriteLoader) -> Self {
+        let mut object = Self {
+            metasprite,
+            objects: Vec::with_capacity(metasprite.parts.len()),
+            position: Vector2D::new(0, 0),
+            hflip: false,
+            vflip: false,
+        };
+
+        object.set_metasprite(metasprite, loader);
+
+        object
+    }
+
+    /// Changes the metasprite being shown, such as for the next frame of an
+    /// animation. Everything else stays the same.
+    pub fn set_metasprite(
+        &mut self,
+        metasprite: &'static MetaSprite,
+        loader: &mut SpriteLoader,
+    ) -> &mut Self {
+        let template = self.objects.first().cloned();
+
+        self.objects.truncate(metasprite.parts.len());
+        for (i, part) in metasprite.parts.iter().enumerate() {
+            let sprite = loader.get_vram_sprite(part.sprite);
+
+            match self.objects.get_mut(i) {
+                Some(object) => {
+                    object.set_sprite(sprite);
+                }
+                None => {
+                    let object = match &template {
+                        Some(template) => {
+                            let mut object = template.clone();
+                            object.set_sprite(sprite);
+                            object
+                        }
+                        None => ObjectUnmanaged::new(sprite),
+                    };
+                    self.objects.push(object);
+                }
+            }
+        }
+
+        self.metasprite = metasprite;
+        self.update_positions();
+
+        self
+    }
+
+    /// The metasprite being shown.
+    #[must_use]
+    pub fn metasprite(&self) -> &'static MetaSprite {
+        self.metasprite
+    }
+
+    fn update_positions(&mut self) {
+        let (top_left, bottom_right) = self.metasprite.bounds();
+
+        for (object, part) in self.objects.iter_mut().zip(self.metasprite.parts) {
+            let (width, height) = part.sprite.size().to_width_height();
+            let mut offset = part.offset;
+
+            if self.hflip {
+                offset.x = top_left.x + bottom_right.x - (offset.x + width as i32);
+            }
+            if self.vflip {
+                offset.y = top_left.y + bottom_right.y - (offset.y + height as i32);
+            }
+
+            object
+                .set_position(self.position + offset)
+                .set_hflip(self.hflip)
+                .set_vflip(self.vflip);
+        }
+    }
+
+    /// Sets the position of the metasprite. Use [position](Self::position) to
+    /// get the value.
+    pub fn set_position(&mut self, position: impl Into<Vector2D<i32>>) -> &mut Self {
+        self.position = position.into();
+        self.update_positions();
+
+        self
+    }
+
+    /// The position of the metasprite.
+    #[must_use]
+    pub fn position(&self) -> Vector2D<i32> {
+        self.position
+    }
+
+    /// Sets whether the whole metasprite is flipped horizontally.
+    pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
+        self.hflip = flip;
+        self.update_positions();
+
+        self
+    }
+
+    /// Whether the metasprite is flipped horizontally.
+    #[must_use]
+    pub fn hflip(&self) -> bool {
+        self.hflip
+    }
+
+    /// Sets whether the whole metasprite is flipped vertically.
+    pub fn set_vflip(&mut self, flip: bool) -> &mut Self {
+        self.vflip = flip;
+        self.update_positions();
+
+        self
+    }
+
+    /// Whether the metasprite is flipped vertically.
+    #[must_use]
+    pub fn vflip(&self) -> bool {
+        self.vflip
+    }
+
+    /// Shows every part of the metasprite.
+    pub fn show(&mut self) -> &mut Self {
+        for object in &mut self.objects {
+            object.show();
+        }
+
+        self
+    }
+
+    /// Hides every part of the metasprite.
+    pub fn hide(&mut self) -> &mut Self {
+        for object in &mut self.objects {
+            object.hide();
+        }
+
+        self
+    }
+
+    /// Sets the priority of every part of the metasprite.
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        for object in &mut self.objects {
+            object.set_priority(priority);
+        }
+
+        self
+    }
+
+    /// Sets the graphics mode of every part of the metasprite.
+    pub fn set_graphics_mode(&mut self, mode: GraphicsMode) -> &mut Self {
+        for object in &mut self.objects {
+            object.set_graphics_mode(mode);
+        }
+
+        self
+    }
+
+    /// The objects for each part, in the same order as the parts. These can be
+    /// given to an [`OamBatch`](super::OamBatch) to sort them with other objects.
+    pub fn objects(&self) -> impl Iterator<Item = &ObjectUnmanaged> {
+        self.objects.iter()
+    }
+
+    /// Writes every part to the next slots of `oam`. If there aren't enough
+    /// slots left for all of them, nothing is written and this returns `false`.
+    pub fn write(&self, oam: &mut OamIterator) -> bool {
+        if oam.len() < self.objects.len() {
+            return false;
+        }
+
+        for object in &self.objects {
+            oam.set_next(object);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{display::object::Graphics, include_aseprite};
+
+    use super::*;
+
+    static GRAPHICS: &Graphics = include_aseprite!("examples/gfx/wide.aseprite");
+
+    static PARTS: &[MetaSpritePart] = &[
+        MetaSpritePart::new(&GRAPHICS.sprites()[0], 0, 0),
+        MetaSpritePart::new(&GRAPHICS.sprites()[0], 0, 32),
+    ];
+
+    static TWO_HIGH: MetaSprite = MetaSprite::new(PARTS);
+
+    #[test_case]
+    fn flipping_mirrors_the_parts(gba: &mut crate::Gba) {
+        let (_oam, mut loader) = gba.display.object.get_unmanaged();
+
+        let (width, height) = GRAPHICS.sprites()[0].size().to_width_height();
+        assert_eq!(
+            TWO_HIGH.bounds(),
+            (
+                Vector2D::new(0, 0),
+                Vector2D::new(width as i32, 32 + height as i32)
+            )
+        );
+
+        let mut object = MetaObject::new(&TWO_HIGH, &mut loader);
+        object.set_position((10, 20));
+
+        let positions = |object: &MetaObject| {
+            object
+                .objects()
+                .map(ObjectUnmanaged::position)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            positions(&object),
+            [Vector2D::new(10, 20), Vector2D::new(10, 52)]
+        );
+
+        object.set_vflip(true);
+        assert_eq!(
+            positions(&object),
+            [Vector2D::new(10, 52), Vector2D::new(10, 20)]
+        );
+        assert!(object.objects().all(ObjectUnmanaged::vflip));
+    }
+}
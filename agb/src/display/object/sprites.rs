@@ -2,6 +2,10 @@ mod sprite;
 mod sprite_allocator;
 
 const BYTES_PER_TILE_4BPP: usize = 32;
+const BYTES_PER_TILE_8BPP: usize = 64;
 
-pub use sprite::{include_aseprite, Graphics, Size, Sprite, Tag, TagMap};
-pub use sprite_allocator::{DynamicSprite, PaletteVram, SpriteLoader, SpriteVram};
+pub use sprite::{include_aseprite, Graphics, Palette256, Size, Sprite, Tag, TagMap};
+pub(crate) use sprite_allocator::{compact_on_commit, sprite_generation};
+pub use sprite_allocator::{
+    DynamicSprite, LoaderError, PaletteVram, SpriteLoader, SpriteVram, SpriteVramArea, VramUsage,
+};
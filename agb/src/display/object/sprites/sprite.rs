@@ -1,16 +1,22 @@
 use core::{alloc::Layout, slice};
 
-use crate::display::palette16::Palette16;
+use crate::display::{object::MetaSprite, palette16::Palette16, tiled::TileFormat};
 
-use super::BYTES_PER_TILE_4BPP;
+use super::BYTES_PER_TILE_8BPP;
 
 /// Sprite data. Refers to the palette, pixel data, and the size of the sprite.
 pub struct Sprite {
-    pub(crate) palette: &'static Palette16,
+    pub(crate) palette: SpritePalette,
     pub(crate) data: &'static [u8],
     pub(crate) size: Size,
 }
 
+#[derive(Clone, Copy)]
+pub(crate) enum SpritePalette {
+    Colours16(&'static Palette16),
+    Colours256(&'static Palette256),
+}
+
 impl Sprite {
     #[doc(hidden)]
     /// Creates a sprite from it's constituent data, used internally by
@@ -21,7 +27,26 @@ impl Sprite {
     #[must_use]
     pub const unsafe fn new(palette: &'static Palette16, data: &'static [u8], size: Size) -> Self {
         Self {
-            palette,
+            palette: SpritePalette::Colours16(palette),
+            data,
+            size,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Creates a 256 colour sprite from it's constituent data, used internally
+    /// by [include_aseprite] and should generally not be used outside it.
+    ///
+    /// # Safety
+    /// The data should be aligned to a 2 byte boundary
+    #[must_use]
+    pub const unsafe fn new_256(
+        palette: &'static Palette256,
+        data: &'static [u8],
+        size: Size,
+    ) -> Self {
+        Self {
+            palette: SpritePalette::Colours256(palette),
             data,
             size,
         }
@@ -32,6 +57,48 @@ impl Sprite {
     pub fn size(&self) -> Size {
         self.size
     }
+
+    #[must_use]
+    /// Whether the sprite uses 16 colours (4bpp) or 256 colours (8bpp)
+    pub fn format(&self) -> TileFormat {
+        match self.palette {
+            SpritePalette::Colours16(_) => TileFormat::FourBpp,
+            SpritePalette::Colours256(_) => TileFormat::EightBpp,
+        }
+    }
+}
+
+/// The 256 colour object palette which is shared by every 256 colour sprite.
+/// This is stored as a number of consecutive 16 colour palettes from the start
+/// of the object palette, and colour 0 is transparent.
+///
+/// The pixels of 256 colour sprites refer directly to colours in the object
+/// palette, so only one of these can be in vram at a time. 16 colour sprites
+/// can still be used alongside 256 colour sprites, with their palettes going
+/// in the part of the object palette this doesn't use.
+pub struct Palette256 {
+    palettes: &'static [Palette16],
+}
+
+impl Palette256 {
+    #[doc(hidden)]
+    /// Creates a 256 colour palette from 16 colour palettes, used internally by
+    /// [include_aseprite] and should generally not be used outside it.
+    #[must_use]
+    pub const fn new(palettes: &'static [Palette16]) -> Self {
+        assert!(
+            !palettes.is_empty() && palettes.len() <= 16,
+            "A 256 colour palette must be made of between 1 and 16 palettes"
+        );
+
+        Self { palettes }
+    }
+
+    #[must_use]
+    /// The 16 colour palettes which make up this palette
+    pub fn palettes(&self) -> &'static [Palette16] {
+        self.palettes
+    }
 }
 
 /// The sizes of sprite supported by the GBA.
@@ -92,6 +159,26 @@ macro_rules! align_bytes {
 /// name in code. You should ensure tags are unique as this is not enforced by
 /// aseprite.
 ///
+/// Sprites are 16 colour (4bpp) by default. Passing `256` first instead makes
+/// them 256 colour (8bpp) sprites which share a single [Palette256] of up to
+/// 255 colours. These use twice as much video memory, but every sprite can use
+/// every colour. As the 256 colour object palette is shared, put every 256
+/// colour sprite you want on screen at once in the same `include_aseprite!`.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::Graphics, include_aseprite};
+/// static GRAPHICS: &Graphics = include_aseprite!(256, "examples/gfx/boss.aseprite");
+/// ```
+///
+/// If a file has slices in it, each slice also becomes a sprite and each frame
+/// also becomes a [MetaSprite](crate::display::object::MetaSprite) made of the
+/// slices. These are available from [Graphics::metasprites]. Each slice must be
+/// one of the supported sizes, but the frames of these files can be bigger
+/// than the sprite sizes the GBA supports, in which case the frames and tags
+/// aren't included.
+///
 /// Including from the out directory is supported through the `$OUT_DIR` token.
 ///
 /// ```rust,ignore
@@ -105,15 +192,25 @@ macro_rules! align_bytes {
 ///
 #[macro_export]
 macro_rules! include_aseprite {
+    (256, $($aseprite_path: expr),*) => {{
+        #[allow(unused_imports)]
+        use $crate::display::object::{Size, Sprite, Tag, TagMap, Graphics, Palette256, MetaSprite, MetaSpritePart};
+        use $crate::display::{palette16::Palette16, Rgb15};
+        use $crate::align_bytes;
+
+        $crate::include_aseprite_inner!(256, $($aseprite_path),*);
+
+        &Graphics::new(SPRITES, &TAGS).with_metasprites(METASPRITES)
+    }};
     ($($aseprite_path: expr),*) => {{
         #[allow(unused_imports)]
-        use $crate::display::object::{Size, Sprite, Tag, TagMap, Graphics};
-        use $crate::display::palette16::Palette16;
+        use $crate::display::object::{Size, Sprite, Tag, TagMap, Graphics, MetaSprite, MetaSpritePart};
+        use $crate::display::{palette16::Palette16, Rgb15};
         use $crate::align_bytes;
 
         $crate::include_aseprite_inner!($($aseprite_path),*);
 
-        &Graphics::new(SPRITES, &TAGS)
+        &Graphics::new(SPRITES, &TAGS).with_metasprites(METASPRITES)
     }};
 }
 
@@ -123,6 +220,7 @@ pub use include_aseprite;
 pub struct Graphics {
     sprites: &'static [Sprite],
     tag_map: &'static TagMap,
+    metasprites: &'static [MetaSprite],
 }
 
 impl Graphics {
@@ -132,7 +230,22 @@ impl Graphics {
     /// use.
     #[must_use]
     pub const fn new(sprites: &'static [Sprite], tag_map: &'static TagMap) -> Self {
-        Self { sprites, tag_map }
+        Self {
+            sprites,
+            tag_map,
+            metasprites: &[],
+        }
+    }
+
+    #[doc(hidden)]
+    /// Adds the metasprites made from files with slices. This is used
+    /// internally by [include_aseprite].
+    #[must_use]
+    pub const fn with_metasprites(self, metasprites: &'static [MetaSprite]) -> Self {
+        Self {
+            metasprites,
+            ..self
+        }
     }
     #[must_use]
     /// Gets the tag map from the aseprite files. This allows reference to
@@ -145,6 +258,12 @@ impl Graphics {
     pub const fn sprites(&self) -> &[Sprite] {
         self.sprites
     }
+    /// Gets the metasprites made from files with slices in them, one for each
+    /// frame of each of those files in order.
+    #[must_use]
+    pub const fn metasprites(&self) -> &[MetaSprite] {
+        self.metasprites
+    }
 }
 
 /// Stores aseprite tags. Can be used to refer to animation sequences by name.
@@ -339,8 +458,14 @@ impl Size {
         (self as u16 >> 2, self as u16 & 0b11)
     }
 
-    pub(crate) fn layout(self) -> Layout {
-        Layout::from_size_align(self.number_of_tiles() * BYTES_PER_TILE_4BPP, 8).unwrap()
+    pub(crate) fn layout(self, format: TileFormat) -> Layout {
+        // 256 colour sprites have to start on an even tile
+        let align = match format {
+            TileFormat::FourBpp => 8,
+            TileFormat::EightBpp => BYTES_PER_TILE_8BPP,
+        };
+
+        Layout::from_size_align(self.number_of_tiles() * format.tile_size(), align).unwrap()
     }
 
     #[must_use]
@@ -1,27 +1,47 @@
-use core::{alloc::Allocator, ptr::NonNull};
+use core::{
+    alloc::{Allocator, Layout},
+    cell::Cell,
+    ptr::NonNull,
+};
+
+use portable_atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 
 use alloc::{
     boxed::Box,
     rc::{Rc, Weak},
+    vec::Vec,
 };
 
 use crate::{
-    agb_alloc::{block_allocator::BlockAllocator, bump_allocator::StartEnd, impl_zst_allocator},
-    display::palette16::Palette16,
+    agb_alloc::{
+        block_allocator::{BlockAllocator, BlockAllocatorUsage},
+        bump_allocator::StartEnd,
+        impl_zst_allocator,
+    },
+    display::{palette16::Palette16, tiled::TileFormat, Rgb15},
     hash_map::HashMap,
+    sync::Lock,
 };
 
 use super::{
-    sprite::{Size, Sprite},
-    BYTES_PER_TILE_4BPP,
+    sprite::{Palette256, Size, Sprite, SpritePalette},
+    BYTES_PER_TILE_4BPP, BYTES_PER_TILE_8BPP,
 };
 
 pub const PALETTE_SPRITE: usize = 0x0500_0200;
 pub const TILE_SPRITE: usize = 0x06010000;
 
+/// The size of the lower part of sprite vram that the bitmap display modes use
+/// for the background.
+const BITMAP_AREA_SIZE: usize = 1024 * 16;
+
+/// Where sprites can start being allocated, which changes with the
+/// [SpriteVramArea].
+static SPRITE_VRAM_START: AtomicUsize = AtomicUsize::new(TILE_SPRITE);
+
 static SPRITE_ALLOCATOR: BlockAllocator = unsafe {
     BlockAllocator::new(StartEnd {
-        start: || TILE_SPRITE,
+        start: || SPRITE_VRAM_START.load(Ordering::Relaxed),
         end: || TILE_SPRITE + 1024 * 8 * 4,
     })
 };
@@ -41,6 +61,153 @@ pub struct PaletteAllocator;
 
 impl_zst_allocator!(PaletteAllocator, PALETTE_ALLOCATOR);
 
+/// Every sprite in vram, so that they can be found when compacting.
+static SPRITES_IN_VRAM: Lock<Vec<Weak<SpriteVramData>>> = Lock::new(Vec::new());
+
+/// Whether sprites get compacted when objects are written to OAM. Set this
+/// with [SpriteLoader::set_compact_on_commit].
+static COMPACT_ON_COMMIT: AtomicBool = AtomicBool::new(false);
+
+/// Goes up every time sprites are moved around in vram, so that objects know
+/// when they have to look up where their sprite is again.
+static SPRITE_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+pub(crate) fn sprite_generation() -> u32 {
+    SPRITE_GENERATION.load(Ordering::Relaxed)
+}
+
+/// Compacts sprite vram if that has been turned on and there are gaps between
+/// the sprites. This is done by [`OamUnmanaged::commit_sprite_vram`](crate::display::object::OamUnmanaged::commit_sprite_vram)
+/// before objects are written to OAM, so that the objects using moved sprites
+/// are updated in the same frame.
+pub(crate) fn compact_on_commit() {
+    if !COMPACT_ON_COMMIT.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let usage = unsafe { SPRITE_ALLOCATOR.usage() };
+    if usage.largest_free != usage.free {
+        compact_sprites();
+    }
+}
+
+fn compact_sprites() -> usize {
+    let mut sprites: Vec<Rc<SpriteVramData>> = {
+        let mut sprites_in_vram = SPRITES_IN_VRAM.lock();
+        sprites_in_vram.retain(|sprite| Weak::strong_count(sprite) != 0);
+        sprites_in_vram.iter().filter_map(Weak::upgrade).collect()
+    };
+
+    sprites.sort_unstable_by_key(|sprite| sprite.location.get().0);
+
+    let mut moved = 0;
+    let mut buffer: Vec<u16> = Vec::new();
+
+    for sprite in &sprites {
+        let layout = sprite.size.layout(sprite.palette.format());
+        let old_location = sprite.location.get().as_sprite_ptr();
+
+        // Freeing and reallocating puts the sprite in the first gap that
+        // fits it, but it also overwrites some of the sprite so it needs
+        // copying out first.
+        buffer.clear();
+        buffer.extend_from_slice(unsafe {
+            core::slice::from_raw_parts(old_location.cast::<u16>(), layout.size() / 2)
+        });
+
+        let new_location = unsafe {
+            SPRITE_ALLOCATOR.dealloc(old_location, layout);
+            SPRITE_ALLOCATOR.alloc(layout)
+        }
+        .expect("space that was just freed should be available");
+
+        unsafe {
+            new_location
+                .as_ptr()
+                .cast::<u16>()
+                .copy_from_nonoverlapping(buffer.as_ptr(), buffer.len());
+        }
+
+        if new_location.as_ptr() != old_location {
+            sprite.location.set(Location::from_sprite_ptr(new_location));
+            moved += 1;
+        }
+    }
+
+    unsafe { SPRITE_ALLOCATOR.reclaim_end() };
+
+    if moved != 0 {
+        SPRITE_GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    moved
+}
+
+/// Which part of the video memory for sprites is available to load sprites
+/// into. Set this with [SpriteLoader::set_vram_area].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SpriteVramArea {
+    /// All 32KB, which is the case in the tiled display modes.
+    #[default]
+    Full,
+    /// Only the upper 16KB, as the bitmap display modes use the lower half
+    /// for the background. Objects can only use tile indices from 512 up in
+    /// these modes.
+    BitmapMode,
+}
+
+impl SpriteVramArea {
+    fn start(self) -> usize {
+        match self {
+            SpriteVramArea::Full => TILE_SPRITE,
+            SpriteVramArea::BitmapMode => TILE_SPRITE + BITMAP_AREA_SIZE,
+        }
+    }
+}
+
+/// How much of an area of video memory is in use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VramUsage {
+    total: usize,
+    free: usize,
+    largest_free_block: usize,
+}
+
+impl VramUsage {
+    fn new(usage: BlockAllocatorUsage) -> Self {
+        Self {
+            total: usage.total,
+            free: usage.free,
+            largest_free_block: usage.largest_free,
+        }
+    }
+
+    /// The size of the area in bytes.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of bytes in use.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.total - self.free
+    }
+
+    /// The number of bytes not in use.
+    #[must_use]
+    pub fn free(&self) -> usize {
+        self.free
+    }
+
+    /// The size of the largest allocation that would fit, in bytes. When this
+    /// is much smaller than [free](Self::free) the memory is fragmented.
+    #[must_use]
+    pub fn largest_free_block(&self) -> usize {
+        self.largest_free_block
+    }
+}
+
 /// The Sprite Id is a thin wrapper around the pointer to the sprite in
 /// rom and is therefore a unique identifier to a sprite
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -58,8 +225,11 @@ impl SpriteId {
 struct PaletteId(usize);
 
 impl PaletteId {
-    fn from_static_palette(palette: &'static Palette16) -> PaletteId {
-        PaletteId(palette as *const _ as usize)
+    fn from_sprite_palette(palette: SpritePalette) -> PaletteId {
+        match palette {
+            SpritePalette::Colours16(palette) => PaletteId(palette as *const _ as usize),
+            SpritePalette::Colours256(palette) => PaletteId(palette as *const _ as usize),
+        }
     }
 }
 
@@ -87,14 +257,26 @@ impl Location {
     }
 }
 
+fn palettes_layout(count: usize) -> Layout {
+    Layout::from_size_align(
+        Palette16::layout().size() * count,
+        Palette16::layout().align(),
+    )
+    .unwrap()
+}
+
 #[derive(Debug)]
 struct PaletteVramData {
     location: Location,
+    count: usize,
+    format: TileFormat,
 }
 
 impl Drop for PaletteVramData {
     fn drop(&mut self) {
-        unsafe { PALETTE_ALLOCATOR.dealloc(self.location.as_palette_ptr(), Palette16::layout()) }
+        unsafe {
+            PALETTE_ALLOCATOR.dealloc(self.location.as_palette_ptr(), palettes_layout(self.count));
+        }
     }
 }
 
@@ -107,42 +289,102 @@ pub struct PaletteVram {
 impl PaletteVram {
     /// Attempts to allocate a new palette in sprite vram
     pub fn new(palette: &Palette16) -> Result<PaletteVram, LoaderError> {
-        let allocated = unsafe { PALETTE_ALLOCATOR.alloc(Palette16::layout()) }
-            .ok_or(LoaderError::PaletteFull)?;
+        Self::from_palettes(core::slice::from_ref(palette), TileFormat::FourBpp)
+    }
 
-        unsafe {
-            allocated
-                .as_ptr()
-                .cast::<u16>()
-                .copy_from_nonoverlapping(palette.colours.as_ptr(), palette.colours.len());
+    /// Attempts to allocate a new 256 colour palette in sprite vram. This goes
+    /// at the start of the object palette, so it needs as many palettes from
+    /// the start as it is made of to be free. In particular, this fails if a
+    /// different 256 colour palette is already in vram.
+    pub fn new_256(palette: &Palette256) -> Result<PaletteVram, LoaderError> {
+        Self::from_palettes(palette.palettes(), TileFormat::EightBpp)
+    }
+
+    fn from_sprite_palette(palette: SpritePalette) -> Result<PaletteVram, LoaderError> {
+        match palette {
+            SpritePalette::Colours16(palette) => Self::new(palette),
+            SpritePalette::Colours256(palette) => Self::new_256(palette),
+        }
+    }
+
+    fn from_palettes(
+        palettes: &[Palette16],
+        format: TileFormat,
+    ) -> Result<PaletteVram, LoaderError> {
+        let layout = palettes_layout(palettes.len());
+
+        if format == TileFormat::EightBpp {
+            // so that a free space at the start joined to the rest is found
+            unsafe { PALETTE_ALLOCATOR.reclaim_end() };
+        }
+
+        let allocated =
+            unsafe { PALETTE_ALLOCATOR.alloc(layout) }.ok_or(LoaderError::PaletteFull)?;
+
+        // 256 colour sprites use the object palette directly, so their palette
+        // has to be at the start of it
+        if format == TileFormat::EightBpp && allocated.as_ptr() as usize != PALETTE_SPRITE {
+            unsafe { PALETTE_ALLOCATOR.dealloc(allocated.as_ptr(), layout) };
+            return Err(LoaderError::PaletteFull);
+        }
+
+        for (i, palette) in palettes.iter().enumerate() {
+            unsafe {
+                allocated
+                    .as_ptr()
+                    .cast::<Rgb15>()
+                    .add(i * palette.colours.len())
+                    .copy_from_nonoverlapping(palette.colours.as_ptr(), palette.colours.len());
+            }
         }
 
         Ok(PaletteVram {
             data: Rc::new(PaletteVramData {
                 location: Location::from_palette_ptr(allocated),
+                count: palettes.len(),
+                format,
             }),
         })
     }
+
+    #[must_use]
+    /// Whether this palette is for 16 colour or 256 colour sprites
+    pub fn format(&self) -> TileFormat {
+        self.data.format
+    }
 }
 
 #[derive(Debug)]
 struct SpriteVramData {
-    location: Location,
+    location: Cell<Location>,
     size: Size,
     palette: PaletteVram,
 }
 
 impl Drop for SpriteVramData {
     fn drop(&mut self) {
-        unsafe { SPRITE_ALLOCATOR.dealloc(self.location.as_sprite_ptr(), self.size.layout()) }
+        unsafe {
+            SPRITE_ALLOCATOR.dealloc(
+                self.location.get().as_sprite_ptr(),
+                self.size.layout(self.palette.format()),
+            );
+        }
     }
 }
 
+/// Why a sprite or palette couldn't be put into vram.
 #[non_exhaustive]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum LoaderError {
+    /// There isn't enough space left in sprite vram
     SpriteFull,
+    /// There isn't enough space left in palette vram
     PaletteFull,
+    /// The lower half of sprite vram couldn't be cleared for a bitmap mode,
+    /// either because the sprites wouldn't fit in the upper half or because
+    /// there are [DynamicSprite]s that haven't been put into vram yet, which
+    /// can't be moved.
+    BitmapAreaInUse,
 }
 
 /// A sprite that is currently loaded into vram.
@@ -161,13 +403,15 @@ pub struct SpriteVram {
 
 impl SpriteVram {
     fn new(data: &[u8], size: Size, palette: PaletteVram) -> Result<SpriteVram, LoaderError> {
-        let allocated =
-            unsafe { SPRITE_ALLOCATOR.alloc(size.layout()) }.ok_or(LoaderError::SpriteFull)?;
+        let allocated = unsafe { SPRITE_ALLOCATOR.alloc(size.layout(palette.format())) }
+            .ok_or(LoaderError::SpriteFull)?;
+
         unsafe {
             allocated
                 .as_ptr()
                 .copy_from_nonoverlapping(data.as_ptr(), data.len());
         }
+
         Ok(unsafe { Self::from_location_size(allocated, size, palette) })
     }
 
@@ -176,17 +420,23 @@ impl SpriteVram {
         size: Size,
         palette: PaletteVram,
     ) -> SpriteVram {
-        SpriteVram {
-            data: Rc::new(SpriteVramData {
-                location: Location::from_sprite_ptr(data),
-                size,
-                palette,
-            }),
+        let data = Rc::new(SpriteVramData {
+            location: Cell::new(Location::from_sprite_ptr(data)),
+            size,
+            palette,
+        });
+
+        let mut sprites_in_vram = SPRITES_IN_VRAM.lock();
+        if sprites_in_vram.len() == sprites_in_vram.capacity() {
+            sprites_in_vram.retain(|sprite| Weak::strong_count(sprite) != 0);
         }
+        sprites_in_vram.push(Rc::downgrade(&data));
+
+        SpriteVram { data }
     }
 
     pub(crate) fn location(&self) -> u16 {
-        self.data.location.0 as u16
+        self.data.location.get().0 as u16
     }
 
     pub(crate) fn size(&self) -> Size {
@@ -196,6 +446,10 @@ impl SpriteVram {
     pub(crate) fn palette_location(&self) -> u16 {
         self.data.palette.data.location.0 as u16
     }
+
+    pub(crate) fn format(&self) -> TileFormat {
+        self.data.palette.format()
+    }
 }
 
 impl SpriteLoader {
@@ -211,20 +465,20 @@ impl SpriteLoader {
 
     fn try_get_vram_palette_asoc(
         palette_map: &mut HashMap<PaletteId, Weak<PaletteVramData>>,
-        palette: &'static Palette16,
+        palette: SpritePalette,
     ) -> Result<PaletteVram, LoaderError> {
-        let id = PaletteId::from_static_palette(palette);
+        let id = PaletteId::from_sprite_palette(palette);
         Ok(match palette_map.entry(id) {
             crate::hash_map::Entry::Occupied(mut entry) => match entry.get().upgrade() {
                 Some(data) => PaletteVram { data },
                 None => {
-                    let pv = PaletteVram::new(palette)?;
+                    let pv = PaletteVram::from_sprite_palette(palette)?;
                     entry.insert(Rc::downgrade(&pv.data));
                     pv
                 }
             },
             crate::hash_map::Entry::Vacant(entry) => {
-                let pv = PaletteVram::new(palette)?;
+                let pv = PaletteVram::from_sprite_palette(palette)?;
                 entry.insert(Rc::downgrade(&pv.data));
                 pv
             }
@@ -264,7 +518,21 @@ impl SpriteLoader {
         &mut self,
         palette: &'static Palette16,
     ) -> Result<PaletteVram, LoaderError> {
-        Self::try_get_vram_palette_asoc(&mut self.static_palette_map, palette)
+        Self::try_get_vram_palette_asoc(
+            &mut self.static_palette_map,
+            SpritePalette::Colours16(palette),
+        )
+    }
+
+    /// Attempts to allocate a static 256 colour palette
+    pub fn try_get_vram_palette_256(
+        &mut self,
+        palette: &'static Palette256,
+    ) -> Result<PaletteVram, LoaderError> {
+        Self::try_get_vram_palette_asoc(
+            &mut self.static_palette_map,
+            SpritePalette::Colours256(palette),
+        )
     }
 
     /// Allocates a sprite to vram, panics if it cannot fit.
@@ -279,6 +547,12 @@ impl SpriteLoader {
             .expect("cannot create sprite")
     }
 
+    /// Allocates a 256 colour palette to vram, panics if it cannot fit.
+    pub fn get_vram_palette_256(&mut self, palette: &'static Palette256) -> PaletteVram {
+        self.try_get_vram_palette_256(palette)
+            .expect("cannot create palette")
+    }
+
     pub(crate) fn new() -> Self {
         Self {
             static_palette_map: HashMap::new(),
@@ -286,6 +560,137 @@ impl SpriteLoader {
         }
     }
 
+    /// How much of the video memory for sprites is in use.
+    #[must_use]
+    pub fn sprite_vram_usage(&self) -> VramUsage {
+        VramUsage::new(unsafe { SPRITE_ALLOCATOR.usage() })
+    }
+
+    /// How much of the object palette is in use.
+    #[must_use]
+    pub fn palette_vram_usage(&self) -> VramUsage {
+        VramUsage::new(unsafe { PALETTE_ALLOCATOR.usage() })
+    }
+
+    /// Moves the sprites in vram together to get rid of the gaps between them,
+    /// so that larger sprites can be loaded. Returns the number of sprites that
+    /// were moved.
+    ///
+    /// Objects using the moved sprites are updated the next time they are
+    /// written to OAM, so call this during vblank just before you do that to
+    /// avoid objects showing the wrong graphics for a frame, or use
+    /// [set_compact_on_commit](Self::set_compact_on_commit) to have it done
+    /// for you. [DynamicSprite]s which haven't been put into vram yet don't
+    /// get moved.
+    pub fn compact(&mut self) -> usize {
+        compact_sprites()
+    }
+
+    /// Sets whether sprites are compacted automatically, which is off by
+    /// default. When this is on, the sprites are moved together whenever there
+    /// are gaps between them on each call to
+    /// [OamUnmanaged::commit_sprite_vram](crate::display::object::OamUnmanaged::commit_sprite_vram)
+    /// or [OamManaged::commit](crate::display::object::OamManaged::commit), and
+    /// objects written afterwards use the new locations of their sprites. See
+    /// [compact](Self::compact).
+    pub fn set_compact_on_commit(&mut self, compact: bool) {
+        COMPACT_ON_COMMIT.store(compact, Ordering::Relaxed);
+    }
+
+    /// Which part of sprite vram sprites are loaded into.
+    #[must_use]
+    pub fn vram_area(&self) -> SpriteVramArea {
+        if SPRITE_VRAM_START.load(Ordering::Relaxed) == TILE_SPRITE {
+            SpriteVramArea::Full
+        } else {
+            SpriteVramArea::BitmapMode
+        }
+    }
+
+    /// Changes which part of sprite vram sprites are loaded into. Set this to
+    /// [SpriteVramArea::BitmapMode] before switching to a bitmap display mode,
+    /// and back to [SpriteVramArea::Full] after switching back to a tiled one.
+    ///
+    /// Sprites already in vram are moved out of the lower half when switching
+    /// to [SpriteVramArea::BitmapMode], and objects using them are updated the
+    /// next time they are written to OAM. If that isn't possible then this
+    /// returns [LoaderError::BitmapAreaInUse] and nothing changes.
+    pub fn set_vram_area(&mut self, area: SpriteVramArea) -> Result<(), LoaderError> {
+        let old_start = SPRITE_VRAM_START.load(Ordering::Relaxed);
+        if old_start == area.start() {
+            return Ok(());
+        }
+
+        match area {
+            SpriteVramArea::Full => {
+                SPRITE_VRAM_START.store(area.start(), Ordering::Relaxed);
+                unsafe { SPRITE_ALLOCATOR.extend_start(old_start) };
+            }
+            SpriteVramArea::BitmapMode => Self::move_to_upper_half()?,
+        }
+
+        Ok(())
+    }
+
+    /// Moves every sprite into the upper half of sprite vram by copying them
+    /// all out, then loading them back in one after another from the start of
+    /// the upper half.
+    fn move_to_upper_half() -> Result<(), LoaderError> {
+        let mut sprites: Vec<Rc<SpriteVramData>> = {
+            let mut sprites_in_vram = SPRITES_IN_VRAM.lock();
+            sprites_in_vram.retain(|sprite| Weak::strong_count(sprite) != 0);
+            sprites_in_vram.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        sprites.sort_unstable_by_key(|sprite| sprite.location.get().0);
+
+        let layout = |sprite: &SpriteVramData| sprite.size.layout(sprite.palette.format());
+        let used: usize = sprites.iter().map(|sprite| layout(sprite).size()).sum();
+        // the sprites are put back in order, so include any gaps for alignment
+        let needed = sprites
+            .iter()
+            .map(|sprite| layout(sprite))
+            .fold(0usize, |end, layout| {
+                end.next_multiple_of(layout.align()) + layout.size()
+            });
+
+        // Anything in vram that isn't a known sprite is a dynamic sprite that
+        // nothing keeps track of, so it can't be moved.
+        let usage = unsafe { SPRITE_ALLOCATOR.usage() };
+        if usage.total - usage.free != used || needed > 1024 * 32 - BITMAP_AREA_SIZE {
+            return Err(LoaderError::BitmapAreaInUse);
+        }
+
+        let data: Vec<Vec<u16>> = sprites
+            .iter()
+            .map(|sprite| {
+                let location = sprite.location.get().as_sprite_ptr().cast::<u16>();
+                unsafe { core::slice::from_raw_parts(location, layout(sprite).size() / 2) }.to_vec()
+            })
+            .collect();
+
+        SPRITE_VRAM_START.store(SpriteVramArea::BitmapMode.start(), Ordering::Relaxed);
+        unsafe { SPRITE_ALLOCATOR.reset() };
+
+        for (sprite, data) in sprites.iter().zip(&data) {
+            let location = unsafe { SPRITE_ALLOCATOR.alloc(layout(sprite)) }
+                .expect("sprites should fit in the upper half of vram");
+
+            unsafe {
+                location
+                    .as_ptr()
+                    .cast::<u16>()
+                    .copy_from_nonoverlapping(data.as_ptr(), data.len());
+            }
+
+            sprite.location.set(Location::from_sprite_ptr(location));
+        }
+
+        SPRITE_GENERATION.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
     /// Remove internal references to sprites that no longer exist in vram. If
     /// you neglect calling this, memory will leak over time in relation to the
     /// total number of different sprites used. It will not leak vram.
@@ -294,6 +699,9 @@ impl SpriteLoader {
             .retain(|_, v| Weak::strong_count(v) != 0);
         self.static_palette_map
             .retain(|_, v| Weak::strong_count(v) != 0);
+        SPRITES_IN_VRAM
+            .lock()
+            .retain(|sprite| Weak::strong_count(sprite) != 0);
     }
 }
 
@@ -307,12 +715,13 @@ impl Default for SpriteLoader {
 pub struct DynamicSprite {
     data: Box<[u16], SpriteAllocator>,
     size: Size,
+    format: TileFormat,
 }
 
 impl Clone for DynamicSprite {
     fn clone(&self) -> Self {
         let allocation = SpriteAllocator
-            .allocate(self.size.layout())
+            .allocate(self.size.layout(self.format))
             .expect("cannot allocate dynamic sprite");
 
         let allocation = core::ptr::slice_from_raw_parts_mut(
@@ -327,6 +736,7 @@ impl Clone for DynamicSprite {
         Self {
             data,
             size: self.size,
+            format: self.format,
         }
     }
 }
@@ -334,8 +744,17 @@ impl Clone for DynamicSprite {
 impl DynamicSprite {
     /// Creates a new dynamic sprite of a given size
     pub fn try_new(size: Size) -> Result<Self, LoaderError> {
+        Self::try_new_with_format(size, TileFormat::FourBpp)
+    }
+
+    /// Creates a new 256 colour dynamic sprite of a given size
+    pub fn try_new_256(size: Size) -> Result<Self, LoaderError> {
+        Self::try_new_with_format(size, TileFormat::EightBpp)
+    }
+
+    fn try_new_with_format(size: Size, format: TileFormat) -> Result<Self, LoaderError> {
         let allocation = SpriteAllocator
-            .allocate_zeroed(size.layout())
+            .allocate_zeroed(size.layout(format))
             .map_err(|_| LoaderError::SpriteFull)?;
 
         let allocation = core::ptr::slice_from_raw_parts_mut(
@@ -345,7 +764,7 @@ impl DynamicSprite {
 
         let data = unsafe { Box::from_raw_in(allocation, SpriteAllocator) };
 
-        Ok(DynamicSprite { data, size })
+        Ok(DynamicSprite { data, size, format })
     }
 
     #[must_use]
@@ -354,12 +773,22 @@ impl DynamicSprite {
         Self::try_new(size).expect("couldn't allocate dynamic sprite")
     }
 
+    #[must_use]
+    /// Creates a new 256 colour dynamic sprite of a given size
+    pub fn new_256(size: Size) -> Self {
+        Self::try_new_256(size).expect("couldn't allocate dynamic sprite")
+    }
+
+    #[must_use]
+    /// Whether this is a 16 colour or 256 colour sprite
+    pub fn format(&self) -> TileFormat {
+        self.format
+    }
+
     /// Set the pixel of a sprite to a given paletted pixel. Panics if the
     /// coordinate is out of range of the sprite or if the paletted pixel is
-    /// greater than 4 bits.
+    /// greater than 4 bits, or 8 bits for a 256 colour sprite.
     pub fn set_pixel(&mut self, x: usize, y: usize, paletted_pixel: usize) {
-        assert!(paletted_pixel < 0x10);
-
         let (sprite_pixel_x, sprite_pixel_y) = self.size.to_width_height();
         assert!(x < sprite_pixel_x, "x too big for sprite size");
         assert!(y < sprite_pixel_y, "y too big for sprite size");
@@ -372,6 +801,21 @@ impl DynamicSprite {
 
         let (x_in_tile, y_in_tile) = (x % 8, y % 8);
 
+        if self.format == TileFormat::EightBpp {
+            assert!(paletted_pixel < 0x100);
+
+            let half_word_to_modify =
+                (tile_number_to_modify * BYTES_PER_TILE_8BPP + y_in_tile * 8 + x_in_tile) / 2;
+            let byte_to_modify = (x % 2) * 8;
+
+            let half_word = self.data[half_word_to_modify];
+            self.data[half_word_to_modify] = (half_word & !(0xff << byte_to_modify))
+                | ((paletted_pixel as u16) << byte_to_modify);
+            return;
+        }
+
+        assert!(paletted_pixel < 0x10);
+
         let half_word_to_modify_in_tile = x_in_tile / 4 + y_in_tile * 2;
 
         let half_word_to_modify =
@@ -387,20 +831,170 @@ impl DynamicSprite {
 
     /// Wipes the sprite
     pub fn clear(&mut self, paletted_pixel: usize) {
-        assert!(paletted_pixel < 0x10);
-        let reset = (paletted_pixel
-            | (paletted_pixel << 4)
-            | (paletted_pixel << 8)
-            | (paletted_pixel << 12)) as u16;
+        let reset = match self.format {
+            TileFormat::FourBpp => {
+                assert!(paletted_pixel < 0x10);
+                (paletted_pixel
+                    | (paletted_pixel << 4)
+                    | (paletted_pixel << 8)
+                    | (paletted_pixel << 12)) as u16
+            }
+            TileFormat::EightBpp => {
+                assert!(paletted_pixel < 0x100);
+                (paletted_pixel | (paletted_pixel << 8)) as u16
+            }
+        };
         self.data.fill(reset);
     }
 
     #[must_use]
     /// Tries to copy the sprite to vram to be used to set object sprites.
-    /// Panics if it cannot be allocated.
+    /// Panics if it cannot be allocated, or if the palette is for a different
+    /// number of colours than the sprite.
     pub fn to_vram(self, palette: PaletteVram) -> SpriteVram {
+        assert_eq!(
+            self.format,
+            palette.format(),
+            "sprite and palette have different numbers of colours"
+        );
+
         let data = unsafe { NonNull::new_unchecked(Box::leak(self.data).as_mut_ptr()) };
 
         unsafe { SpriteVram::from_location_size(data.cast(), self.size, palette) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn compacting_fills_gaps(_gba: &mut crate::Gba) {
+        let mut loader = SpriteLoader::new();
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+
+        let sprite = |colour| {
+            let mut sprite = DynamicSprite::new(Size::S16x16);
+            sprite.clear(colour);
+            sprite.to_vram(palette.clone())
+        };
+
+        let first = sprite(1);
+        let gap = sprite(2);
+        let last = sprite(3);
+
+        let location = last.location();
+        drop(gap);
+
+        let usage = loader.sprite_vram_usage();
+        assert!(usage.largest_free_block() < usage.free());
+
+        assert!(loader.compact() >= 1);
+        assert!(last.location() < location);
+        assert_eq!(first.location() + 4, last.location());
+
+        let usage = loader.sprite_vram_usage();
+        assert_eq!(usage.largest_free_block(), usage.free());
+
+        let pixels = Location(last.location() as usize)
+            .as_sprite_ptr()
+            .cast::<u16>();
+        for i in 0..Size::S16x16.layout(TileFormat::FourBpp).size() / 2 {
+            assert_eq!(unsafe { pixels.add(i).read_volatile() }, 0x3333);
+        }
+    }
+
+    #[test_case]
+    fn bitmap_mode_moves_sprites_to_upper_half(_gba: &mut crate::Gba) {
+        let mut loader = SpriteLoader::new();
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+
+        let mut sprite = DynamicSprite::new(Size::S16x16);
+        sprite.clear(5);
+        let sprite = sprite.to_vram(palette.clone());
+        assert!(sprite.location() < 512);
+
+        let unplaced = DynamicSprite::new(Size::S16x16);
+        assert_eq!(
+            loader.set_vram_area(SpriteVramArea::BitmapMode),
+            Err(LoaderError::BitmapAreaInUse)
+        );
+        assert_eq!(loader.vram_area(), SpriteVramArea::Full);
+        drop(unplaced);
+
+        loader.set_vram_area(SpriteVramArea::BitmapMode).unwrap();
+        assert!(sprite.location() >= 512);
+        assert_eq!(loader.sprite_vram_usage().total(), 1024 * 16);
+
+        let pixels = Location(sprite.location() as usize)
+            .as_sprite_ptr()
+            .cast::<u16>();
+        for i in 0..Size::S16x16.layout(TileFormat::FourBpp).size() / 2 {
+            assert_eq!(unsafe { pixels.add(i).read_volatile() }, 0x5555);
+        }
+
+        loader.set_vram_area(SpriteVramArea::Full).unwrap();
+        assert_eq!(loader.sprite_vram_usage().total(), 1024 * 32);
+
+        let other = DynamicSprite::new(Size::S16x16).to_vram(palette);
+        assert!(other.location() < 512);
+    }
+
+    #[test_case]
+    fn palette_256_is_shared_from_the_start_of_the_object_palette(_gba: &mut crate::Gba) {
+        static PALETTES: [Palette16; 2] = [
+            Palette16::new([Rgb15::BLACK; 16]),
+            Palette16::new([Rgb15::WHITE; 16]),
+        ];
+        static PALETTE: Palette256 = Palette256::new(&PALETTES);
+        static OTHER_PALETTE: Palette256 = Palette256::new(&PALETTES);
+
+        let in_the_way = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+        assert_eq!(
+            PaletteVram::new_256(&PALETTE).err(),
+            Some(LoaderError::PaletteFull)
+        );
+        drop(in_the_way);
+
+        let palette = PaletteVram::new_256(&PALETTE).unwrap();
+        assert_eq!(palette.data.location.0, 0);
+        assert_eq!(
+            PaletteVram::new_256(&OTHER_PALETTE).err(),
+            Some(LoaderError::PaletteFull)
+        );
+
+        let palette_16 = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+        assert_eq!(palette_16.data.location.0, PALETTES.len());
+
+        // a 16 colour sprite first so that the 256 colour one needs aligning
+        let _sprite_16 = DynamicSprite::new(Size::S8x8).to_vram(palette_16);
+
+        let mut sprite = DynamicSprite::new_256(Size::S8x8);
+        sprite.set_pixel(0, 0, 17);
+        let sprite = sprite.to_vram(palette);
+
+        assert!(sprite.location().is_multiple_of(2));
+        let pixels = Location(sprite.location() as usize)
+            .as_sprite_ptr()
+            .cast::<u16>();
+        assert_eq!(unsafe { pixels.read_volatile() }, 17);
+    }
+
+    #[test_case]
+    fn sprites_can_exactly_fill_the_upper_half(_gba: &mut crate::Gba) {
+        let mut loader = SpriteLoader::new();
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+
+        let sprites: Vec<_> = (0..BITMAP_AREA_SIZE
+            / Size::S64x64.layout(TileFormat::FourBpp).size())
+            .map(|_| DynamicSprite::new(Size::S64x64).to_vram(palette.clone()))
+            .collect();
+
+        loader.set_vram_area(SpriteVramArea::BitmapMode).unwrap();
+        assert_eq!(loader.sprite_vram_usage().free(), 0);
+        assert!(sprites.iter().all(|sprite| sprite.location() >= 512));
+
+        drop(sprites);
+        loader.set_vram_area(SpriteVramArea::Full).unwrap();
+    }
+}
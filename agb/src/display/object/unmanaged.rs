@@ -1,5 +1,7 @@
 mod attributes;
+mod batch;
 mod object;
 
 pub use attributes::{AffineMode, GraphicsMode};
+pub use batch::{OamBatch, OamBatchReport};
 pub use object::{OamIterator, OamSlot, OamUnmanaged, ObjectUnmanaged};
@@ -1,6 +1,6 @@
 use bilge::prelude::*;
 
-use crate::display::Priority;
+use crate::display::{tiled::TileFormat, Priority};
 
 use self::attributes::{
     ObjectAttribute0, ObjectAttribute1Affine, ObjectAttribute1Standard, ObjectAttribute2,
@@ -138,14 +138,29 @@ impl Attributes {
         self
     }
 
+    pub fn set_colour_mode(&mut self, format: TileFormat) -> &mut Self {
+        self.a0.set_colour_mode(match format {
+            TileFormat::FourBpp => ColourMode::Four,
+            TileFormat::EightBpp => ColourMode::Eight,
+        });
+
+        self
+    }
+
     pub fn set_affine_matrix(&mut self, affine_matrix_id: u16) -> &mut Self {
         self.a1a.set_affine_index(u5::new(affine_matrix_id as u8));
 
         self
     }
 
-    pub fn set_sprite(&mut self, sprite_id: u16, shape: u16, size: u16) -> &mut Self {
+    pub fn set_tile_index(&mut self, sprite_id: u16) -> &mut Self {
         self.a2.set_tile_index(u10::new(sprite_id));
+
+        self
+    }
+
+    pub fn set_sprite(&mut self, sprite_id: u16, shape: u16, size: u16) -> &mut Self {
+        self.set_tile_index(sprite_id);
         self.a1a.set_size(u2::new(size as u8));
         self.a1s.set_size(u2::new(size as u8));
         self.a0.set_shape(u2::new(shape as u8));
@@ -153,6 +168,16 @@ impl Attributes {
         self
     }
 
+    pub fn set_mosaic(&mut self, mosaic: bool) -> &mut Self {
+        self.a0.set_mosaic(mosaic);
+
+        self
+    }
+
+    pub fn mosaic(self) -> bool {
+        self.a0.mosaic()
+    }
+
     pub fn set_graphics_mode(&mut self, mode: GraphicsMode) -> &mut Self {
         self.a0.set_graphics_mode(match mode {
             GraphicsMode::Normal => GraphicsModeInternal::Normal,
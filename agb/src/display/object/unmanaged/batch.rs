@@ -0,0 +1,358 @@
+use alloc::vec::Vec;
+
+use super::{OamIterator, ObjectUnmanaged};
+
+/// The number of bits in a sort key, 2 for the priority and 32 for the z.
+const KEY_BITS: u32 = 34;
+
+/// Collects the objects to show in a frame and writes them to OAM sorted by
+/// their priority and then their z. This gives [`OamUnmanaged`](super::OamUnmanaged)
+/// the same ordering that [`OamManaged`](super::super::OamManaged) has, without
+/// you having to keep the objects in order yourself.
+///
+/// Objects with a lower priority value are drawn on top, as is the case on the
+/// GBA. Within the same priority, an object with a more *negative* z is drawn
+/// on top of an object with a more *positive* z. Objects with the same priority
+/// and z stay in the order they were submitted.
+///
+/// The GBA can only show 128 objects at once. If more than that are submitted,
+/// the ones at the back are left out. With [multiplexing](OamBatch::set_multiplexing)
+/// turned on, a different set of objects is left out each frame instead so that
+/// all of them flicker rather than some of them disappearing.
+///
+/// The storage used is kept between frames, so once the batch has grown to fit
+/// the number of objects you use it won't allocate.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::object::{OamBatch, OamUnmanaged, ObjectUnmanaged};
+///
+/// fn show(oam: &mut OamUnmanaged, batch: &mut OamBatch, player: &ObjectUnmanaged, enemies: &[ObjectUnmanaged]) {
+///     // the player is always drawn above the enemies
+///     batch.submit(player, -1);
+///     for enemy in enemies {
+///         batch.submit(enemy, 0);
+///     }
+///
+///     let report = batch.commit(&mut oam.iter());
+///     if report.overflowed() {
+///         agb::println!("{} objects weren't shown", report.hidden());
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct OamBatch {
+    objects: Vec<ObjectUnmanaged>,
+    keys: Vec<u64>,
+    order: Vec<u16>,
+    scratch: Vec<u16>,
+    multiplexing: bool,
+    rotation: usize,
+}
+
+/// What happened when an [`OamBatch`] was committed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OamBatchReport {
+    submitted: usize,
+    shown: usize,
+}
+
+impl OamBatchReport {
+    /// The number of visible objects submitted to the batch.
+    #[must_use]
+    pub fn submitted(&self) -> usize {
+        self.submitted
+    }
+
+    /// The number of objects written to OAM.
+    #[must_use]
+    pub fn shown(&self) -> usize {
+        self.shown
+    }
+
+    /// The number of objects left out because there were no OAM slots for them.
+    #[must_use]
+    pub fn hidden(&self) -> usize {
+        self.submitted - self.shown
+    }
+
+    /// Whether more objects were submitted than could be shown.
+    #[must_use]
+    pub fn overflowed(&self) -> bool {
+        self.hidden() > 0
+    }
+}
+
+impl OamBatch {
+    /// Creates an empty batch.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty batch with room for `capacity` objects before it needs
+    /// to allocate.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            objects: Vec::with_capacity(capacity),
+            keys: Vec::with_capacity(capacity),
+            order: Vec::with_capacity(capacity),
+            scratch: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Sets whether to rotate which objects are left out each frame when there
+    /// are more than can be shown. This is off by default.
+    pub fn set_multiplexing(&mut self, multiplexing: bool) -> &mut Self {
+        self.multiplexing = multiplexing;
+
+        self
+    }
+
+    /// Whether multiplexing is turned on.
+    #[must_use]
+    pub fn multiplexing(&self) -> bool {
+        self.multiplexing
+    }
+
+    /// Adds an object to be shown this frame with the given z. Objects that are
+    /// hidden are ignored.
+    pub fn submit(&mut self, object: &ObjectUnmanaged, z: i32) {
+        if !object.is_visible() {
+            return;
+        }
+
+        assert!(
+            self.objects.len() < u16::MAX as usize,
+            "too many objects in one batch"
+        );
+
+        let z = (z as u32) ^ (1 << 31);
+        self.keys
+            .push(((object.priority() as u64) << 32) | u64::from(z));
+        self.objects.push(object.clone());
+    }
+
+    /// The number of objects submitted so far this frame.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether no objects have been submitted this frame.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Writes the submitted objects to the remaining slots of `oam` in order,
+    /// and empties the batch ready for the next frame.
+    pub fn commit(&mut self, oam: &mut OamIterator) -> OamBatchReport {
+        let submitted = self.objects.len();
+        let shown = submitted.min(oam.len());
+        let hidden = submitted - shown;
+
+        self.order.clear();
+        self.order.extend(0..submitted as u16);
+        radix_sort(&self.keys, &mut self.order, &mut self.scratch);
+
+        // The objects shown are a window of the sorted objects which wraps
+        // around, and moves along by the number hidden every frame so that
+        // the objects hidden in consecutive frames are all different.
+        let start = if self.multiplexing && hidden > 0 {
+            let start = self.rotation % submitted;
+            self.rotation = start + hidden;
+            start
+        } else {
+            0
+        };
+
+        for (position, &index) in self.order.iter().enumerate() {
+            if (position + submitted - start) % submitted < shown {
+                oam.set_next(&self.objects[index as usize]);
+            }
+        }
+
+        self.objects.clear();
+        self.keys.clear();
+
+        OamBatchReport { submitted, shown }
+    }
+}
+
+/// Sorts `order` by the keys it refers to. This is a least significant digit
+/// radix sort, so it is stable and doesn't need to allocate beyond `scratch`.
+fn radix_sort(keys: &[u64], order: &mut Vec<u16>, scratch: &mut Vec<u16>) {
+    scratch.clear();
+    scratch.resize(order.len(), 0);
+
+    for shift in (0..KEY_BITS).step_by(8) {
+        let digit = |index: u16| (keys[index as usize] >> shift) as u8 as usize;
+
+        let mut counts = [0; 256];
+        for &index in order.iter() {
+            counts[digit(index)] += 1;
+        }
+
+        // nothing would move if every key has the same digit
+        if counts.contains(&order.len()) {
+            continue;
+        }
+
+        let mut total = 0;
+        for count in counts.iter_mut() {
+            let this_count = *count;
+            *count = total;
+            total += this_count;
+        }
+
+        for &index in order.iter() {
+            let digit = digit(index);
+            scratch[counts[digit]] = index;
+            counts[digit] += 1;
+        }
+
+        core::mem::swap(order, scratch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::Range;
+
+    use alloc::vec;
+
+    use crate::display::{
+        object::{DynamicSprite, PaletteVram, Size, OBJECT_ATTRIBUTE_MEMORY},
+        palette16::Palette16,
+        Priority, Rgb15,
+    };
+
+    use super::{super::OamUnmanaged, *};
+
+    /// Creates an object for each x position, which tells them apart in OAM
+    fn objects_at(xs: impl IntoIterator<Item = u16>) -> Vec<ObjectUnmanaged> {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+        let sprite = DynamicSprite::new(Size::S8x8).to_vram(palette);
+
+        xs.into_iter()
+            .map(|x| {
+                let mut object = ObjectUnmanaged::new(sprite.clone());
+                object.show().set_x(x);
+                object
+            })
+            .collect()
+    }
+
+    /// The x positions of the objects written to the OAM slots in `slots`
+    fn oam_xs(slots: Range<usize>) -> Vec<u16> {
+        slots
+            .map(
+                |slot| unsafe { OBJECT_ATTRIBUTE_MEMORY.add(slot * 4 + 1).read_volatile() } & 0x1ff,
+            )
+            .collect()
+    }
+
+    /// Submits `objects` in order with a z of 0 to only the last `slots` slots
+    /// of OAM, so some of them don't fit
+    fn commit_to_last_slots(
+        oam: &mut OamUnmanaged,
+        batch: &mut OamBatch,
+        objects: &[ObjectUnmanaged],
+        slots: usize,
+    ) -> OamBatchReport {
+        for object in objects {
+            batch.submit(object, 0);
+        }
+
+        let mut iter = oam.iter();
+        for _ in 0..128 - slots {
+            iter.next();
+        }
+
+        batch.commit(&mut iter)
+    }
+
+    #[test_case]
+    fn commit_writes_objects_in_order(gba: &mut crate::Gba) {
+        let (mut oam, _loader) = gba.display.object.get_unmanaged();
+        let mut objects = objects_at([10, 20, 30, 40, 50]);
+        objects[0].set_priority(Priority::P1);
+        objects[4].hide();
+
+        let mut batch = OamBatch::new();
+        for (object, z) in objects.iter().zip([0, 5, -3, 5, -10]) {
+            batch.submit(object, z);
+        }
+        assert_eq!(batch.len(), 4);
+
+        let report = batch.commit(&mut oam.iter());
+
+        assert_eq!(report.submitted(), 4);
+        assert_eq!(report.shown(), 4);
+        assert!(!report.overflowed());
+        assert!(batch.is_empty());
+        assert_eq!(oam_xs(0..4), [30, 20, 40, 10]);
+    }
+
+    #[test_case]
+    fn overflowing_objects_are_reported_as_hidden(gba: &mut crate::Gba) {
+        let (mut oam, _loader) = gba.display.object.get_unmanaged();
+        let objects = objects_at([1, 2, 3, 4, 5]);
+
+        let mut batch = OamBatch::new();
+        let report = commit_to_last_slots(&mut oam, &mut batch, &objects, 3);
+
+        assert_eq!(report.submitted(), 5);
+        assert_eq!(report.shown(), 3);
+        assert_eq!(report.hidden(), 2);
+        assert!(report.overflowed());
+        assert_eq!(oam_xs(125..128), [1, 2, 3]);
+
+        // without multiplexing the same objects are left out every frame
+        commit_to_last_slots(&mut oam, &mut batch, &objects, 3);
+        assert_eq!(oam_xs(125..128), [1, 2, 3]);
+    }
+
+    #[test_case]
+    fn multiplexing_rotates_the_hidden_objects(gba: &mut crate::Gba) {
+        let (mut oam, _loader) = gba.display.object.get_unmanaged();
+        let objects = objects_at([1, 2, 3, 4, 5]);
+
+        let mut batch = OamBatch::new();
+        batch.set_multiplexing(true);
+
+        let mut shown_frames = Vec::new();
+        for _ in 0..3 {
+            let report = commit_to_last_slots(&mut oam, &mut batch, &objects, 3);
+            assert_eq!(report.hidden(), 2);
+            shown_frames.push(oam_xs(125..128));
+        }
+
+        // the objects stay in order, but a different two are left out each frame
+        assert_eq!(shown_frames, [vec![1, 2, 3], vec![3, 4, 5], vec![1, 2, 5]]);
+    }
+
+    #[test_case]
+    fn radix_sort_is_stable(_gba: &mut crate::Gba) {
+        let key = |priority: u64, z: i32| (priority << 32) | u64::from((z as u32) ^ (1 << 31));
+
+        let keys = [
+            key(1, 0),
+            key(0, 5),
+            key(0, -300),
+            key(1, 0),
+            key(0, 5),
+            key(3, i32::MIN),
+        ];
+
+        let mut order: Vec<u16> = (0..keys.len() as u16).collect();
+        let mut scratch = Vec::new();
+        radix_sort(&keys, &mut order, &mut scratch);
+
+        assert_eq!(order, vec![2, 1, 4, 0, 3, 5]);
+    }
+}
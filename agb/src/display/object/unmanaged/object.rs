@@ -1,12 +1,16 @@
-use core::{cell::UnsafeCell, marker::PhantomData};
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+};
 
 use agb_fixnum::Vector2D;
 use alloc::vec::Vec;
 
 use crate::display::{
     object::{
-        affine::AffineMatrixVram, sprites::SpriteVram, AffineMatrixInstance,
-        OBJECT_ATTRIBUTE_MEMORY,
+        affine::AffineMatrixVram,
+        sprites::{compact_on_commit, sprite_generation, SpriteVram},
+        AffineMatrixInstance, OBJECT_ATTRIBUTE_MEMORY,
     },
     Priority,
 };
@@ -122,7 +126,17 @@ impl OamSlot<'_> {
     /// compiler doesn't have to copy around the slot structure while still
     /// keeping move semantics. This is slightly faster in benchmarks.
     fn set_inner(&self, object: &ObjectUnmanaged) {
-        let mut attributes = object.attributes;
+        // the sprite could have been moved by compacting since it was last
+        // written, so update the object to use its new location
+        let generation = sprite_generation();
+        if object.sprite_generation.get() != generation {
+            let mut attributes = object.attributes.get();
+            attributes.set_tile_index(object.sprite.location());
+            object.attributes.set(attributes);
+            object.sprite_generation.set(generation);
+        }
+
+        let mut attributes = object.attributes.get();
         // SAFETY: This function is not reentrant and we currently hold a mutable borrow of the [UnmanagedOAM].
         let frame_data = unsafe { &mut *self.frame_data.get() };
 
@@ -170,8 +184,14 @@ impl<'oam> Iterator for OamIterator<'oam> {
             })
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (128 - self.index, Some(128 - self.index))
+    }
 }
 
+impl ExactSizeIterator for OamIterator<'_> {}
+
 impl Drop for OamIterator<'_> {
     fn drop(&mut self) {
         let number_writen = self.index;
@@ -188,6 +208,15 @@ impl Drop for OamIterator<'_> {
 }
 
 impl OamUnmanaged<'_> {
+    /// Compacts sprite vram if
+    /// [`set_compact_on_commit`](super::super::SpriteLoader::set_compact_on_commit)
+    /// is on and there are gaps between the sprites. Call this during vblank
+    /// before [`iter`](Self::iter), so that the objects written afterwards use
+    /// the new locations of their sprites.
+    pub fn commit_sprite_vram(&mut self) {
+        compact_on_commit();
+    }
+
     /// Returns the OamSlot iterator for this frame.
     pub fn iter(&mut self) -> OamIterator<'_> {
         let frame_data = self.frame_data.get_mut();
@@ -226,8 +255,9 @@ impl OamUnmanaged<'_> {
 /// An object to be used by the [`OamUnmanaged`] system. Changes made here are
 /// reflected when set to an OamSlot using [`OamSlot::set`].
 pub struct ObjectUnmanaged {
-    attributes: Attributes,
+    attributes: Cell<Attributes>,
     sprite: SpriteVram,
+    sprite_generation: Cell<u32>,
     affine_matrix: Option<AffineMatrixVram>,
 }
 
@@ -237,16 +267,22 @@ impl ObjectUnmanaged {
     pub fn new(sprite: SpriteVram) -> Self {
         let sprite_location = sprite.location();
         let palette_location = sprite.palette_location();
+        let format = sprite.format();
         let (shape, size) = sprite.size().shape_size();
 
         let mut sprite = Self {
-            attributes: Attributes::default(),
+            attributes: Cell::new(Attributes::default()),
             sprite,
+            sprite_generation: Cell::new(sprite_generation()),
             affine_matrix: None,
         };
 
-        sprite.attributes.set_sprite(sprite_location, shape, size);
-        sprite.attributes.set_palette(palette_location);
+        sprite
+            .attributes
+            .get_mut()
+            .set_sprite(sprite_location, shape, size);
+        sprite.attributes.get_mut().set_palette(palette_location);
+        sprite.attributes.get_mut().set_colour_mode(format);
 
         sprite
     }
@@ -255,12 +291,12 @@ impl ObjectUnmanaged {
     /// Checks whether the object is not marked as hidden. Note that it could be
     /// off screen or completely transparent and still claimed to be visible.
     pub fn is_visible(&self) -> bool {
-        self.attributes.is_visible()
+        self.attributes.get().is_visible()
     }
 
     /// Display the sprite in Normal mode.
     pub fn show(&mut self) -> &mut Self {
-        self.attributes.show();
+        self.attributes.get_mut().show();
 
         self
     }
@@ -272,7 +308,7 @@ impl ObjectUnmanaged {
             "affine matrix must be set before enabling affine matrix!"
         );
 
-        self.attributes.show_affine(affine_mode);
+        self.attributes.get_mut().show_affine(affine_mode);
 
         self
     }
@@ -280,7 +316,7 @@ impl ObjectUnmanaged {
     /// Sets the horizontal flip, note that this only has a visible affect in Normal mode.  
     /// Use [hflip](Self::hflip) to get the value
     pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
-        self.attributes.set_hflip(flip);
+        self.attributes.get_mut().set_hflip(flip);
 
         self
     }
@@ -289,13 +325,13 @@ impl ObjectUnmanaged {
     /// Use [set_hflip](Self::set_hflip) to set the value
     #[must_use]
     pub fn hflip(&self) -> bool {
-        self.attributes.hflip()
+        self.attributes.get().hflip()
     }
 
     /// Sets the vertical flip, note that this only has a visible affect in Normal mode.  
     /// Use [vflip](Self::vflip) to get the value
     pub fn set_vflip(&mut self, flip: bool) -> &mut Self {
-        self.attributes.set_vflip(flip);
+        self.attributes.get_mut().set_vflip(flip);
 
         self
     }
@@ -304,13 +340,13 @@ impl ObjectUnmanaged {
     /// Use [set_vflip](Self::set_vflip) to set the value
     #[must_use]
     pub fn vflip(&self) -> bool {
-        self.attributes.vflip()
+        self.attributes.get().vflip()
     }
 
     /// Sets the priority of the object relative to the backgrounds priority.  
     /// Use [priority](Self::priority) to get the value
     pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
-        self.attributes.set_priority(priority);
+        self.attributes.get_mut().set_priority(priority);
 
         self
     }
@@ -319,14 +355,14 @@ impl ObjectUnmanaged {
     /// Use [set_priority](Self::set_priority) to set the value
     #[must_use]
     pub fn priority(&self) -> Priority {
-        self.attributes.priority()
+        self.attributes.get().priority()
     }
 
     /// Changes the sprite mode to be hidden, can be changed to Normal or Affine  
     /// modes using [`show`][ObjectUnmanaged::show] and  
     /// [`show_affine`][ObjectUnmanaged::show_affine] respectively.
     pub fn hide(&mut self) -> &mut Self {
-        self.attributes.hide();
+        self.attributes.get_mut().hide();
 
         self
     }
@@ -335,7 +371,7 @@ impl ObjectUnmanaged {
     /// Use [x](Self::x) to get the value  
     /// Use [set_position](Self::set_position) to set both `x` and `y`
     pub fn set_x(&mut self, x: u16) -> &mut Self {
-        self.attributes.set_x(x);
+        self.attributes.get_mut().set_x(x);
 
         self
     }
@@ -344,14 +380,14 @@ impl ObjectUnmanaged {
     /// Use [set_x](Self::set_x) to set the value
     #[must_use]
     pub fn x(&self) -> u16 {
-        self.attributes.x()
+        self.attributes.get().x()
     }
 
     /// Sets the y position of the object.  
     /// Use [y](Self::y) to get the value  
     /// Use [set_position](Self::set_position) to set both `x` and `y`
     pub fn set_y(&mut self, y: u16) -> &mut Self {
-        self.attributes.set_y(y);
+        self.attributes.get_mut().set_y(y);
 
         self
     }
@@ -360,7 +396,7 @@ impl ObjectUnmanaged {
     /// Use [set_y](Self::set_y) to set the value
     #[must_use]
     pub fn y(&self) -> u16 {
-        self.attributes.y()
+        self.attributes.get().y()
     }
 
     /// Sets the position of the object.  
@@ -391,8 +427,14 @@ impl ObjectUnmanaged {
         let size = sprite.size();
         let (shape, size) = size.shape_size();
 
-        self.attributes.set_sprite(sprite.location(), shape, size);
-        self.attributes.set_palette(sprite.palette_location());
+        self.attributes
+            .get_mut()
+            .set_sprite(sprite.location(), shape, size);
+        self.attributes
+            .get_mut()
+            .set_palette(sprite.palette_location());
+        self.attributes.get_mut().set_colour_mode(sprite.format());
+        self.sprite_generation.set(sprite_generation());
 
         self
     }
@@ -408,16 +450,35 @@ impl ObjectUnmanaged {
 
     /// Sets the graphics mode of the object
     pub fn set_graphics_mode(&mut self, mode: GraphicsMode) -> &mut Self {
-        self.attributes.set_graphics_mode(mode);
+        self.attributes.get_mut().set_graphics_mode(mode);
 
         self
     }
+
+    /// Sets whether the mosaic effect applies to this object. The size of the
+    /// mosaic is set with [`Mosaic`](crate::display::Mosaic).
+    pub fn set_mosaic(&mut self, mosaic: bool) -> &mut Self {
+        self.attributes.get_mut().set_mosaic(mosaic);
+
+        self
+    }
+
+    /// Whether the mosaic effect applies to this object.
+    #[must_use]
+    pub fn mosaic(&self) -> bool {
+        self.attributes.get().mosaic()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        display::object::{Graphics, Tag},
+        display::{
+            object::{DynamicSprite, Graphics, PaletteVram, Size, Tag},
+            palette16::Palette16,
+            tiled::TileFormat,
+            Rgb15,
+        },
         include_aseprite,
     };
 
@@ -448,4 +509,73 @@ mod tests {
             slot_a.set(&obj);
         }
     }
+
+    #[test_case]
+    fn object_usage_256_colours(gba: &mut crate::Gba) {
+        static GRAPHICS: &Graphics =
+            include_aseprite!(256, "../examples/the-purple-night/gfx/boss.aseprite");
+
+        static BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+        let (mut gfx, mut loader) = gba.display.object.get_unmanaged();
+
+        let sprite = loader.get_vram_sprite(BOSS.sprite(0));
+        assert_eq!(sprite.format(), TileFormat::EightBpp);
+        assert_eq!(sprite.location() & 1, 0);
+
+        let mut obj = ObjectUnmanaged::new(sprite);
+        obj.show();
+
+        gfx.iter().next().unwrap().set(&obj);
+    }
+
+    #[test_case]
+    fn compacting_on_commit_updates_objects_in_the_same_frame(gba: &mut crate::Gba) {
+        let (mut gfx, mut loader) = gba.display.object.get_unmanaged();
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+
+        let sprite = || DynamicSprite::new(Size::S16x16).to_vram(palette.clone());
+        let _first = sprite();
+        let gap = sprite();
+        let last = sprite();
+
+        let mut obj = ObjectUnmanaged::new(last.clone());
+        obj.show();
+
+        let location = last.location();
+        drop(gap);
+
+        loader.set_compact_on_commit(true);
+        gfx.commit_sprite_vram();
+        gfx.iter().set_next(&obj);
+        loader.set_compact_on_commit(false);
+
+        assert!(last.location() < location);
+
+        let tile_index = unsafe { OBJECT_ATTRIBUTE_MEMORY.add(2).read_volatile() } & 0x3ff;
+        assert_eq!(tile_index, last.location());
+
+        // the object remembers the new location, so it isn't looked up again
+        assert_eq!(obj.sprite_generation.get(), sprite_generation());
+    }
+
+    #[test_case]
+    fn iterating_does_not_compact(gba: &mut crate::Gba) {
+        let (mut gfx, mut loader) = gba.display.object.get_unmanaged();
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::BLACK; 16])).unwrap();
+
+        let sprite = || DynamicSprite::new(Size::S16x16).to_vram(palette.clone());
+        let _first = sprite();
+        let gap = sprite();
+        let last = sprite();
+
+        let location = last.location();
+        drop(gap);
+
+        loader.set_compact_on_commit(true);
+        drop(gfx.iter());
+        loader.set_compact_on_commit(false);
+
+        assert_eq!(last.location(), location);
+    }
 }
@@ -1,25 +1,27 @@
 use core::alloc::Layout;
 
+use super::Rgb15;
+
 #[repr(C)]
 #[derive(Clone)]
 pub struct Palette16 {
-    pub(crate) colours: [u16; 16],
+    pub(crate) colours: [Rgb15; 16],
 }
 
 impl Palette16 {
     #[must_use]
-    pub const fn new(colours: [u16; 16]) -> Self {
+    pub const fn new(colours: [Rgb15; 16]) -> Self {
         Palette16 { colours }
     }
 
     // Clippy bug: claims that index is only used in recursion. I can't reproduce in
     // other examples, even just copy pasting this struct and impl into a blank project :/
-    pub fn update_colour(&mut self, index: usize, colour: u16) {
+    pub fn update_colour(&mut self, index: usize, colour: Rgb15) {
         self.colours[index] = colour;
     }
 
     #[must_use]
-    pub fn colour(&self, index: usize) -> u16 {
+    pub fn colour(&self, index: usize) -> Rgb15 {
         self.colours[index]
     }
 
@@ -31,7 +33,7 @@ impl Palette16 {
 #[macro_export]
 macro_rules! include_palette {
     ($palette:literal) => {
-        $crate::include_colours_inner!($palette)
+        $crate::include_colours_inner!($crate::display::Rgb15, $palette)
     };
 }
 
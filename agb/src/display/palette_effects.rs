@@ -0,0 +1,601 @@
+//! Palette effects such as colour cycling, fades and tints.
+//!
+//! Rather than writing colours to palette memory one at a time, you give a
+//! [`PaletteEffects`] the palettes you want to show along with the effects to
+//! apply to them. Call [`PaletteEffects::update`] once a frame to move the
+//! effects along, and [`PaletteEffects::commit`] during vblank to copy any
+//! changed colours to palette memory with DMA.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::display::{
+//!     palette_effects::{PaletteEffects, PaletteTarget},
+//!     Rgb15,
+//! };
+//!
+//! # fn foo(_gba: agb::Gba) {
+//! let vblank = agb::interrupt::VBlank::get();
+//!
+//! // start from the colours currently in palette memory
+//! let mut effects = PaletteEffects::new();
+//!
+//! // make colours 1 to 4 of the first background palette flow like water
+//! effects.add_cycle(PaletteTarget::Background, 1..5, 8);
+//! // and fade everything in from black over a second
+//! effects.fade_from_colour(PaletteTarget::Background, Rgb15::BLACK, 60);
+//! effects.fade_from_colour(PaletteTarget::Object, Rgb15::BLACK, 60);
+//!
+//! loop {
+//!     effects.update();
+//!
+//!     vblank.wait_for_vblank();
+//!     effects.commit();
+//! }
+//! # }
+//! ```
+
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use crate::{dma, fixnum::Num};
+
+use super::{palette16::Palette16, Rgb15};
+
+const PALETTE_BACKGROUND: *mut Rgb15 = 0x0500_0000 as *mut Rgb15;
+const PALETTE_OBJECT: *mut Rgb15 = 0x0500_0200 as *mut Rgb15;
+
+/// The number of colours in each of the background and object palettes.
+const COLOURS: usize = 256;
+
+/// Which palette an effect applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PaletteTarget {
+    /// The 256 colours used by the backgrounds.
+    Background,
+    /// The 256 colours used by objects.
+    Object,
+}
+
+impl PaletteTarget {
+    fn index(self) -> usize {
+        match self {
+            PaletteTarget::Background => 0,
+            PaletteTarget::Object => 1,
+        }
+    }
+
+    pub(crate) fn address(self) -> *mut Rgb15 {
+        match self {
+            PaletteTarget::Background => PALETTE_BACKGROUND,
+            PaletteTarget::Object => PALETTE_OBJECT,
+        }
+    }
+}
+
+/// A transformation applied to every colour of a palette after the other
+/// effects. The default changes nothing.
+///
+/// The steps are applied in the order greyscale, tint and then brightness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ColourTransform {
+    greyscale: bool,
+    tint: Rgb15,
+    tint_amount: u8,
+    brightness: i8,
+}
+
+impl ColourTransform {
+    /// A transform which leaves colours as they are.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            greyscale: false,
+            tint: Rgb15::BLACK,
+            tint_amount: 0,
+            brightness: 0,
+        }
+    }
+
+    /// Turns colours into shades of grey with the same brightness.
+    #[must_use]
+    pub const fn with_greyscale(self, greyscale: bool) -> Self {
+        Self { greyscale, ..self }
+    }
+
+    /// Mixes `colour` into every colour. An `amount` of 0 leaves the colours
+    /// as they are and 16 or more replaces them with `colour`.
+    #[must_use]
+    pub const fn with_tint(self, colour: Rgb15, amount: u8) -> Self {
+        Self {
+            tint: colour,
+            tint_amount: if amount > 16 { 16 } else { amount },
+            ..self
+        }
+    }
+
+    /// Brightens colours towards white for positive values and darkens them
+    /// towards black for negative values. 16 is fully white and -16 is fully
+    /// black, and values outside that range are clamped.
+    #[must_use]
+    pub const fn with_brightness(self, brightness: i8) -> Self {
+        Self {
+            brightness: if brightness > 16 {
+                16
+            } else if brightness < -16 {
+                -16
+            } else {
+                brightness
+            },
+            ..self
+        }
+    }
+
+    /// Whether this transform doesn't change any colours.
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        !self.greyscale && self.tint_amount == 0 && self.brightness == 0
+    }
+
+    /// Applies the transform to a single colour.
+    #[must_use]
+    pub fn apply(&self, colour: Rgb15) -> Rgb15 {
+        let mut colour = colour;
+
+        if self.greyscale {
+            colour = colour.greyscale();
+        }
+
+        if self.tint_amount != 0 {
+            colour = colour.lerp(self.tint, Num::new(i32::from(self.tint_amount)) / 16);
+        }
+
+        if self.brightness != 0 {
+            colour = colour.adjust_brightness(Num::new(i32::from(self.brightness)) / 16);
+        }
+
+        colour
+    }
+}
+
+/// Identifies a colour cycle added with [`PaletteEffects::add_cycle`] so that
+/// it can be removed again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ColourCycleId(u32);
+
+struct ColourCycle {
+    id: ColourCycleId,
+    target: PaletteTarget,
+    range: Range<usize>,
+    frames_per_step: u16,
+    frame: u16,
+    offset: usize,
+}
+
+enum FadeTo {
+    Colour(Rgb15),
+    Palette(Vec<Rgb15>),
+}
+
+impl FadeTo {
+    fn colour(&self, index: usize, base: Rgb15) -> Rgb15 {
+        match self {
+            FadeTo::Colour(colour) => *colour,
+            FadeTo::Palette(palette) => palette.get(index).copied().unwrap_or(base),
+        }
+    }
+}
+
+struct Fade {
+    to: FadeTo,
+    frames: u16,
+    elapsed: u16,
+    /// Fades from the target towards the palette rather than the other way
+    /// around.
+    reverse: bool,
+}
+
+impl Fade {
+    /// How far towards the target colours the palette is, from 0 to 1.
+    fn amount(&self) -> Num<i32, 8> {
+        let amount = Num::new(i32::from(self.elapsed)) / i32::from(self.frames);
+        if self.reverse {
+            Num::new(1) - amount
+        } else {
+            amount
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.frames
+    }
+}
+
+struct TargetState {
+    base: Vec<Rgb15>,
+    output: Vec<Rgb15>,
+    fade: Option<Fade>,
+    transform: ColourTransform,
+    dirty: bool,
+    needs_commit: bool,
+}
+
+impl TargetState {
+    fn new(target: PaletteTarget) -> Self {
+        let mut state = Self {
+            base: Vec::new(),
+            output: Vec::new(),
+            fade: None,
+            transform: ColourTransform::new(),
+            dirty: true,
+            needs_commit: false,
+        };
+
+        state.capture(target);
+        state
+    }
+
+    fn capture(&mut self, target: PaletteTarget) {
+        self.base.clear();
+        self.base.extend(
+            (0..COLOURS).map(|index| unsafe { target.address().add(index).read_volatile() }),
+        );
+        self.output.clone_from(&self.base);
+        self.dirty = true;
+    }
+}
+
+/// Colour cycles, fades and transforms for the background and object palettes.
+/// See the [module documentation](self) for an example.
+///
+/// This keeps its own copy of both palettes, and whenever something changes
+/// the whole of the affected palette gets written to palette memory. Any
+/// colours written to palette memory some other way will get overwritten, so
+/// set them here with [`set_colour`](Self::set_colour) or
+/// [`set_palette16`](Self::set_palette16), or load them before calling
+/// [`capture`](Self::capture).
+pub struct PaletteEffects {
+    targets: [TargetState; 2],
+    cycles: Vec<ColourCycle>,
+    next_cycle_id: u32,
+}
+
+impl PaletteEffects {
+    /// Creates palette effects starting with the colours currently in palette
+    /// memory, with no effects applied.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            targets: [
+                TargetState::new(PaletteTarget::Background),
+                TargetState::new(PaletteTarget::Object),
+            ],
+            cycles: Vec::new(),
+            next_cycle_id: 0,
+        }
+    }
+
+    /// Replaces the colours of `target` with the ones currently in palette
+    /// memory, such as after loading backgrounds or sprites. Do this before
+    /// applying any effects, otherwise the effects will be captured too.
+    pub fn capture(&mut self, target: PaletteTarget) {
+        self.targets[target.index()].capture(target);
+    }
+
+    /// The colour at `index` of `target` before any effects.
+    #[must_use]
+    pub fn colour(&self, target: PaletteTarget, index: usize) -> Rgb15 {
+        self.targets[target.index()].base[index]
+    }
+
+    /// Sets the colour at `index` of `target`. Effects are applied to this as
+    /// they are to any other colour.
+    pub fn set_colour(&mut self, target: PaletteTarget, index: usize, colour: Rgb15) {
+        let state = &mut self.targets[target.index()];
+        state.base[index] = colour;
+        state.dirty = true;
+    }
+
+    /// Sets the colours of one of the 16 colour palettes of `target`.
+    pub fn set_palette16(
+        &mut self,
+        target: PaletteTarget,
+        palette_index: usize,
+        palette: &Palette16,
+    ) {
+        assert!(palette_index < 16, "there are only 16 palettes");
+
+        let state = &mut self.targets[target.index()];
+        state.base[palette_index * 16..][..16].copy_from_slice(&palette.colours);
+        state.dirty = true;
+    }
+
+    /// Rotates the colours in `range` of `target` along by one every
+    /// `frames_per_step` frames, so that each colour takes the place of the one
+    /// after it and the last takes the place of the first. This is how effects
+    /// such as flowing water and lava are usually done.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty, reversed or goes past the end of the
+    /// palette, or if `frames_per_step` is 0.
+    pub fn add_cycle(
+        &mut self,
+        target: PaletteTarget,
+        range: Range<usize>,
+        frames_per_step: u16,
+    ) -> ColourCycleId {
+        assert!(range.start < range.end, "colour range is empty or reversed");
+        assert!(range.end <= COLOURS, "colour range is outside the palette");
+        assert!(
+            frames_per_step > 0,
+            "a cycle needs at least one frame per step"
+        );
+
+        let id = ColourCycleId(self.next_cycle_id);
+        self.next_cycle_id += 1;
+
+        self.cycles.push(ColourCycle {
+            id,
+            target,
+            range,
+            frames_per_step,
+            frame: 0,
+            offset: 0,
+        });
+
+        id
+    }
+
+    /// Stops a colour cycle, putting its colours back where they started.
+    pub fn remove_cycle(&mut self, id: ColourCycleId) {
+        let targets = &mut self.targets;
+        self.cycles.retain(|cycle| {
+            if cycle.id == id {
+                targets[cycle.target.index()].dirty = true;
+            }
+
+            cycle.id != id
+        });
+    }
+
+    /// Stops every colour cycle.
+    pub fn clear_cycles(&mut self) {
+        for cycle in self.cycles.drain(..) {
+            self.targets[cycle.target.index()].dirty = true;
+        }
+    }
+
+    fn start_fade(&mut self, target: PaletteTarget, to: FadeTo, frames: u16, reverse: bool) {
+        let state = &mut self.targets[target.index()];
+        state.fade = Some(Fade {
+            to,
+            frames: frames.max(1),
+            elapsed: 0,
+            reverse,
+        });
+        state.dirty = true;
+    }
+
+    /// Fades every colour of `target` to `colour` over `frames` frames. Once
+    /// the fade has finished the palette stays that colour until the fade is
+    /// [cleared](Self::clear_fade) or replaced by another one.
+    pub fn fade_to_colour(&mut self, target: PaletteTarget, colour: Rgb15, frames: u16) {
+        self.start_fade(target, FadeTo::Colour(colour), frames, false);
+    }
+
+    /// Starts every colour of `target` as `colour` and fades them to their
+    /// actual colours over `frames` frames.
+    pub fn fade_from_colour(&mut self, target: PaletteTarget, colour: Rgb15, frames: u16) {
+        self.start_fade(target, FadeTo::Colour(colour), frames, true);
+    }
+
+    /// Fades the colours of `target` to the ones in `palette` over `frames`
+    /// frames. Colours past the end of `palette` aren't changed.
+    pub fn fade_to_palette(&mut self, target: PaletteTarget, palette: &[Rgb15], frames: u16) {
+        self.start_fade(target, FadeTo::Palette(palette.to_vec()), frames, false);
+    }
+
+    /// Whether `target` is part way through a fade.
+    #[must_use]
+    pub fn is_fading(&self, target: PaletteTarget) -> bool {
+        self.targets[target.index()]
+            .fade
+            .as_ref()
+            .is_some_and(|fade| !fade.is_finished())
+    }
+
+    /// Removes the fade from `target`, going straight back to its actual
+    /// colours.
+    pub fn clear_fade(&mut self, target: PaletteTarget) {
+        let state = &mut self.targets[target.index()];
+        if state.fade.take().is_some() {
+            state.dirty = true;
+        }
+    }
+
+    /// Sets the transform applied to every colour of `target` after the other
+    /// effects.
+    pub fn set_transform(&mut self, target: PaletteTarget, transform: ColourTransform) {
+        let state = &mut self.targets[target.index()];
+        if state.transform != transform {
+            state.transform = transform;
+            state.dirty = true;
+        }
+    }
+
+    /// The transform applied to `target`.
+    #[must_use]
+    pub fn transform(&self, target: PaletteTarget) -> ColourTransform {
+        self.targets[target.index()].transform
+    }
+
+    /// Moves every effect along by a frame and works out the colours to show.
+    /// Call this once per frame, before [`commit`](Self::commit).
+    pub fn update(&mut self) {
+        for cycle in &mut self.cycles {
+            cycle.frame += 1;
+            if cycle.frame >= cycle.frames_per_step {
+                cycle.frame = 0;
+                cycle.offset = (cycle.offset + 1) % cycle.range.len();
+                self.targets[cycle.target.index()].dirty = true;
+            }
+        }
+
+        for state in &mut self.targets {
+            if let Some(fade) = &mut state.fade {
+                if !fade.is_finished() {
+                    fade.elapsed += 1;
+                    state.dirty = true;
+                }
+            }
+        }
+
+        for (index, state) in self.targets.iter_mut().enumerate() {
+            if !state.dirty {
+                continue;
+            }
+
+            state.output.clone_from(&state.base);
+
+            for cycle in &self.cycles {
+                if cycle.target.index() == index {
+                    state.output[cycle.range.clone()].rotate_right(cycle.offset);
+                }
+            }
+
+            if let Some(fade) = &state.fade {
+                let amount = fade.amount();
+                for (index, colour) in state.output.iter_mut().enumerate() {
+                    *colour = colour.lerp(fade.to.colour(index, *colour), amount);
+                }
+            }
+
+            if !state.transform.is_identity() {
+                for colour in &mut state.output {
+                    *colour = state.transform.apply(*colour);
+                }
+            }
+
+            state.dirty = false;
+            state.needs_commit = true;
+        }
+    }
+
+    /// Copies any palettes which have changed since the last commit to palette
+    /// memory. Call this during vblank.
+    pub fn commit(&mut self) {
+        for target in [PaletteTarget::Background, PaletteTarget::Object] {
+            let state = &mut self.targets[target.index()];
+            if !state.needs_commit {
+                continue;
+            }
+
+            dma::dma3_exclusive(|| unsafe {
+                dma::dma_copy16(
+                    state.output.as_ptr().cast(),
+                    target.address().cast(),
+                    COLOURS,
+                );
+            });
+
+            state.needs_commit = false;
+        }
+    }
+}
+
+impl Default for PaletteEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn transforms(_gba: &mut crate::Gba) {
+        let colour = Rgb15::from_components(4, 8, 12);
+
+        assert!(ColourTransform::new().is_identity());
+        assert_eq!(ColourTransform::new().apply(colour), colour);
+        assert_eq!(
+            ColourTransform::new().with_brightness(-20).apply(colour),
+            Rgb15::BLACK
+        );
+        assert_eq!(
+            ColourTransform::new()
+                .with_tint(Rgb15::WHITE, 16)
+                .apply(colour),
+            Rgb15::WHITE
+        );
+        assert_eq!(
+            ColourTransform::new().with_greyscale(true).apply(colour),
+            colour.greyscale()
+        );
+    }
+
+    fn committed_colours(effects: &mut PaletteEffects, target: PaletteTarget) -> Vec<Rgb15> {
+        effects.commit();
+        (0..COLOURS)
+            .map(|index| unsafe { target.address().add(index).read_volatile() })
+            .collect()
+    }
+
+    #[test_case]
+    fn cycles_and_fades(_gba: &mut crate::Gba) {
+        let background = PaletteTarget::Background;
+        let mut effects = PaletteEffects::new();
+        let [a, b, c, d] = [1, 2, 3, 4].map(Rgb15);
+        for (index, colour) in [a, b, c, d].into_iter().enumerate() {
+            effects.set_colour(background, index, colour);
+        }
+
+        let id = effects.add_cycle(background, 0..3, 2);
+        effects.update();
+        assert_eq!(
+            committed_colours(&mut effects, background)[..4],
+            [a, b, c, d]
+        );
+        effects.update();
+        assert_eq!(
+            committed_colours(&mut effects, background)[..4],
+            [c, a, b, d]
+        );
+
+        effects.remove_cycle(id);
+        effects.fade_to_colour(background, Rgb15::WHITE, 2);
+        effects.update();
+        assert!(effects.is_fading(background));
+        effects.update();
+        assert!(!effects.is_fading(background));
+        assert!(committed_colours(&mut effects, background)
+            .iter()
+            .all(|&colour| colour == Rgb15::WHITE));
+
+        effects.clear_fade(background);
+        effects.update();
+        assert_eq!(
+            committed_colours(&mut effects, background)[..4],
+            [a, b, c, d]
+        );
+    }
+
+    #[test_case]
+    fn cycles_only_change_the_colours_in_their_range(_gba: &mut crate::Gba) {
+        let object = PaletteTarget::Object;
+        let mut effects = PaletteEffects::new();
+        for index in 0..COLOURS {
+            effects.set_colour(object, index, Rgb15(index as u16));
+        }
+
+        effects.add_cycle(object, 254..256, 1);
+        effects.update();
+
+        let colours = committed_colours(&mut effects, object);
+        assert!((0..254).all(|index| colours[index] == Rgb15(index as u16)));
+        assert_eq!(colours[254..], [Rgb15(255), Rgb15(254)]);
+    }
+}
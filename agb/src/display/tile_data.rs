@@ -1,4 +1,6 @@
-use super::tiled::{TileSet, TileSetting};
+use agb_fixnum::{Rect, Vector2D};
+
+use super::tiled::{TileSet, TileSetting, TileTarget};
 
 #[non_exhaustive]
 pub struct TileData {
@@ -15,3 +17,140 @@ impl TileData {
         }
     }
 }
+
+/// A complete tile map of an image, generated by the `map` option of
+/// [`include_background_gfx!`](crate::include_background_gfx). Identical tiles, including
+/// flipped copies, are only stored once and each tile has its own palette.
+///
+/// The image can be any multiple of 8 pixels in size, and any part of it can be copied to a
+/// [`TileTarget`] such as a [`RegularBackgroundTiles`](super::tiled::RegularBackgroundTiles)
+/// with [`blit_to`](TileMap::blit_to).
+#[non_exhaustive]
+pub struct TileMap {
+    pub tiles: TileSet<'static>,
+    /// The setting of every tile in the map, row by row
+    pub tile_settings: &'static [TileSetting],
+    /// The width of the map in tiles
+    pub width: usize,
+    /// The height of the map in tiles
+    pub height: usize,
+}
+
+impl TileMap {
+    #[must_use]
+    pub const fn new(
+        tiles: TileSet<'static>,
+        tile_settings: &'static [TileSetting],
+        width: usize,
+        height: usize,
+    ) -> Self {
+        assert!(
+            tile_settings.len() == width * height,
+            "Tile settings don't match map size"
+        );
+
+        TileMap {
+            tiles,
+            tile_settings,
+            width,
+            height,
+        }
+    }
+
+    /// The size of the map in tiles.
+    #[must_use]
+    pub fn size(&self) -> Vector2D<i32> {
+        Vector2D::new(self.width as i32, self.height as i32)
+    }
+
+    /// The tile setting at the given tile position. Positions outside the map are blank.
+    #[must_use]
+    pub fn tile_setting(&self, pos: impl Into<Vector2D<i32>>) -> TileSetting {
+        let pos = pos.into();
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            return TileSetting::BLANK;
+        }
+
+        self.tile_settings[pos.x as usize + pos.y as usize * self.width]
+    }
+
+    /// The tile set and tile setting at the given tile position, in the form expected by
+    /// [`InfiniteScrolledMap::set_pos`](super::tiled::InfiniteScrolledMap::set_pos).
+    #[must_use]
+    pub fn tile(
+        &'static self,
+        pos: impl Into<Vector2D<i32>>,
+    ) -> (&'static TileSet<'static>, TileSetting) {
+        (&self.tiles, self.tile_setting(pos))
+    }
+
+    /// Copies the tiles in `region` of this map to `target`, with the top left corner of the
+    /// region ending up at `offset`. All positions are measured in tiles, and any part of the
+    /// region outside the map is skipped.
+    ///
+    /// ```rust,ignore
+    /// // show the bottom right quarter of the map in the top left of the screen
+    /// let size = map.size() / 2;
+    /// map.blit_to(&mut bg, Rect::new(size, size), (0, 0));
+    /// ```
+    pub fn blit_to(
+        &self,
+        target: &mut impl TileTarget,
+        region: Rect<i32>,
+        offset: impl Into<Vector2D<i32>>,
+    ) {
+        let offset = offset.into() - region.position;
+
+        let start_x = region.position.x.max(0);
+        let start_y = region.position.y.max(0);
+        let end_x = (region.position.x + region.size.x).min(self.width as i32);
+        let end_y = (region.position.y + region.size.y).min(self.height as i32);
+
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                let pos = Vector2D::new(x, y);
+                target.set_tile(pos + offset, &self.tiles, self.tile_setting(pos));
+            }
+        }
+    }
+
+    /// Copies the entire map to `target` with its top left corner at `offset`.
+    pub fn draw_to(&self, target: &mut impl TileTarget, offset: impl Into<Vector2D<i32>>) {
+        self.blit_to(target, Rect::new((0, 0).into(), self.size()), offset);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{display::tiled::TileFormat, Gba};
+
+    struct Recorder(Vec<Vector2D<i32>>);
+
+    impl TileTarget for Recorder {
+        fn set_tile(&mut self, pos: Vector2D<i32>, _: &TileSet<'_>, _: TileSetting) {
+            self.0.push(pos);
+        }
+    }
+
+    static MAP: TileMap = TileMap::new(
+        TileSet::new(&[], TileFormat::FourBpp),
+        &[TileSetting::BLANK; 6],
+        3,
+        2,
+    );
+
+    #[test_case]
+    fn blit_clips_region_to_map(_gba: &mut Gba) {
+        let mut recorder = Recorder(Vec::new());
+        MAP.blit_to(
+            &mut recorder,
+            Rect::new((-1, 1).into(), (3, 5).into()),
+            (10, 10),
+        );
+
+        assert_eq!(recorder.0, [Vector2D::new(11, 10), Vector2D::new(12, 10)]);
+    }
+}
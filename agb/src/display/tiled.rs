@@ -1,15 +1,24 @@
 mod affine_background;
+mod imported_map;
 mod infinite_scrolled_map;
+mod metatile;
 mod regular_background;
 mod vram_manager;
 
 use core::marker::PhantomData;
 
+use portable_atomic::{AtomicU8, Ordering};
+
 pub use super::affine::AffineMatrixBackground;
 pub use affine_background::{
     AffineBackgroundSize, AffineBackgroundTiles, AffineBackgroundWrapBehaviour,
 };
+pub use imported_map::{
+    LevelNeighbour, MapIntGridLayer, MapLayer, MapLevel, MapObject, MapObjectLayer,
+    NeighbourDirection, Properties, Property, PropertyValue, TileProperties,
+};
 pub use infinite_scrolled_map::{InfiniteScrolledMap, PartialUpdateStatus};
+pub use metatile::{MetatileMap, MetatileSet, MetatileSize, MetatileSource};
 pub use regular_background::{RegularBackgroundSize, RegularBackgroundTiles};
 pub use vram_manager::{DynamicTile, TileFormat, TileIndex, TileSet, VRAM_MANAGER};
 
@@ -22,21 +31,56 @@ use crate::{
 
 use super::DISPLAY_CONTROL;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct BackgroundId(pub(crate) u8);
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct AffineBackgroundId(pub(crate) u8);
 
 impl BackgroundId {
+    /// The first hardware background slot.
+    pub const BG0: Self = Self(0);
+    /// The second hardware background slot.
+    pub const BG1: Self = Self(1);
+    /// The third hardware background slot, which can also be affine.
+    pub const BG2: Self = Self(2);
+    /// The fourth hardware background slot, which can also be affine.
+    pub const BG3: Self = Self(3);
+
+    /// The horizontal scroll register of this background, for use with
+    /// [`hblank_transfer`](crate::dma::Dma::hblank_transfer). Once this has been
+    /// called, [`commit`](BackgroundIterator::commit) writes the scroll of this
+    /// slot every frame, since the DMA changes it behind the background's back.
     #[must_use]
     pub fn x_scroll_dma(self) -> DmaControllable<u16> {
+        DMA_SCROLL_SLOTS.fetch_or(1 << self.0, Ordering::Relaxed);
         unsafe { DmaControllable::new((0x0400_0010 + self.0 as usize * 4) as *mut _) }
     }
 }
 
+impl AffineBackgroundId {
+    /// The third hardware background slot.
+    pub const BG2: Self = Self(2);
+    /// The fourth hardware background slot.
+    pub const BG3: Self = Self(3);
+}
+
+impl From<AffineBackgroundId> for BackgroundId {
+    fn from(id: AffineBackgroundId) -> Self {
+        Self(id.0)
+    }
+}
+
 const TRANSPARENT_TILE_INDEX: u16 = 0xffff;
 
+/// Somewhere tiles can be drawn to, such as a [`RegularBackgroundTiles`] or an
+/// [`InfiniteScrolledMap`]. Used by [`TileMap`](super::tile_data::TileMap) to copy regions
+/// of a map.
+pub trait TileTarget {
+    /// Sets the tile at the given position, measured in tiles.
+    fn set_tile(&mut self, pos: Vector2D<i32>, tileset: &TileSet<'_>, tile_setting: TileSetting);
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(align(4))]
 pub struct TileSetting {
@@ -129,140 +173,402 @@ static SCREENBLOCK_ALLOCATOR: BlockAllocator = unsafe {
 
 impl_zst_allocator!(ScreenblockAllocator, SCREENBLOCK_ALLOCATOR);
 
-#[derive(Default)]
+#[derive(Clone, Copy)]
 struct RegularBackgroundData {
     bg_ctrl: u16,
     scroll_offset: Vector2D<u16>,
+    visible: bool,
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy)]
 struct AffineBackgroundData {
     bg_ctrl: u16,
     scroll_offset: Vector2D<Num<i32, 8>>,
     affine_transform: AffineMatrixBackground,
+    visible: bool,
+}
+
+/// Why a background couldn't be shown by
+/// [`try_show`](RegularBackgroundTiles::try_show).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShowError {
+    /// Every slot that the background could go in is already used this frame.
+    /// There are 4 slots, and affine backgrounds can only go in BG2 and BG3.
+    NoFreeSlot,
+    /// The background is pinned to a slot that is already used this frame.
+    SlotInUse,
+    /// The background is pinned to a slot that can't be used alongside the
+    /// backgrounds already shown. Once BG2 is affine, regular backgrounds can
+    /// only use BG0 and BG1, and once BG3 is affine they can't be shown at all.
+    IncompatibleSlot,
+}
+
+/// The values last written to the background registers, so that only the ones
+/// which change need writing again.
+#[derive(Default)]
+struct BackgroundRegisters {
+    bg_ctrl: [Option<u16>; 4],
+    scroll: [Option<Vector2D<u16>>; 4],
+    affine_scroll: [Option<Vector2D<Num<i32, 8>>>; 2],
+    affine_transform: [Option<AffineMatrixBackground>; 2],
+}
+
+/// The slots whose scroll registers have been given out for DMA, which always
+/// need writing in case the DMA has changed them.
+static DMA_SCROLL_SLOTS: AtomicU8 = AtomicU8::new(0);
+
+fn write_if_changed<T: Copy + PartialEq>(last: &mut Option<T>, address: usize, value: T) {
+    if *last != Some(value) {
+        unsafe { MemoryMapped::new(address) }.set(value);
+        *last = Some(value);
+    }
 }
 
 pub struct TiledBackground<'gba> {
     _phantom: PhantomData<&'gba ()>,
+    registers: BackgroundRegisters,
 }
 
 impl TiledBackground<'_> {
     pub(crate) unsafe fn new() -> Self {
         Self {
             _phantom: PhantomData,
+            registers: BackgroundRegisters::default(),
         }
     }
 
     pub fn iter(&mut self) -> BackgroundIterator<'_> {
         BackgroundIterator {
-            _phantom: PhantomData,
-            num_regular: 0,
-            regular_backgrounds: Default::default(),
-            num_affine: 0,
-            affine_backgrounds: Default::default(),
+            registers: &mut self.registers,
+            regular_backgrounds: [None; 4],
+            affine_backgrounds: [None; 2],
         }
     }
+
+    /// Makes the next [`commit`](BackgroundIterator::commit) write every
+    /// background register, rather than only the ones that have changed. Call
+    /// this if something else has written to them. Scroll registers written by
+    /// DMA through [`x_scroll_dma`](BackgroundId::x_scroll_dma) are always
+    /// written, so don't need this.
+    pub fn invalidate_registers(&mut self) {
+        self.registers = BackgroundRegisters::default();
+    }
 }
 
+/// Collects the backgrounds to show this frame. Backgrounds which aren't pinned
+/// to a slot are given the lowest free one, so call `show` in the same order
+/// every frame to keep them in the same slots.
 pub struct BackgroundIterator<'bg> {
-    _phantom: PhantomData<&'bg ()>,
-
-    num_regular: usize,
-    regular_backgrounds: [RegularBackgroundData; 4],
+    registers: &'bg mut BackgroundRegisters,
 
-    num_affine: usize,
-    affine_backgrounds: [AffineBackgroundData; 2],
+    regular_backgrounds: [Option<RegularBackgroundData>; 4],
+    affine_backgrounds: [Option<AffineBackgroundData>; 2],
 }
 
 impl BackgroundIterator<'_> {
-    fn set_next_regular(&mut self, data: RegularBackgroundData) -> BackgroundId {
-        let bg_index = self.next_regular_index();
-
-        self.regular_backgrounds[bg_index] = data;
-        BackgroundId(bg_index as u8)
+    fn video_mode(&self) -> u16 {
+        match self.affine_backgrounds {
+            [_, Some(_)] => 2,
+            [Some(_), None] => 1,
+            [None, None] => 0,
+        }
     }
 
-    fn next_regular_index(&mut self) -> usize {
-        if self.num_regular + self.num_affine * 2 >= 4 {
-            panic!(
-                "Can only have 4 backgrounds at once, affine counts as 2. regular: {}, affine: {}",
-                self.num_regular, self.num_affine
-            );
+    fn regular_allowed(slot: usize, video_mode: u16) -> bool {
+        match video_mode {
+            0 => true,
+            1 => slot < 2,
+            _ => false,
         }
-
-        let index = self.num_regular;
-        self.num_regular += 1;
-        index
     }
 
-    fn set_next_affine(&mut self, data: AffineBackgroundData) -> AffineBackgroundId {
-        let bg_index = self.next_affine_index();
+    fn is_free(&self, slot: usize) -> bool {
+        self.regular_backgrounds[slot].is_none()
+            && (slot < 2 || self.affine_backgrounds[slot - 2].is_none())
+    }
 
-        self.affine_backgrounds[bg_index - 2] = data;
-        AffineBackgroundId(bg_index as u8)
+    fn set_regular(
+        &mut self,
+        data: RegularBackgroundData,
+        pinned: Option<BackgroundId>,
+    ) -> Result<BackgroundId, ShowError> {
+        let video_mode = self.video_mode();
+
+        let slot = match pinned {
+            Some(BackgroundId(slot)) => {
+                let slot = slot as usize;
+                if !self.is_free(slot) {
+                    return Err(ShowError::SlotInUse);
+                }
+                if !Self::regular_allowed(slot, video_mode) {
+                    return Err(ShowError::IncompatibleSlot);
+                }
+
+                slot
+            }
+            None => (0..4)
+                .find(|&slot| self.is_free(slot) && Self::regular_allowed(slot, video_mode))
+                .ok_or(ShowError::NoFreeSlot)?,
+        };
+
+        self.regular_backgrounds[slot] = Some(data);
+        Ok(BackgroundId(slot as u8))
     }
 
-    fn next_affine_index(&mut self) -> usize {
-        if self.num_affine * 2 + self.num_regular >= 3 {
-            panic!(
-                "Can only have 4 backgrounds at once, affine counts as 2. regular: {}, affine: {}",
-                self.num_regular, self.num_affine
-            );
+    fn affine_allowed(&self, slot: usize) -> Result<(), ShowError> {
+        if !self.is_free(slot) {
+            return Err(ShowError::SlotInUse);
         }
 
-        let index = self.num_affine;
-        self.num_affine += 1;
+        let video_mode = self.video_mode().max(slot as u16 - 1);
+        let regular_fits = self
+            .regular_backgrounds
+            .iter()
+            .enumerate()
+            .all(|(slot, data)| data.is_none() || Self::regular_allowed(slot, video_mode));
 
-        index + 2 // first affine BG is bg2
+        if regular_fits {
+            Ok(())
+        } else {
+            Err(ShowError::IncompatibleSlot)
+        }
+    }
+
+    fn set_affine(
+        &mut self,
+        data: AffineBackgroundData,
+        pinned: Option<AffineBackgroundId>,
+    ) -> Result<AffineBackgroundId, ShowError> {
+        let slot = match pinned {
+            Some(AffineBackgroundId(slot)) => {
+                let slot = slot as usize;
+                self.affine_allowed(slot)?;
+                slot
+            }
+            None => {
+                let mut allowed = Err(ShowError::NoFreeSlot);
+                for slot in [2, 3] {
+                    allowed = self.affine_allowed(slot).map(|()| slot);
+                    if allowed.is_ok() {
+                        break;
+                    }
+                }
+
+                // a slot being used is only a problem for pinned backgrounds
+                allowed.map_err(|error| match error {
+                    ShowError::SlotInUse => ShowError::NoFreeSlot,
+                    error => error,
+                })?
+            }
+        };
+
+        self.affine_backgrounds[slot - 2] = Some(data);
+        Ok(AffineBackgroundId(slot as u8))
     }
 
     pub fn commit(self) {
-        let video_mode = self.num_affine as u16;
-        let enabled_backgrounds =
-            ((1u16 << self.num_regular) - 1) | (((1 << self.num_affine) - 1) << 2);
+        let video_mode = self.video_mode();
+        let registers = self.registers;
+        let mut enabled_backgrounds = 0u16;
+
+        let dma_scroll_slots = DMA_SCROLL_SLOTS.load(Ordering::Relaxed);
+        for (i, scroll) in registers.scroll.iter_mut().enumerate() {
+            if dma_scroll_slots & (1 << i) != 0 {
+                *scroll = None;
+            }
+        }
+
+        for (i, regular_background) in self.regular_backgrounds.iter().enumerate() {
+            let Some(regular_background) = regular_background else {
+                continue;
+            };
+
+            if regular_background.visible {
+                enabled_backgrounds |= 1 << i;
+            }
+
+            write_if_changed(
+                &mut registers.bg_ctrl[i],
+                0x0400_0008 + i * 2,
+                regular_background.bg_ctrl,
+            );
+            if registers.scroll[i] != Some(regular_background.scroll_offset) {
+                let bg_x_offset = unsafe { MemoryMapped::new(0x0400_0010 + i * 4) };
+                bg_x_offset.set(regular_background.scroll_offset.x);
+                let bg_y_offset = unsafe { MemoryMapped::new(0x0400_0012 + i * 4) };
+                bg_y_offset.set(regular_background.scroll_offset.y);
+
+                registers.scroll[i] = Some(regular_background.scroll_offset);
+            }
+        }
+
+        for (i, affine_background) in self.affine_backgrounds.iter().enumerate() {
+            let Some(affine_background) = affine_background else {
+                continue;
+            };
+
+            if affine_background.visible {
+                enabled_backgrounds |= 1 << (i + 2);
+            }
+
+            write_if_changed(
+                &mut registers.bg_ctrl[i + 2],
+                0x0400_0008 + (i + 2) * 2,
+                affine_background.bg_ctrl,
+            );
+
+            if registers.affine_scroll[i] != Some(affine_background.scroll_offset) {
+                let bg_x_offset = unsafe { MemoryMapped::new(0x0400_0028 + i * 16) };
+                bg_x_offset.set(affine_background.scroll_offset.x.to_raw());
+                let bg_y_offset = unsafe { MemoryMapped::new(0x0400_002c + i * 16) };
+                bg_y_offset.set(affine_background.scroll_offset.y.to_raw());
+
+                registers.affine_scroll[i] = Some(affine_background.scroll_offset);
+            }
+
+            write_if_changed(
+                &mut registers.affine_transform[i],
+                0x0400_0020 + i * 16,
+                affine_background.affine_transform,
+            );
+        }
 
         let mut display_control = DISPLAY_CONTROL.get();
+        let previous_display_control = display_control;
 
         display_control &= 0b1111000001111000;
         display_control |= video_mode | (enabled_backgrounds << 8);
 
-        DISPLAY_CONTROL.set(display_control);
-
-        for (i, regular_background) in self
-            .regular_backgrounds
-            .iter()
-            .take(self.num_regular)
-            .enumerate()
-        {
-            let bg_ctrl = unsafe { MemoryMapped::new(0x0400_0008 + i * 2) };
-            bg_ctrl.set(regular_background.bg_ctrl);
-
-            let bg_x_offset = unsafe { MemoryMapped::new(0x0400_0010 + i * 4) };
-            bg_x_offset.set(regular_background.scroll_offset.x);
-            let bg_y_offset = unsafe { MemoryMapped::new(0x0400_0012 + i * 4) };
-            bg_y_offset.set(regular_background.scroll_offset.y);
+        if display_control != previous_display_control {
+            DISPLAY_CONTROL.set(display_control);
         }
 
-        for (i, affine_background) in self
-            .affine_backgrounds
-            .iter()
-            .take(self.num_affine)
-            .enumerate()
-        {
-            let i = i + 2;
+        VRAM_MANAGER.gc();
+    }
+}
 
-            let bg_ctrl = unsafe { MemoryMapped::new(0x0400_0008 + i * 2) };
-            bg_ctrl.set(affine_background.bg_ctrl);
+#[cfg(test)]
+mod tests {
+    use crate::display::Priority;
+
+    use super::*;
+
+    #[test_case]
+    fn pinned_backgrounds_keep_their_slots(gba: &mut crate::Gba) {
+        let mut gfx = gba.display.video.tiled();
+
+        let mut pinned = RegularBackgroundTiles::new(
+            Priority::P0,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+        pinned.pin(BackgroundId::BG2);
+        pinned.set_visible(false);
+
+        let unpinned = RegularBackgroundTiles::new(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+        let affine = AffineBackgroundTiles::new(
+            Priority::P0,
+            AffineBackgroundSize::Background16x16,
+            AffineBackgroundWrapBehaviour::NoWrap,
+        );
+
+        let mut bg_iter = gfx.iter();
+        assert_eq!(pinned.show(&mut bg_iter), BackgroundId::BG2);
+        assert_eq!(unpinned.show(&mut bg_iter), BackgroundId::BG0);
+        assert_eq!(
+            affine.try_show(&mut bg_iter),
+            Err(ShowError::IncompatibleSlot)
+        );
+        assert_eq!(pinned.try_show(&mut bg_iter), Err(ShowError::SlotInUse));
+        bg_iter.commit();
+
+        let mut bg_iter = gfx.iter();
+        assert_eq!(affine.show(&mut bg_iter), AffineBackgroundId::BG2);
+        assert_eq!(pinned.try_show(&mut bg_iter), Err(ShowError::SlotInUse));
+
+        pinned.pin(BackgroundId::BG3);
+        assert_eq!(
+            pinned.try_show(&mut bg_iter),
+            Err(ShowError::IncompatibleSlot)
+        );
+        assert_eq!(unpinned.show(&mut bg_iter), BackgroundId::BG0);
+    }
 
-            let bg_x_offset = unsafe { MemoryMapped::new(0x0400_0028 + (i - 2) * 16) };
-            bg_x_offset.set(affine_background.scroll_offset.x.to_raw());
-            let bg_y_offset = unsafe { MemoryMapped::new(0x0400_002c + (i - 2) * 16) };
-            bg_y_offset.set(affine_background.scroll_offset.y.to_raw());
+    #[test_case]
+    fn unpinned_affine_backgrounds_run_out_of_slots(gba: &mut crate::Gba) {
+        let mut gfx = gba.display.video.tiled();
 
-            let affine_transform_offset = unsafe { MemoryMapped::new(0x0400_0020 + (i - 2) * 16) };
-            affine_transform_offset.set(affine_background.affine_transform);
-        }
+        let affine = AffineBackgroundTiles::new(
+            Priority::P0,
+            AffineBackgroundSize::Background16x16,
+            AffineBackgroundWrapBehaviour::NoWrap,
+        );
 
-        VRAM_MANAGER.gc();
+        let mut bg_iter = gfx.iter();
+        assert_eq!(affine.show(&mut bg_iter), AffineBackgroundId::BG2);
+        assert_eq!(affine.show(&mut bg_iter), AffineBackgroundId::BG3);
+        assert_eq!(affine.try_show(&mut bg_iter), Err(ShowError::NoFreeSlot));
+    }
+
+    #[test_case]
+    fn commit_only_writes_changed_registers(gba: &mut crate::Gba) {
+        let mut gfx = gba.display.video.tiled();
+        let bg_ctrl = unsafe { MemoryMapped::<u16>::new(0x0400_0008) };
+
+        let mut background = RegularBackgroundTiles::new(
+            Priority::P0,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+        background.pin(BackgroundId::BG0);
+
+        let mut bg_iter = gfx.iter();
+        background.show(&mut bg_iter);
+        bg_iter.commit();
+        let committed = bg_ctrl.get();
+
+        // nothing has changed, so the register isn't written again
+        bg_ctrl.set(committed ^ 0b11);
+        let mut bg_iter = gfx.iter();
+        background.show(&mut bg_iter);
+        bg_iter.commit();
+        assert_eq!(bg_ctrl.get(), committed ^ 0b11);
+
+        // changing the priority writes it
+        background.set_priority(Priority::P2);
+        let mut bg_iter = gfx.iter();
+        background.show(&mut bg_iter);
+        bg_iter.commit();
+        assert_eq!(bg_ctrl.get(), (committed & !0b11) | 2);
+    }
+
+    #[test_case]
+    fn invalidate_registers_writes_every_register(gba: &mut crate::Gba) {
+        let mut gfx = gba.display.video.tiled();
+        let bg_ctrl = unsafe { MemoryMapped::<u16>::new(0x0400_000a) };
+
+        let mut background = RegularBackgroundTiles::new(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+        background.pin(BackgroundId::BG1);
+
+        let mut bg_iter = gfx.iter();
+        background.show(&mut bg_iter);
+        bg_iter.commit();
+        let committed = bg_ctrl.get();
+
+        bg_ctrl.set(0);
+        gfx.invalidate_registers();
+        let mut bg_iter = gfx.iter();
+        background.show(&mut bg_iter);
+        bg_iter.commit();
+        assert_eq!(bg_ctrl.get(), committed);
     }
 }
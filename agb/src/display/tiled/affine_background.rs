@@ -8,8 +8,8 @@ use crate::{
 };
 
 use super::{
-    AffineBackgroundData, AffineBackgroundId, BackgroundIterator, ScreenblockAllocator, TileIndex,
-    TileSet, SCREENBLOCK_SIZE, TRANSPARENT_TILE_INDEX, VRAM_MANAGER, VRAM_START,
+    AffineBackgroundData, AffineBackgroundId, BackgroundIterator, ScreenblockAllocator, ShowError,
+    TileIndex, TileSet, SCREENBLOCK_SIZE, TRANSPARENT_TILE_INDEX, VRAM_MANAGER, VRAM_START,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -66,6 +66,9 @@ pub struct AffineBackgroundTiles {
     is_dirty: bool,
 
     scroll: Vector2D<Num<i32, 8>>,
+    mosaic: bool,
+    visible: bool,
+    pinned: Option<AffineBackgroundId>,
     screenblock_ptr: NonNull<u8>,
 
     transform: AffineMatrixBackground,
@@ -92,6 +95,9 @@ impl AffineBackgroundTiles {
             is_dirty: true,
 
             scroll: Vector2D::default(),
+            mosaic: false,
+            visible: true,
+            pinned: None,
 
             screenblock_ptr,
 
@@ -109,6 +115,60 @@ impl AffineBackgroundTiles {
         self.scroll
     }
 
+    /// Sets whether the mosaic effect applies to this background. The size of
+    /// the mosaic is set with [`Mosaic`](crate::display::Mosaic).
+    pub fn set_mosaic(&mut self, mosaic: bool) {
+        self.mosaic = mosaic;
+    }
+
+    /// Whether the mosaic effect applies to this background.
+    #[must_use]
+    pub fn mosaic(&self) -> bool {
+        self.mosaic
+    }
+
+    /// Sets the priority of this background relative to the other backgrounds
+    /// and objects. Use [priority](Self::priority) to get the value.
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    /// The priority of this background.
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Sets whether this background is drawn. A hidden background still takes
+    /// up its slot when shown, so the other backgrounds don't move.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Whether this background is drawn.
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Pins this background to a hardware slot, so that it always gets the same
+    /// id when shown. This keeps settings such as [`Blend`](crate::display::blend::Blend)
+    /// and [`Windows`](crate::display::window::Windows) applying to it.
+    pub fn pin(&mut self, id: AffineBackgroundId) {
+        self.pinned = Some(id);
+    }
+
+    /// Lets this background go in any free slot when shown.
+    pub fn unpin(&mut self) {
+        self.pinned = None;
+    }
+
+    /// The slot this background is pinned to, if any.
+    #[must_use]
+    pub fn pinned(&self) -> Option<AffineBackgroundId> {
+        self.pinned
+    }
+
     pub fn set_tile(
         &mut self,
         pos: impl Into<Vector2D<i32>>,
@@ -179,16 +239,36 @@ impl AffineBackgroundTiles {
         self.is_dirty = true;
     }
 
+    /// Shows this background for the frame being built by `bg_iter`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no slot for it. See [`try_show`](Self::try_show).
     pub fn show(&self, bg_iter: &mut BackgroundIterator<'_>) -> AffineBackgroundId {
-        bg_iter.set_next_affine(AffineBackgroundData {
-            bg_ctrl: self.bg_ctrl(),
-            scroll_offset: self.scroll,
-            affine_transform: self.transform,
-        })
+        self.try_show(bg_iter)
+            .expect("couldn't find a slot for the affine background")
+    }
+
+    /// Shows this background for the frame being built by `bg_iter`, in the
+    /// slot it is [pinned](Self::pin) to or the lowest free one otherwise.
+    pub fn try_show(
+        &self,
+        bg_iter: &mut BackgroundIterator<'_>,
+    ) -> Result<AffineBackgroundId, ShowError> {
+        bg_iter.set_affine(
+            AffineBackgroundData {
+                bg_ctrl: self.bg_ctrl(),
+                scroll_offset: self.scroll,
+                affine_transform: self.transform,
+                visible: self.visible,
+            },
+            self.pinned,
+        )
     }
 
     fn bg_ctrl(&self) -> u16 {
         self.priority as u16
+            | (u16::from(self.mosaic) << 6)
             | (self.screen_base_block() << 8)
             | ((self.wrap_behaviour as u16) << 0xd)
             | ((self.size as u16) << 0xe)
@@ -0,0 +1,685 @@
+use agb_fixnum::{Num, Vector2D};
+
+use super::{TileSet, TileSetting, TRANSPARENT_TILE_INDEX};
+
+/// A set of custom properties as defined in a map editor.
+#[derive(Clone, Copy)]
+pub struct Properties {
+    properties: &'static [Property],
+}
+
+impl Properties {
+    /// A set of properties containing no properties.
+    pub const EMPTY: Self = Self::new(&[]);
+
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(properties: &'static [Property]) -> Self {
+        Self { properties }
+    }
+
+    /// Gets the property with the given name if it exists.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&'static PropertyValue> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| &property.value)
+    }
+
+    /// Returns `true` if the property with the given name exists and is `true`. Useful for
+    /// flags such as whether a tile is solid.
+    #[must_use]
+    pub fn flag(&self, name: &str) -> bool {
+        self.get(name).and_then(PropertyValue::as_bool) == Some(true)
+    }
+
+    /// Iterates over every property.
+    pub fn iter(&self) -> impl Iterator<Item = &'static Property> {
+        self.properties.iter()
+    }
+}
+
+/// A single named custom property.
+pub struct Property {
+    name: &'static str,
+    value: PropertyValue,
+}
+
+impl Property {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(name: &'static str, value: PropertyValue) -> Self {
+        Self { name, value }
+    }
+
+    /// The name of the property.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The value of the property.
+    #[must_use]
+    pub fn value(&self) -> &PropertyValue {
+        &self.value
+    }
+}
+
+/// The value of a custom property.
+pub enum PropertyValue {
+    /// A `bool` property
+    Bool(bool),
+    /// An `int` property
+    Int(i32),
+    /// A `float` property, as a fixed point number
+    Float(Num<i32, 8>),
+    /// A `string` or `file` property
+    String(&'static str),
+    /// A colour in the Game Boy Advance's 15 bit format
+    Colour(u16),
+    /// The id of an object in the map
+    Object(u32),
+    /// A property with members of its own
+    Class(Properties),
+}
+
+impl PropertyValue {
+    /// Returns the value if this is a boolean property.
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an integer property.
+    #[must_use]
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            PropertyValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a float or an integer property.
+    #[must_use]
+    pub fn as_num(&self) -> Option<Num<i32, 8>> {
+        match self {
+            PropertyValue::Float(value) => Some(*value),
+            PropertyValue::Int(value) => Some((*value).into()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a string property.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            PropertyValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The class and custom properties given to a tile in a tileset.
+pub struct TileProperties {
+    tile_id: u16,
+    class: &'static str,
+    properties: Properties,
+}
+
+impl TileProperties {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(tile_id: u16, class: &'static str, properties: Properties) -> Self {
+        Self {
+            tile_id,
+            class,
+            properties,
+        }
+    }
+}
+
+/// A layer of tiles imported from a map editor, for example by
+/// [`include_tiled_map!`](crate::include_tiled_map).
+///
+/// The tiles can be copied to a [`RegularBackgroundTiles`](super::RegularBackgroundTiles) with
+/// `set_tile`, or used to provide the tiles to an
+/// [`InfiniteScrolledMap`](super::InfiniteScrolledMap) with [`tile`](MapLayer::tile).
+pub struct MapLayer {
+    name: &'static str,
+    tileset: &'static TileSet<'static>,
+    width: usize,
+    height: usize,
+    tiles: &'static [TileSetting],
+    tile_properties: &'static [TileProperties],
+    properties: Properties,
+}
+
+impl MapLayer {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        tileset: &'static TileSet<'static>,
+        width: usize,
+        height: usize,
+        tiles: &'static [TileSetting],
+        tile_properties: &'static [TileProperties],
+        properties: Properties,
+    ) -> Self {
+        assert!(
+            tiles.len() == width * height,
+            "Tile data doesn't match map size"
+        );
+
+        Self {
+            name,
+            tileset,
+            width,
+            height,
+            tiles,
+            tile_properties,
+            properties,
+        }
+    }
+
+    /// The name of the layer in the map editor.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The width of the layer in tiles.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the layer in tiles.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The custom properties of this layer.
+    #[must_use]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// The tile set containing all the tiles used by this layer.
+    #[must_use]
+    pub fn tileset(&self) -> &'static TileSet<'static> {
+        self.tileset
+    }
+
+    /// The tile setting at the given tile position. Positions outside the layer are blank.
+    #[must_use]
+    pub fn tile_setting(&self, pos: impl Into<Vector2D<i32>>) -> TileSetting {
+        self.index(pos.into())
+            .map_or(TileSetting::BLANK, |index| self.tiles[index])
+    }
+
+    /// The tile set and tile setting at the given tile position, in the form expected by
+    /// [`InfiniteScrolledMap::set_pos`](super::InfiniteScrolledMap::set_pos).
+    #[must_use]
+    pub fn tile(&self, pos: impl Into<Vector2D<i32>>) -> (&'static TileSet<'static>, TileSetting) {
+        (self.tileset, self.tile_setting(pos))
+    }
+
+    /// The class of the tile at the given tile position, or the empty string if it doesn't
+    /// have one.
+    #[must_use]
+    pub fn tile_class(&self, pos: impl Into<Vector2D<i32>>) -> &'static str {
+        self.tile_info(pos.into()).map_or("", |info| info.class)
+    }
+
+    /// The custom properties of the tile at the given tile position. This is where you would
+    /// normally store things like collision information.
+    #[must_use]
+    pub fn tile_properties(&self, pos: impl Into<Vector2D<i32>>) -> Properties {
+        self.tile_info(pos.into())
+            .map_or(Properties::EMPTY, |info| info.properties)
+    }
+
+    fn tile_info(&self, pos: Vector2D<i32>) -> Option<&'static TileProperties> {
+        let tile_id = self.tile_setting(pos).index();
+        if tile_id == TRANSPARENT_TILE_INDEX {
+            return None;
+        }
+
+        self.tile_properties
+            .binary_search_by_key(&tile_id, |info| info.tile_id)
+            .ok()
+            .map(|index| &self.tile_properties[index])
+    }
+
+    fn index(&self, pos: Vector2D<i32>) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            return None;
+        }
+
+        Some(pos.x as usize + pos.y as usize * self.width)
+    }
+}
+
+/// An object placed in a map editor.
+pub struct MapObject {
+    id: u32,
+    name: &'static str,
+    class: &'static str,
+    position: Vector2D<i32>,
+    size: Vector2D<i32>,
+    tile: Option<TileSetting>,
+    properties: Properties,
+}
+
+impl MapObject {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        id: u32,
+        name: &'static str,
+        class: &'static str,
+        position: Vector2D<i32>,
+        size: Vector2D<i32>,
+        tile: Option<TileSetting>,
+        properties: Properties,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            class,
+            position,
+            size,
+            tile,
+            properties,
+        }
+    }
+
+    /// The unique id of this object in the map.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The name of the object, or the empty string if it doesn't have one.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The class of the object, or the empty string if it doesn't have one.
+    #[must_use]
+    pub fn class(&self) -> &'static str {
+        self.class
+    }
+
+    /// The position of the object in pixels, exactly as given by the map editor. Note that
+    /// for tile objects in Tiled, this is the bottom left corner of the tile.
+    #[must_use]
+    pub fn position(&self) -> Vector2D<i32> {
+        self.position
+    }
+
+    /// The size of the object in pixels.
+    #[must_use]
+    pub fn size(&self) -> Vector2D<i32> {
+        self.size
+    }
+
+    /// The tile used to display this object if it is a tile object.
+    #[must_use]
+    pub fn tile(&self) -> Option<TileSetting> {
+        self.tile
+    }
+
+    /// The custom properties of this object.
+    #[must_use]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// A layer of objects imported from a map editor.
+pub struct MapObjectLayer {
+    name: &'static str,
+    objects: &'static [MapObject],
+    properties: Properties,
+}
+
+impl MapObjectLayer {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        objects: &'static [MapObject],
+        properties: Properties,
+    ) -> Self {
+        Self {
+            name,
+            objects,
+            properties,
+        }
+    }
+
+    /// The name of the layer in the map editor.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Every object in this layer.
+    #[must_use]
+    pub fn objects(&self) -> &'static [MapObject] {
+        self.objects
+    }
+
+    /// The first object with the given name.
+    #[must_use]
+    pub fn object(&self, name: &str) -> Option<&'static MapObject> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    /// Every object with the given class.
+    pub fn objects_with_class<'a>(
+        &self,
+        class: &'a str,
+    ) -> impl Iterator<Item = &'static MapObject> + 'a {
+        self.objects
+            .iter()
+            .filter(move |object| object.class == class)
+    }
+
+    /// The custom properties of this layer.
+    #[must_use]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// A layer of integer values on a grid imported from an LDtk IntGrid layer. These are
+/// commonly used for collision.
+pub struct MapIntGridLayer {
+    name: &'static str,
+    width: usize,
+    height: usize,
+    grid_size: usize,
+    values: &'static [u8],
+    value_names: &'static [(u8, &'static str)],
+}
+
+impl MapIntGridLayer {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        width: usize,
+        height: usize,
+        grid_size: usize,
+        values: &'static [u8],
+        value_names: &'static [(u8, &'static str)],
+    ) -> Self {
+        assert!(
+            values.len() == width * height,
+            "IntGrid data doesn't match layer size"
+        );
+
+        Self {
+            name,
+            width,
+            height,
+            grid_size,
+            values,
+            value_names,
+        }
+    }
+
+    /// The name of the layer in the map editor.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The width of the layer in grid cells.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the layer in grid cells.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The size of each grid cell in pixels.
+    #[must_use]
+    pub fn grid_size(&self) -> usize {
+        self.grid_size
+    }
+
+    /// The value of the given grid cell. Empty cells and positions outside the layer are 0.
+    #[must_use]
+    pub fn value(&self, pos: impl Into<Vector2D<i32>>) -> u8 {
+        let pos = pos.into();
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            return 0;
+        }
+
+        self.values[pos.x as usize + pos.y as usize * self.width]
+    }
+
+    /// The value of the grid cell containing the given pixel position in the level.
+    #[must_use]
+    pub fn value_at_pixel(&self, pos: impl Into<Vector2D<i32>>) -> u8 {
+        let pos = pos.into();
+        let grid_size = self.grid_size as i32;
+
+        self.value((pos.x.div_euclid(grid_size), pos.y.div_euclid(grid_size)))
+    }
+
+    /// The identifier given to `value` in the map editor, if it has one.
+    #[must_use]
+    pub fn value_identifier(&self, value: u8) -> Option<&'static str> {
+        self.value_names
+            .iter()
+            .find(|(id, name)| *id == value && !name.is_empty())
+            .map(|(_, name)| *name)
+    }
+}
+
+/// The direction of a [`LevelNeighbour`] relative to the level it neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NeighbourDirection {
+    /// The neighbour is above the level
+    North,
+    /// The neighbour is below the level
+    South,
+    /// The neighbour is to the right of the level
+    East,
+    /// The neighbour is to the left of the level
+    West,
+    /// The neighbour touches the top right corner of the level
+    NorthEast,
+    /// The neighbour touches the top left corner of the level
+    NorthWest,
+    /// The neighbour touches the bottom right corner of the level
+    SouthEast,
+    /// The neighbour touches the bottom left corner of the level
+    SouthWest,
+    /// The neighbour is in the same position one depth above
+    Above,
+    /// The neighbour is in the same position one depth below
+    Below,
+    /// The neighbour overlaps this level at the same depth
+    Overlap,
+}
+
+/// A level which touches another level in the world, for moving between rooms.
+pub struct LevelNeighbour {
+    direction: NeighbourDirection,
+    level: &'static MapLevel,
+}
+
+impl LevelNeighbour {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(direction: NeighbourDirection, level: &'static MapLevel) -> Self {
+        Self { direction, level }
+    }
+
+    /// Which side of the level the neighbour is on.
+    #[must_use]
+    pub fn direction(&self) -> NeighbourDirection {
+        self.direction
+    }
+
+    /// The neighbouring level.
+    #[must_use]
+    pub fn level(&self) -> &'static MapLevel {
+        self.level
+    }
+}
+
+/// A level imported from an LDtk project by [`include_ldtk!`](crate::include_ldtk).
+///
+/// The tile layers can be displayed like any other [`MapLayer`], the IntGrid layers are
+/// available as [`MapIntGridLayer`]s and the entities as [`MapObject`]s, where the name of
+/// each object is its iid and its class is the identifier of the entity.
+pub struct MapLevel {
+    identifier: &'static str,
+    iid: &'static str,
+    world: &'static str,
+    world_position: Vector2D<i32>,
+    size: Vector2D<i32>,
+    layers: &'static [MapLayer],
+    int_grids: &'static [MapIntGridLayer],
+    entity_layers: &'static [MapObjectLayer],
+    neighbours: &'static [LevelNeighbour],
+    properties: Properties,
+}
+
+impl MapLevel {
+    #[doc(hidden)]
+    #[must_use]
+    #[allow(clippy::too_many_arguments)] // only used in macro
+    pub const fn new(
+        identifier: &'static str,
+        iid: &'static str,
+        world: &'static str,
+        world_position: Vector2D<i32>,
+        size: Vector2D<i32>,
+        layers: &'static [MapLayer],
+        int_grids: &'static [MapIntGridLayer],
+        entity_layers: &'static [MapObjectLayer],
+        neighbours: &'static [LevelNeighbour],
+        properties: Properties,
+    ) -> Self {
+        Self {
+            identifier,
+            iid,
+            world,
+            world_position,
+            size,
+            layers,
+            int_grids,
+            entity_layers,
+            neighbours,
+            properties,
+        }
+    }
+
+    /// The identifier of the level in the map editor.
+    #[must_use]
+    pub fn identifier(&self) -> &'static str {
+        self.identifier
+    }
+
+    /// The unique instance id of the level.
+    #[must_use]
+    pub fn iid(&self) -> &'static str {
+        self.iid
+    }
+
+    /// The identifier of the world containing this level.
+    #[must_use]
+    pub fn world(&self) -> &'static str {
+        self.world
+    }
+
+    /// The position of the top left corner of the level in the world in pixels.
+    #[must_use]
+    pub fn world_position(&self) -> Vector2D<i32> {
+        self.world_position
+    }
+
+    /// The size of the level in pixels.
+    #[must_use]
+    pub fn size(&self) -> Vector2D<i32> {
+        self.size
+    }
+
+    /// Every tile layer in the level, including auto-layers and the tiles of IntGrid layers.
+    /// The top-most layer comes first.
+    #[must_use]
+    pub fn layers(&self) -> &'static [MapLayer] {
+        self.layers
+    }
+
+    /// The tile layer with the given name.
+    #[must_use]
+    pub fn layer(&self, name: &str) -> Option<&'static MapLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Every IntGrid layer in the level, top-most first.
+    #[must_use]
+    pub fn int_grids(&self) -> &'static [MapIntGridLayer] {
+        self.int_grids
+    }
+
+    /// The IntGrid layer with the given name.
+    #[must_use]
+    pub fn int_grid(&self, name: &str) -> Option<&'static MapIntGridLayer> {
+        self.int_grids.iter().find(|layer| layer.name == name)
+    }
+
+    /// Every entity layer in the level, top-most first.
+    #[must_use]
+    pub fn entity_layers(&self) -> &'static [MapObjectLayer] {
+        self.entity_layers
+    }
+
+    /// The entity layer with the given name.
+    #[must_use]
+    pub fn entity_layer(&self, name: &str) -> Option<&'static MapObjectLayer> {
+        self.entity_layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Every level touching this one.
+    #[must_use]
+    pub fn neighbours(&self) -> &'static [LevelNeighbour] {
+        self.neighbours
+    }
+
+    /// The levels touching this one in the given direction.
+    pub fn neighbours_in(
+        &self,
+        direction: NeighbourDirection,
+    ) -> impl Iterator<Item = &'static MapLevel> {
+        self.neighbours
+            .iter()
+            .filter(move |neighbour| neighbour.direction == direction)
+            .map(|neighbour| neighbour.level)
+    }
+
+    /// The custom fields of the level.
+    #[must_use]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
@@ -2,7 +2,10 @@ use agb_fixnum::{vec2, Rect, Vector2D};
 
 use crate::display::{HEIGHT, WIDTH};
 
-use super::{BackgroundId, BackgroundIterator, RegularBackgroundTiles, TileSet, TileSetting};
+use super::{
+    BackgroundId, BackgroundIterator, MetatileSource, RegularBackgroundTiles, ShowError, TileSet,
+    TileSetting, TileTarget,
+};
 
 /// In tiles
 const ONE_MORE_THAN_SCREEN_HEIGHT: i32 = HEIGHT / 8 + 1;
@@ -54,7 +57,7 @@ impl InfiniteScrolledMap {
     fn do_initial_case(
         &mut self,
         new_pos: Vector2D<i32>,
-        tile: impl Fn(Vector2D<i32>) -> (&'static TileSet<'static>, TileSetting),
+        mut update_rectangle: impl FnMut(&mut RegularBackgroundTiles, Rect<i32>),
     ) -> PartialUpdateStatus {
         let working = new_pos.div_floor_stable(8);
 
@@ -75,13 +78,13 @@ impl InfiniteScrolledMap {
 
         const ROWS_TO_COPY_IN_ONE_CALL: u32 = 2;
 
-        for y in current_work_done..(current_work_done + ROWS_TO_COPY_IN_ONE_CALL) {
-            for x in 0..(WIDTH / 8 + 1) {
-                let pos = working + vec2(x, y as i32);
-                let (tileset, tile_setting) = tile(pos);
-                self.map.set_tile(pos, tileset, tile_setting);
-            }
-        }
+        update_rectangle(
+            &mut self.map,
+            Rect::new(
+                working + vec2(0, current_work_done as i32),
+                vec2(WIDTH / 8, ROWS_TO_COPY_IN_ONE_CALL as i32 - 1),
+            ),
+        );
 
         if current_work_done + ROWS_TO_COPY_IN_ONE_CALL < ONE_MORE_THAN_SCREEN_HEIGHT as u32 {
             self.current_pos = Position::Working {
@@ -96,23 +99,11 @@ impl InfiniteScrolledMap {
         }
     }
 
-    fn update_rectangle(
-        &mut self,
-        rectangle: Rect<i32>,
-        tile: impl Fn(Vector2D<i32>) -> (&'static TileSet<'static>, TileSetting),
-    ) {
-        for pos in rectangle.iter() {
-            let (tileset, tile_setting) = tile(pos);
-
-            self.map.set_tile(pos, tileset, tile_setting);
-        }
-    }
-
     fn incremental_update(
         &mut self,
         old_pos: Vector2D<i32>,
         new_pos: Vector2D<i32>,
-        tile: impl Fn(Vector2D<i32>) -> (&'static TileSet<'static>, TileSetting),
+        mut update_rectangle: impl FnMut(&mut RegularBackgroundTiles, Rect<i32>),
     ) -> PartialUpdateStatus {
         let old_working = old_pos.div_floor_stable(8);
         let new_working = new_pos.div_floor_stable(8);
@@ -122,42 +113,42 @@ impl InfiniteScrolledMap {
         }
 
         if old_working.x > new_working.x {
-            self.update_rectangle(
+            update_rectangle(
+                &mut self.map,
                 Rect::new(
                     new_working,
                     vec2(old_working.x - new_working.x, ONE_MORE_THAN_SCREEN_HEIGHT),
                 ),
-                &tile,
             );
         }
 
         if old_working.x < new_working.x {
-            self.update_rectangle(
+            update_rectangle(
+                &mut self.map,
                 Rect::new(
                     old_working + vec2(ONE_MORE_THAN_SCREEN_WIDTH, 0),
                     vec2(new_working.x - old_working.x, ONE_MORE_THAN_SCREEN_HEIGHT),
                 ),
-                &tile,
             );
         }
 
         if old_working.y > new_working.y {
-            self.update_rectangle(
+            update_rectangle(
+                &mut self.map,
                 Rect::new(
                     new_working,
                     vec2(ONE_MORE_THAN_SCREEN_WIDTH, old_working.y - new_working.y),
                 ),
-                &tile,
             );
         }
 
         if old_working.y < new_working.y {
-            self.update_rectangle(
+            update_rectangle(
+                &mut self.map,
                 Rect::new(
                     old_working + vec2(0, ONE_MORE_THAN_SCREEN_HEIGHT),
                     vec2(ONE_MORE_THAN_SCREEN_WIDTH, new_working.y - old_working.y),
                 ),
-                &tile,
             );
         }
 
@@ -170,6 +161,33 @@ impl InfiniteScrolledMap {
         &mut self,
         new_pos: Vector2D<i32>,
         tile: impl Fn(Vector2D<i32>) -> (&'static TileSet<'static>, TileSetting),
+    ) -> PartialUpdateStatus {
+        self.update(new_pos, |map, rectangle| {
+            for pos in rectangle.iter() {
+                let (tileset, tile_setting) = tile(pos);
+
+                map.set_tile(pos, tileset, tile_setting);
+            }
+        })
+    }
+
+    /// Like [`set_pos`](InfiniteScrolledMap::set_pos), but with the tiles coming from the
+    /// metatiles in `source`. Each metatile which comes into view is only looked up once,
+    /// rather than once for every tile in it.
+    pub fn set_pos_metatiles(
+        &mut self,
+        new_pos: Vector2D<i32>,
+        source: &impl MetatileSource,
+    ) -> PartialUpdateStatus {
+        self.update(new_pos, |map, rectangle| {
+            set_metatiles(map, rectangle, source);
+        })
+    }
+
+    fn update(
+        &mut self,
+        new_pos: Vector2D<i32>,
+        update_rectangle: impl FnMut(&mut RegularBackgroundTiles, Rect<i32>),
     ) -> PartialUpdateStatus {
         self.map.set_scroll_pos(new_pos);
 
@@ -182,8 +200,12 @@ impl InfiniteScrolledMap {
         }
 
         match self.current_pos {
-            Position::Current(old_pos) => self.incremental_update(old_pos, new_pos, tile),
-            Position::Working { .. } | Position::None => self.do_initial_case(new_pos, tile),
+            Position::Current(old_pos) => {
+                self.incremental_update(old_pos, new_pos, update_rectangle)
+            }
+            Position::Working { .. } | Position::None => {
+                self.do_initial_case(new_pos, update_rectangle)
+            }
         }
     }
 
@@ -194,6 +216,68 @@ impl InfiniteScrolledMap {
     pub fn show(&self, bg_iter: &mut BackgroundIterator<'_>) -> BackgroundId {
         self.map.show(bg_iter)
     }
+
+    /// Shows the map if there is a slot for it. See
+    /// [`RegularBackgroundTiles::try_show`].
+    pub fn try_show(
+        &self,
+        bg_iter: &mut BackgroundIterator<'_>,
+    ) -> Result<BackgroundId, ShowError> {
+        self.map.try_show(bg_iter)
+    }
+
+    /// The background the map is drawn to, for changing settings such as its
+    /// priority or which slot it is pinned to.
+    pub fn background_mut(&mut self) -> &mut RegularBackgroundTiles {
+        &mut self.map
+    }
+}
+
+/// Tiles are set in the same coordinates as the tile positions passed to the closure in
+/// [`set_pos`](InfiniteScrolledMap::set_pos), and will be overwritten if that part of the
+/// map scrolls off screen and back on again.
+impl TileTarget for InfiniteScrolledMap {
+    fn set_tile(&mut self, pos: Vector2D<i32>, tileset: &TileSet<'_>, tile_setting: TileSetting) {
+        self.map.set_tile(pos, tileset, tile_setting);
+    }
+}
+
+/// Sets the tiles in `rectangle` (including its far edges, like [`Rect::iter`]) a metatile at
+/// a time, so that each metatile is only looked up once.
+fn set_metatiles(
+    map: &mut RegularBackgroundTiles,
+    rectangle: Rect<i32>,
+    source: &impl MetatileSource,
+) {
+    let set = source.metatile_set();
+    let tileset = set.tileset();
+    let size = set.size().tiles() as i32;
+
+    let top_left = rectangle.position;
+    let bottom_right = rectangle.position + rectangle.size;
+
+    let first_metatile = top_left.div_floor_stable(size);
+    let last_metatile = bottom_right.div_floor_stable(size);
+
+    for metatile_y in first_metatile.y..=last_metatile.y {
+        for metatile_x in first_metatile.x..=last_metatile.x {
+            let metatile_pos = vec2(metatile_x, metatile_y);
+            let metatile = source.metatile(metatile_pos);
+            let origin = metatile_pos * size;
+
+            // only the part of the metatile which is inside the rectangle
+            for y in origin.y.max(top_left.y)..(origin.y + size).min(bottom_right.y + 1) {
+                for x in origin.x.max(top_left.x)..(origin.x + size).min(bottom_right.x + 1) {
+                    let pos = vec2(x, y);
+                    let tile_setting = metatile.map_or(TileSetting::BLANK, |metatile| {
+                        set.tile_setting(metatile, pos - origin)
+                    });
+
+                    map.set_tile(pos, tileset, tile_setting);
+                }
+            }
+        }
+    }
 }
 
 // Can remove once div_floor and div_ceil are stable
@@ -224,7 +308,63 @@ impl IntDivRoundingExt<i32> for Vector2D<i32> {
 
 #[cfg(test)]
 mod tests {
+    use core::cell::Cell;
+
     use super::*;
+    use crate::display::{
+        tile_data::TileMap,
+        tiled::{MetatileSet, MetatileSize, RegularBackgroundSize, TileFormat},
+        Priority,
+    };
+
+    static TILES: TileMap = TileMap::new(
+        TileSet::new(&[], TileFormat::FourBpp),
+        &[TileSetting::BLANK; 4],
+        2,
+        2,
+    );
+
+    static METATILES: MetatileSet = MetatileSet::new(&TILES, MetatileSize::Size16x16, &[]);
+
+    struct CountingSource {
+        lookups: Cell<usize>,
+    }
+
+    impl MetatileSource for CountingSource {
+        fn metatile_set(&self) -> &'static MetatileSet {
+            &METATILES
+        }
+
+        fn metatile(&self, _pos: Vector2D<i32>) -> Option<u16> {
+            self.lookups.set(self.lookups.get() + 1);
+            Some(0)
+        }
+    }
+
+    #[test_case]
+    fn metatiles_are_looked_up_once(_: &mut crate::Gba) {
+        let mut map = InfiniteScrolledMap::new(RegularBackgroundTiles::new(
+            Priority::P0,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        ));
+
+        let source = CountingSource {
+            lookups: Cell::new(0),
+        };
+
+        // the first two rows of tiles are one row of 16 metatiles
+        map.set_pos_metatiles(vec2(0, 0), &source);
+        assert_eq!(source.lookups.get(), 16);
+
+        while map.set_pos_metatiles(vec2(0, 0), &source) == PartialUpdateStatus::Continue {}
+
+        // moving right by a tile updates the two columns of tiles on the right
+        // edge, which are in two columns of 11 metatiles
+        source.lookups.set(0);
+        map.set_pos_metatiles(vec2(8, 0), &source);
+        assert_eq!(source.lookups.get(), 22);
+    }
 
     #[test_case]
     fn div_floor_stable(_: &mut crate::Gba) {
@@ -0,0 +1,281 @@
+use agb_fixnum::Vector2D;
+
+use crate::display::tile_data::TileMap;
+
+use super::{TileSet, TileSetting};
+
+/// The size of each metatile in a [`MetatileSet`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetatileSize {
+    /// 2x2 tiles
+    Size16x16,
+    /// 4x4 tiles
+    Size32x32,
+}
+
+impl MetatileSize {
+    const fn shift(self) -> u32 {
+        match self {
+            MetatileSize::Size16x16 => 1,
+            MetatileSize::Size32x32 => 2,
+        }
+    }
+
+    /// The width and height of the metatile in tiles.
+    #[must_use]
+    pub const fn tiles(self) -> usize {
+        1 << self.shift()
+    }
+
+    /// The width and height of the metatile in pixels.
+    #[must_use]
+    pub const fn pixels(self) -> usize {
+        self.tiles() * 8
+    }
+}
+
+/// A set of metatiles, which are square blocks of tiles that get placed together. Storing
+/// a world as metatiles uses a fraction of the space of storing every tile, and each
+/// metatile can have attributes such as whether it is solid.
+///
+/// The metatiles are taken from a [`TileMap`], usually imported with the `map` option of
+/// [`include_background_gfx!`](crate::include_background_gfx), going from left to right and
+/// then top to bottom.
+///
+/// ```rust,ignore
+/// include_background_gfx!(tiles, "2ce8f4", blocks => map "gfx/blocks.png");
+///
+/// const SOLID: u16 = 1;
+///
+/// static BLOCKS: MetatileSet =
+///     MetatileSet::new(&tiles::blocks, MetatileSize::Size16x16, &[0, SOLID, SOLID, 0]);
+/// ```
+pub struct MetatileSet {
+    tiles: &'static TileMap,
+    size: MetatileSize,
+    columns: usize,
+    attributes: &'static [u16],
+}
+
+impl MetatileSet {
+    /// Creates a metatile set from the tiles in `tiles`. `attributes` gives the attributes
+    /// of each metatile, the meaning of which is up to you. Any metatile without an entry in
+    /// `attributes` has attributes of 0.
+    #[must_use]
+    pub const fn new(
+        tiles: &'static TileMap,
+        size: MetatileSize,
+        attributes: &'static [u16],
+    ) -> Self {
+        assert!(
+            tiles.width & (size.tiles() - 1) == 0 && tiles.height & (size.tiles() - 1) == 0,
+            "Tile map size must be a multiple of the metatile size"
+        );
+
+        Self {
+            tiles,
+            size,
+            columns: tiles.width / size.tiles(),
+            attributes,
+        }
+    }
+
+    /// The size of every metatile in this set.
+    #[must_use]
+    pub fn size(&self) -> MetatileSize {
+        self.size
+    }
+
+    /// The number of metatiles in this set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.columns * (self.tiles.height / self.size.tiles())
+    }
+
+    /// Returns `true` if there are no metatiles in this set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The tile set containing the tiles used by the metatiles.
+    #[must_use]
+    pub fn tileset(&self) -> &'static TileSet<'static> {
+        &self.tiles.tiles
+    }
+
+    /// The attributes of the given metatile.
+    #[must_use]
+    pub fn attributes(&self, metatile: u16) -> u16 {
+        self.attributes.get(metatile as usize).copied().unwrap_or(0)
+    }
+
+    /// The setting of the tile at `offset` tiles from the top left of the given metatile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `metatile` isn't in this set.
+    #[must_use]
+    pub fn tile_setting(&self, metatile: u16, offset: Vector2D<i32>) -> TileSetting {
+        let metatile = metatile as usize;
+        assert!(
+            metatile < self.len(),
+            "metatile {metatile} is outside of a set of {} metatiles",
+            self.len()
+        );
+
+        let size = self.size.tiles() as i32;
+        let x = (metatile % self.columns) as i32 * size + offset.x;
+        let y = (metatile / self.columns) as i32 * size + offset.y;
+
+        self.tiles.tile_setting((x, y))
+    }
+}
+
+/// Somewhere to get metatiles from, such as a [`MetatileMap`] stored in ROM or a world which
+/// gets generated as you go. An [`InfiniteScrolledMap`](super::InfiniteScrolledMap) can
+/// display these with
+/// [`set_pos_metatiles`](super::InfiniteScrolledMap::set_pos_metatiles).
+pub trait MetatileSource {
+    /// The set which the metatiles come from.
+    fn metatile_set(&self) -> &'static MetatileSet;
+
+    /// The id of the metatile at the given position, measured in metatiles, or `None` if
+    /// there isn't one there.
+    fn metatile(&self, pos: Vector2D<i32>) -> Option<u16>;
+
+    /// The tile set and tile setting at the given position, measured in tiles.
+    fn tile(&self, pos: Vector2D<i32>) -> (&'static TileSet<'static>, TileSetting) {
+        let set = self.metatile_set();
+        let shift = set.size.shift();
+        let metatile_pos = Vector2D::new(pos.x >> shift, pos.y >> shift);
+
+        let tile_setting = match self.metatile(metatile_pos) {
+            Some(metatile) => {
+                let mask = set.size.tiles() as i32 - 1;
+                set.tile_setting(metatile, Vector2D::new(pos.x & mask, pos.y & mask))
+            }
+            None => TileSetting::BLANK,
+        };
+
+        (set.tileset(), tile_setting)
+    }
+
+    /// The attributes of the metatile at the given position, measured in metatiles. This is
+    /// 0 if there isn't a metatile there.
+    fn attributes(&self, pos: Vector2D<i32>) -> u16 {
+        self.metatile(pos)
+            .map_or(0, |metatile| self.metatile_set().attributes(metatile))
+    }
+
+    /// The attributes of the metatile covering the given pixel. Useful for collision.
+    fn attributes_at_pixel(&self, pos: Vector2D<i32>) -> u16 {
+        let shift = self.metatile_set().size.shift() + 3;
+        self.attributes(Vector2D::new(pos.x >> shift, pos.y >> shift))
+    }
+}
+
+/// A rectangular map of metatiles stored in ROM.
+pub struct MetatileMap {
+    metatiles: &'static MetatileSet,
+    width: usize,
+    height: usize,
+    map: &'static [u16],
+}
+
+impl MetatileMap {
+    /// Creates a map from the id of each metatile, row by row.
+    #[must_use]
+    pub const fn new(
+        metatiles: &'static MetatileSet,
+        width: usize,
+        height: usize,
+        map: &'static [u16],
+    ) -> Self {
+        assert!(
+            map.len() == width * height,
+            "Map data doesn't match map size"
+        );
+
+        Self {
+            metatiles,
+            width,
+            height,
+            map,
+        }
+    }
+
+    /// The width of the map in metatiles.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the map in metatiles.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl MetatileSource for MetatileMap {
+    fn metatile_set(&self) -> &'static MetatileSet {
+        self.metatiles
+    }
+
+    fn metatile(&self, pos: Vector2D<i32>) -> Option<u16> {
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            return None;
+        }
+
+        Some(self.map[pos.x as usize + pos.y as usize * self.width])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{display::tiled::TileFormat, Gba};
+
+    const fn tile(id: u16) -> TileSetting {
+        TileSetting::new(id, false, false, 0)
+    }
+
+    // two 2x2 metatiles side by side
+    static TILES: TileMap = TileMap::new(
+        TileSet::new(&[], TileFormat::FourBpp),
+        &[
+            tile(0),
+            tile(1),
+            tile(4),
+            tile(5),
+            tile(2),
+            tile(3),
+            tile(6),
+            tile(7),
+        ],
+        4,
+        2,
+    );
+
+    static METATILES: MetatileSet = MetatileSet::new(&TILES, MetatileSize::Size16x16, &[0, 1]);
+
+    static MAP: MetatileMap = MetatileMap::new(&METATILES, 2, 1, &[1, 0]);
+
+    #[test_case]
+    fn looks_up_tiles_and_attributes(_gba: &mut Gba) {
+        assert_eq!(METATILES.len(), 2);
+
+        assert_eq!(MAP.tile(Vector2D::new(0, 0)).1.index(), 4);
+        assert_eq!(MAP.tile(Vector2D::new(1, 1)).1.index(), 7);
+        assert_eq!(MAP.tile(Vector2D::new(3, 0)).1.index(), 1);
+        assert_eq!(
+            MAP.tile(Vector2D::new(-1, 0)).1.index(),
+            TileSetting::BLANK.index()
+        );
+
+        assert_eq!(MAP.attributes_at_pixel(Vector2D::new(15, 15)), 1);
+        assert_eq!(MAP.attributes_at_pixel(Vector2D::new(16, 0)), 0);
+        assert_eq!(MAP.attributes_at_pixel(Vector2D::new(-1, 0)), 0);
+    }
+}
@@ -10,9 +10,9 @@ use alloc::{vec, vec::Vec};
 use crate::display::{tile_data::TileData, Priority};
 
 use super::{
-    BackgroundId, BackgroundIterator, RegularBackgroundData, ScreenblockAllocator, Tile,
-    TileFormat, TileSet, TileSetting, SCREENBLOCK_SIZE, TRANSPARENT_TILE_INDEX, VRAM_MANAGER,
-    VRAM_START,
+    BackgroundId, BackgroundIterator, RegularBackgroundData, ScreenblockAllocator, ShowError, Tile,
+    TileFormat, TileSet, TileSetting, TileTarget, SCREENBLOCK_SIZE, TRANSPARENT_TILE_INDEX,
+    VRAM_MANAGER, VRAM_START,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -81,6 +81,9 @@ pub struct RegularBackgroundTiles {
     is_dirty: bool,
 
     scroll: Vector2D<i32>,
+    mosaic: bool,
+    visible: bool,
+    pinned: Option<BackgroundId>,
 
     screenblock_ptr: NonNull<Tile>,
 }
@@ -102,6 +105,9 @@ impl RegularBackgroundTiles {
             is_dirty: true,
 
             scroll: Vector2D::default(),
+            mosaic: false,
+            visible: true,
+            pinned: None,
 
             screenblock_ptr,
         }
@@ -116,6 +122,60 @@ impl RegularBackgroundTiles {
         self.scroll
     }
 
+    /// Sets whether the mosaic effect applies to this background. The size of
+    /// the mosaic is set with [`Mosaic`](crate::display::Mosaic).
+    pub fn set_mosaic(&mut self, mosaic: bool) {
+        self.mosaic = mosaic;
+    }
+
+    /// Whether the mosaic effect applies to this background.
+    #[must_use]
+    pub fn mosaic(&self) -> bool {
+        self.mosaic
+    }
+
+    /// Sets the priority of this background relative to the other backgrounds
+    /// and objects. Use [priority](Self::priority) to get the value.
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    /// The priority of this background.
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Sets whether this background is drawn. A hidden background still takes
+    /// up its slot when shown, so the other backgrounds don't move.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Whether this background is drawn.
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Pins this background to a hardware slot, so that it always gets the same
+    /// id when shown. This keeps settings such as [`Blend`](crate::display::blend::Blend)
+    /// and [`Windows`](crate::display::window::Windows) applying to it.
+    pub fn pin(&mut self, id: BackgroundId) {
+        self.pinned = Some(id);
+    }
+
+    /// Lets this background go in any free slot when shown.
+    pub fn unpin(&mut self) {
+        self.pinned = None;
+    }
+
+    /// The slot this background is pinned to, if any.
+    #[must_use]
+    pub fn pinned(&self) -> Option<BackgroundId> {
+        self.pinned
+    }
+
     pub fn set_tile(
         &mut self,
         pos: impl Into<Vector2D<i32>>,
@@ -193,11 +253,30 @@ impl RegularBackgroundTiles {
         self.is_dirty = false;
     }
 
+    /// Shows this background for the frame being built by `bg_iter`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no slot for it. See [`try_show`](Self::try_show).
     pub fn show(&self, bg_iter: &mut BackgroundIterator<'_>) -> BackgroundId {
-        bg_iter.set_next_regular(RegularBackgroundData {
-            bg_ctrl: self.bg_ctrl_value(),
-            scroll_offset: Vector2D::new(self.scroll.x as u16, self.scroll.y as u16),
-        })
+        self.try_show(bg_iter)
+            .expect("couldn't find a slot for the background")
+    }
+
+    /// Shows this background for the frame being built by `bg_iter`, in the
+    /// slot it is [pinned](Self::pin) to or the lowest free one otherwise.
+    pub fn try_show(
+        &self,
+        bg_iter: &mut BackgroundIterator<'_>,
+    ) -> Result<BackgroundId, ShowError> {
+        bg_iter.set_regular(
+            RegularBackgroundData {
+                bg_ctrl: self.bg_ctrl_value(),
+                scroll_offset: Vector2D::new(self.scroll.x as u16, self.scroll.y as u16),
+                visible: self.visible,
+            },
+            self.pinned,
+        )
     }
 
     pub fn clear(&mut self) {
@@ -224,6 +303,7 @@ impl RegularBackgroundTiles {
         };
 
         self.priority as u16
+            | (u16::from(self.mosaic) << 6)
             | (tile_colour_flag << 7)
             | (self.screen_base_block() << 8)
             | (self.size.size_flag() << 0xe)
@@ -241,3 +321,9 @@ impl Drop for RegularBackgroundTiles {
         unsafe { ScreenblockAllocator.deallocate(self.screenblock_ptr.cast(), self.size.layout()) };
     }
 }
+
+impl TileTarget for RegularBackgroundTiles {
+    fn set_tile(&mut self, pos: Vector2D<i32>, tileset: &TileSet<'_>, tile_setting: TileSetting) {
+        RegularBackgroundTiles::set_tile(self, pos, tileset, tile_setting);
+    }
+}
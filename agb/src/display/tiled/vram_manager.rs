@@ -4,7 +4,7 @@ use alloc::{slice, vec::Vec};
 
 use crate::{
     agb_alloc::{block_allocator::BlockAllocator, bump_allocator::StartEnd},
-    display::palette16,
+    display::{palette16, Rgb15},
     dma,
     hash_map::{Entry, HashMap},
     memory_mapped::MemoryMapped1DArray,
@@ -13,7 +13,7 @@ use crate::{
 
 use super::{TileSetting, CHARBLOCK_SIZE, VRAM_START};
 
-const PALETTE_BACKGROUND: MemoryMapped1DArray<u16, 256> =
+const PALETTE_BACKGROUND: MemoryMapped1DArray<Rgb15, 256> =
     unsafe { MemoryMapped1DArray::new(0x0500_0000) };
 
 static TILE_ALLOCATOR: BlockAllocator = unsafe {
@@ -255,7 +255,7 @@ impl VRamManager {
         self.with(VRamManagerInner::gc);
     }
 
-    pub fn set_background_palette_raw(&self, palette: &[u16]) {
+    pub fn set_background_palette_raw(&self, palette: &[Rgb15]) {
         self.with(|inner| inner.set_background_palette_raw(palette));
     }
 
@@ -284,7 +284,7 @@ impl VRamManager {
         &self,
         pal_index: usize,
         colour_index: usize,
-    ) -> dma::DmaControllable<u16> {
+    ) -> dma::DmaControllable<Rgb15> {
         self.with(|inner| inner.background_palette_colour_dma(pal_index, colour_index))
     }
 
@@ -292,20 +292,20 @@ impl VRamManager {
         &mut self,
         pal_index: usize,
         colour_index: usize,
-        colour: u16,
+        colour: Rgb15,
     ) {
         self.with(|inner| inner.set_background_palette_colour(pal_index, colour_index, colour));
     }
 
     /// Gets the index of the colour for a given background palette, or None if it doesn't exist
     #[must_use]
-    pub fn find_colour_index_16(&self, palette_index: usize, colour: u16) -> Option<usize> {
+    pub fn find_colour_index_16(&self, palette_index: usize, colour: Rgb15) -> Option<usize> {
         self.with(|inner| inner.find_colour_index_16(palette_index, colour))
     }
 
     /// Gets the index of the colour in the entire background palette, or None if it doesn't exist
     #[must_use]
-    pub fn find_colour_index_256(&self, colour: u16) -> Option<usize> {
+    pub fn find_colour_index_256(&self, colour: Rgb15) -> Option<usize> {
         self.with(|inner| inner.find_colour_index_256(colour))
     }
 }
@@ -525,7 +525,7 @@ impl VRamManagerInner {
     }
 
     /// Copies raw palettes to the background palette without any checks.
-    pub fn set_background_palette_raw(&mut self, palette: &[u16]) {
+    pub fn set_background_palette_raw(&mut self, palette: &[Rgb15]) {
         unsafe {
             PALETTE_BACKGROUND
                 .as_ptr()
@@ -547,7 +547,7 @@ impl VRamManagerInner {
         &self,
         pal_index: usize,
         colour_index: usize,
-    ) -> dma::DmaControllable<u16> {
+    ) -> dma::DmaControllable<Rgb15> {
         assert!(pal_index < 16);
         assert!(colour_index < 16);
 
@@ -565,7 +565,7 @@ impl VRamManagerInner {
         &mut self,
         pal_index: usize,
         colour_index: usize,
-        colour: u16,
+        colour: Rgb15,
     ) {
         assert!(pal_index < 16);
         assert!(colour_index < 16);
@@ -582,7 +582,7 @@ impl VRamManagerInner {
 
     /// Gets the index of the colour for a given background palette, or None if it doesn't exist
     #[must_use]
-    pub fn find_colour_index_16(&self, palette_index: usize, colour: u16) -> Option<usize> {
+    pub fn find_colour_index_16(&self, palette_index: usize, colour: Rgb15) -> Option<usize> {
         assert!(palette_index < 16);
 
         (0..16).find(|i| PALETTE_BACKGROUND.get(palette_index * 16 + i) == colour)
@@ -590,7 +590,7 @@ impl VRamManagerInner {
 
     /// Gets the index of the colour in the entire background palette, or None if it doesn't exist
     #[must_use]
-    pub fn find_colour_index_256(&self, colour: u16) -> Option<usize> {
+    pub fn find_colour_index_256(&self, colour: Rgb15) -> Option<usize> {
         (0..256).find(|&i| PALETTE_BACKGROUND.get(i) == colour)
     }
 }
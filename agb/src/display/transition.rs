@@ -0,0 +1,453 @@
+//! Screen transitions for moving between scenes.
+//!
+//! Each transition runs for a set number of frames. Call `update` once a frame
+//! to move it along, which tells you when it has finished, and `apply` during
+//! vblank to show it. Transitions going [out](TransitionDirection::Out) hide the
+//! screen, and ones going [in](TransitionDirection::In) reveal it again.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::display::transition::{
+//!     Fade, FadeColour, Transition, TransitionDirection, TransitionStatus,
+//! };
+//!
+//! # fn foo(mut gba: agb::Gba) {
+//! let vblank = agb::interrupt::VBlank::get();
+//! let mut blend = gba.display.blend.get();
+//!
+//! let mut fade = Fade::new(FadeColour::Black, TransitionDirection::Out, 30);
+//! while fade.update() == TransitionStatus::Running {
+//!     vblank.wait_for_vblank();
+//!     fade.apply(&mut blend);
+//! }
+//! // the screen is now black, so the next scene can be loaded
+//! # }
+//! ```
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    dma::{self, Dma, DmaTransferHandle},
+    fixnum::{Num, Rect, Vector2D},
+};
+
+use super::{
+    blend::{Blend, BlendMode, Layer},
+    palette_effects::PaletteTarget,
+    tiled::BackgroundId,
+    window::{WinIn, Windows},
+    Mosaic, Rgb15, HEIGHT, WIDTH,
+};
+
+/// Whether a transition is still going.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionStatus {
+    /// The transition has more frames to go.
+    Running,
+    /// The transition has reached its end, and will stay there.
+    Finished,
+}
+
+/// Which way a transition goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionDirection {
+    /// Starts with the screen visible and ends with it hidden.
+    Out,
+    /// Starts with the screen hidden and ends with it visible.
+    In,
+}
+
+/// Something which changes the screen over several frames.
+pub trait Transition {
+    /// Moves the transition along by a frame. Call this once per frame.
+    fn update(&mut self) -> TransitionStatus;
+
+    /// Whether the transition has reached its end.
+    fn is_finished(&self) -> bool;
+}
+
+struct Progress {
+    frame: u16,
+    frames: u16,
+    direction: TransitionDirection,
+}
+
+impl Progress {
+    fn new(direction: TransitionDirection, frames: u16) -> Self {
+        Self {
+            frame: 0,
+            frames: frames.max(1),
+            direction,
+        }
+    }
+
+    fn update(&mut self) -> TransitionStatus {
+        if self.frame < self.frames {
+            self.frame += 1;
+        }
+
+        self.status()
+    }
+
+    fn status(&self) -> TransitionStatus {
+        if self.frame >= self.frames {
+            TransitionStatus::Finished
+        } else {
+            TransitionStatus::Running
+        }
+    }
+
+    /// How much of the screen is hidden, from 0 to 1.
+    fn coverage(&self) -> Num<i32, 8> {
+        let amount = Num::new(i32::from(self.frame)) / i32::from(self.frames);
+
+        match self.direction {
+            TransitionDirection::Out => amount,
+            TransitionDirection::In => Num::new(1) - amount,
+        }
+    }
+}
+
+/// Works out what to show for the current frame after the progress changes.
+/// Transitions which only need the progress when they're applied can leave
+/// this as it is.
+trait Refresh {
+    fn refresh(&mut self) {}
+}
+
+macro_rules! impl_transition {
+    ($transition: ty) => {
+        impl Transition for $transition {
+            fn update(&mut self) -> TransitionStatus {
+                self.progress.update();
+                Refresh::refresh(self);
+
+                self.progress.status()
+            }
+
+            fn is_finished(&self) -> bool {
+                self.progress.status() == TransitionStatus::Finished
+            }
+        }
+    };
+}
+
+/// The colour a [`Fade`] goes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FadeColour {
+    /// Darken the screen.
+    Black,
+    /// Brighten the screen.
+    White,
+}
+
+/// Fades the whole screen to or from black or white using the blend hardware.
+pub struct Fade {
+    colour: FadeColour,
+    progress: Progress,
+}
+
+impl Fade {
+    /// Creates a fade that lasts `frames` frames.
+    #[must_use]
+    pub fn new(colour: FadeColour, direction: TransitionDirection, frames: u16) -> Self {
+        Self {
+            colour,
+            progress: Progress::new(direction, frames),
+        }
+    }
+
+    /// Sets up `blend` to fade every background, object and the backdrop, and
+    /// commits it. This replaces any other blending.
+    pub fn apply(&self, blend: &mut Blend) {
+        let level = (self.progress.coverage() * 16 + Num::new(1) / 2)
+            .floor()
+            .clamp(0, 16);
+
+        blend.reset_targets();
+        let mut layer = blend.layer(Layer::Top);
+        for background in 0..4 {
+            layer.set_background_enable(BackgroundId(background), true);
+        }
+        layer.set_object_enable(true).set_backdrop_enable(true);
+
+        blend
+            .set_blend_mode(match self.colour {
+                FadeColour::Black => BlendMode::FadeToBlack,
+                FadeColour::White => BlendMode::FadeToWhite,
+            })
+            .set_fade(Num::from_raw(level as u8))
+            .commit();
+    }
+}
+
+impl Refresh for Fade {}
+
+impl_transition!(Fade);
+
+/// A circle centred on a point which shrinks to hide the screen or grows to
+/// reveal it, using a window which is changed every scanline with DMA. Outside
+/// of the circle only the backdrop colour is shown.
+pub struct IrisWipe {
+    centre: Vector2D<i32>,
+    progress: Progress,
+    lines: Vec<u16>,
+}
+
+impl IrisWipe {
+    /// Creates an iris wipe around `centre` that lasts `frames` frames.
+    #[must_use]
+    pub fn new(
+        centre: impl Into<Vector2D<i32>>,
+        direction: TransitionDirection,
+        frames: u16,
+    ) -> Self {
+        let mut wipe = Self {
+            centre: centre.into(),
+            progress: Progress::new(direction, frames),
+            lines: vec![0; HEIGHT as usize],
+        };
+
+        wipe.refresh();
+        wipe
+    }
+
+    /// Sets up window 0 to show everything inside the circle and the outside
+    /// window to show nothing, and starts `dma` changing the window every
+    /// scanline. Keep the returned handle until the next time you call this.
+    ///
+    /// Once the wipe has finished revealing the screen, disable the windows.
+    pub fn apply<'dma>(
+        &self,
+        windows: &mut Windows,
+        dma: &'dma mut Dma,
+    ) -> DmaTransferHandle<'dma, u16> {
+        let inside = windows.win_in(WinIn::Win0);
+        inside.reset();
+        for background in 0..4 {
+            inside.set_background_enable(BackgroundId(background), true);
+        }
+        inside
+            .set_object_enable(true)
+            .set_position(&Rect::new((0, 0).into(), (WIDTH, HEIGHT).into()))
+            .enable();
+
+        windows.win_out().reset().enable();
+        windows.commit();
+
+        let horizontal = windows.win_in(WinIn::Win0).horizontal_position_dma();
+        // SAFETY: the transfer handle keeps its own copy of the lines
+        unsafe { dma.hblank_transfer(&horizontal, &self.lines) }
+    }
+}
+
+impl Refresh for IrisWipe {
+    fn refresh(&mut self) {
+        let centre = self.centre;
+
+        // the distance to the furthest corner, so the circle covers everything
+        let furthest = Vector2D::new(
+            centre.x.max(WIDTH - centre.x),
+            centre.y.max(HEIGHT - centre.y),
+        );
+        let max_radius = (furthest.x * furthest.x + furthest.y * furthest.y).isqrt() + 1;
+
+        let radius = (Num::new(max_radius) * (Num::new(1) - self.progress.coverage())).floor();
+
+        for (y, line) in self.lines.iter_mut().enumerate() {
+            let dy = y as i32 - centre.y;
+            *line = if dy.abs() < radius {
+                let half_width = (radius * radius - dy * dy).isqrt();
+                let left = (centre.x - half_width).clamp(0, WIDTH) as u16;
+                let right = (centre.x + half_width).clamp(0, WIDTH) as u16;
+
+                (left << 8) | right
+            } else {
+                0
+            };
+        }
+    }
+}
+
+impl_transition!(IrisWipe);
+
+/// Pixelates the screen with the mosaic effect while fading it to or from a
+/// colour. Only backgrounds and objects which have mosaic turned on with
+/// `set_mosaic` get pixelated.
+pub struct MosaicDissolve {
+    fade: Fade,
+    progress: Progress,
+}
+
+impl MosaicDissolve {
+    /// Creates a mosaic dissolve that lasts `frames` frames.
+    #[must_use]
+    pub fn new(colour: FadeColour, direction: TransitionDirection, frames: u16) -> Self {
+        Self {
+            fade: Fade::new(colour, direction, frames),
+            progress: Progress::new(direction, frames),
+        }
+    }
+
+    /// The mosaic for the current frame.
+    #[must_use]
+    pub fn mosaic(&self) -> Mosaic {
+        let size = (self.progress.coverage() * 15 + Num::new(1) / 2).floor() as u8 + 1;
+
+        Mosaic::new()
+            .with_background_size(size, size)
+            .with_object_size(size, size)
+    }
+
+    /// Commits the mosaic size and sets up `blend` as [`Fade::apply`] does.
+    pub fn apply(&self, blend: &mut Blend) {
+        self.mosaic().commit();
+        self.fade.apply(blend);
+    }
+}
+
+impl Refresh for MosaicDissolve {
+    fn refresh(&mut self) {
+        self.fade.update();
+    }
+}
+
+impl_transition!(MosaicDissolve);
+
+/// Fades between the colours currently in a palette and some new ones, such as
+/// to change the time of day without the screen going dark.
+///
+/// This writes straight to palette memory, so don't use it at the same time as
+/// [`PaletteEffects`](super::palette_effects::PaletteEffects) on the same
+/// palette.
+pub struct PaletteCrossfade {
+    target: PaletteTarget,
+    from: Vec<Rgb15>,
+    to: Vec<Rgb15>,
+    output: Vec<Rgb15>,
+    progress: Progress,
+}
+
+impl PaletteCrossfade {
+    /// Creates a crossfade from the colours currently in `target` to the ones
+    /// in `to`, starting from the first colour, over `frames` frames.
+    #[must_use]
+    pub fn new(target: PaletteTarget, to: &[Rgb15], frames: u16) -> Self {
+        assert!(to.len() <= 256, "a palette only has 256 colours");
+
+        let from: Vec<_> = (0..to.len())
+            .map(|index| unsafe { target.address().add(index).read_volatile() })
+            .collect();
+
+        Self {
+            target,
+            output: from.clone(),
+            from,
+            to: to.to_vec(),
+            progress: Progress::new(TransitionDirection::Out, frames),
+        }
+    }
+
+    /// Copies the colours for the current frame to palette memory.
+    pub fn apply(&self) {
+        dma::dma3_exclusive(|| unsafe {
+            dma::dma_copy16(
+                self.output.as_ptr().cast(),
+                self.target.address().cast(),
+                self.output.len(),
+            );
+        });
+    }
+}
+
+impl Refresh for PaletteCrossfade {
+    fn refresh(&mut self) {
+        let amount = self.progress.coverage();
+
+        for ((output, from), to) in self.output.iter_mut().zip(&self.from).zip(&self.to) {
+            *output = from.lerp(*to, amount);
+        }
+    }
+}
+
+impl_transition!(PaletteCrossfade);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn progress_reaches_the_end(_gba: &mut crate::Gba) {
+        let mut fade = Fade::new(FadeColour::Black, TransitionDirection::In, 2);
+        assert_eq!(fade.progress.coverage(), Num::new(1));
+
+        assert_eq!(fade.update(), TransitionStatus::Running);
+        assert_eq!(fade.update(), TransitionStatus::Finished);
+        assert_eq!(fade.update(), TransitionStatus::Finished);
+        assert_eq!(fade.progress.coverage(), Num::new(0));
+    }
+
+    #[test_case]
+    fn iris_wipe_lines(_gba: &mut crate::Gba) {
+        let mut wipe = IrisWipe::new((120, 80), TransitionDirection::Out, 2);
+
+        // fully open, every line is the full width of the screen
+        assert!(wipe.lines.iter().all(|&line| line == 240));
+
+        wipe.update();
+        assert_eq!(wipe.lines[0], 0);
+        let middle = wipe.lines[80];
+        assert!(middle >> 8 > 0 && middle & 0xff < 240);
+        assert_eq!((middle >> 8) + (middle & 0xff), 240);
+
+        wipe.update();
+        assert!(wipe.lines.iter().all(|&line| line == 0));
+    }
+
+    #[test_case]
+    fn mosaic_dissolve_grows_the_mosaic_while_fading(_gba: &mut crate::Gba) {
+        let mut dissolve = MosaicDissolve::new(FadeColour::White, TransitionDirection::Out, 4);
+        assert_eq!(dissolve.mosaic(), Mosaic::new());
+
+        dissolve.update();
+        assert_eq!(dissolve.update(), TransitionStatus::Running);
+        assert_eq!(dissolve.mosaic().background_size(), (9, 9));
+        assert_eq!(dissolve.mosaic().object_size(), (9, 9));
+        assert_eq!(dissolve.fade.progress.coverage(), Num::new(1) / 2);
+
+        dissolve.update();
+        assert_eq!(dissolve.update(), TransitionStatus::Finished);
+        assert_eq!(dissolve.mosaic().background_size(), (16, 16));
+        assert!(dissolve.fade.is_finished());
+    }
+
+    #[test_case]
+    fn palette_crossfade_writes_the_mixed_colours(_gba: &mut crate::Gba) {
+        let target = PaletteTarget::Object;
+        let from = [Rgb15::BLACK, Rgb15::WHITE];
+        for (index, &colour) in from.iter().enumerate() {
+            unsafe { target.address().add(index).write_volatile(colour) };
+        }
+        let read_palette =
+            || [0, 1].map(|index| unsafe { target.address().add(index).read_volatile() });
+
+        let to = [Rgb15::WHITE, Rgb15::BLACK];
+        let mut crossfade = PaletteCrossfade::new(target, &to, 2);
+
+        crossfade.apply();
+        assert_eq!(read_palette(), from);
+
+        assert_eq!(crossfade.update(), TransitionStatus::Running);
+        crossfade.apply();
+        let half = Num::new(1) / 2;
+        assert_eq!(
+            read_palette(),
+            [from[0].lerp(to[0], half), from[1].lerp(to[1], half)]
+        );
+
+        assert_eq!(crossfade.update(), TransitionStatus::Finished);
+        crossfade.apply();
+        assert_eq!(read_palette(), to);
+    }
+}
@@ -70,6 +70,10 @@ impl Dma {
     /// drop the DmaTransferHandler return value until the next vblank interrupt to ensure that you
     /// a continuous effect.
     ///
+    /// Background registers are only written when their value changes, apart from the scroll
+    /// registers given out by [`x_scroll_dma`](crate::display::tiled::BackgroundId::x_scroll_dma)
+    /// which get their proper value back at the start of every frame.
+    ///
     /// # Safety
     ///
     /// While DmaTransferHandle is not dropped, the slice at `values` must not move in memory.
@@ -174,6 +178,13 @@ const fn dma_control_addr(dma: usize) -> usize {
     0x0400_00b8 + 0x0c * dma
 }
 
+/// Stops every DMA channel, including the ones the mixer uses to play sound.
+pub(crate) fn stop_all_dma() {
+    for dma in 0..4 {
+        unsafe { MemoryMapped::<u16>::new(dma_control_addr(dma) + 2) }.set(0);
+    }
+}
+
 const DMA3_SOURCE_ADDR: MemoryMapped<u32> = unsafe { MemoryMapped::new(dma_source_addr(3)) };
 const DMA3_DEST_ADDR: MemoryMapped<u32> = unsafe { MemoryMapped::new(dma_dest_addr(3)) };
 const DMA3_CONTROL: MemoryMapped<u32> = unsafe { MemoryMapped::new(dma_control_addr(3)) };
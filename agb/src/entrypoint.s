@@ -70,6 +70,14 @@ CommonInit:
     ldr r2, =__iwram_rom_length_halfwords
     swi 0x000B0000
 
+    @ zeroes the bss section, which still has the old values in it after a soft reset
+    ldr r0, =.Lzero
+    ldr r1, =__bss_start
+    ldr r2, =__bss_length_words
+    orr r2, r2, #(1 << 24)                     @ fill with the value at r0
+    orr r2, r2, #(1 << 26)                     @ 32 bits at a time
+    swi 0x000B0000
+
     @ enable interrupts
     ldr r0, =0x04000208
     ldr r1, =1
@@ -91,4 +99,7 @@ CommonInit:
     @ loop if we end up here
 1:
     b 1b
+
+.Lzero:
+    .word 0
 .pool
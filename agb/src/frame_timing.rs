@@ -0,0 +1,411 @@
+//! Measures how much of each frame your game uses, so you can find out what's
+//! taking too long before it causes stutter.
+//!
+//! [`FrameTiming`] chains timers 2 and 3 together to count cycles. Wrap the
+//! parts of your frame you're interested in with [`FrameTiming::time`], and
+//! wait for vblank with [`FrameTiming::wait_for_vblank`] to finish the frame.
+//! It keeps the minimum, average and maximum of each part over the last
+//! [`WINDOW`] frames, along with the number of vblanks that were missed because
+//! a frame took too long.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::frame_timing::FrameTiming;
+//!
+//! # fn foo(mut gba: agb::Gba) {
+//! let vblank = agb::interrupt::VBlank::get();
+//! let mut timers = gba.timers.timers();
+//! let mut timing = FrameTiming::new(&mut timers);
+//!
+//! loop {
+//!     timing.time("logic", || {
+//!         // update your game
+//!     });
+//!     timing.time("oam", || {
+//!         // write your objects to oam
+//!     });
+//!
+//!     timing.wait_for_vblank(&vblank);
+//!
+//!     if timing.frames() % 300 == 0 {
+//!         let _ = timing.print_summary();
+//!     }
+//! }
+//! # }
+//! ```
+
+use core::fmt::{self, Write};
+
+use alloc::vec::Vec;
+
+use crate::{
+    debug_output,
+    display::{tiled::RegularBackgroundTiles, Font},
+    fixnum::Vector2D,
+    interrupt::VBlank,
+    mgba::DebugLevel,
+    timer::{Divider, Timer, Timers},
+};
+
+/// The number of cycles between the start of one frame and the next.
+pub const CYCLES_PER_FRAME: u32 = 280_896;
+
+/// The number of frames the statistics are kept for.
+pub const WINDOW: usize = 64;
+
+/// The minimum, average and maximum number of cycles something took over the
+/// last [`WINDOW`] frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimingStats {
+    /// The fewest CPU cycles taken in any of the last [`WINDOW`] frames.
+    pub min: u32,
+    /// The mean number of CPU cycles taken over the last [`WINDOW`] frames.
+    pub average: u32,
+    /// The most CPU cycles taken in any of the last [`WINDOW`] frames.
+    pub max: u32,
+}
+
+impl TimingStats {
+    /// The average as tenths of a percent of [`CYCLES_PER_FRAME`].
+    fn average_per_mille(&self) -> u32 {
+        per_mille(self.average)
+    }
+
+    /// The maximum as tenths of a percent of [`CYCLES_PER_FRAME`].
+    fn max_per_mille(&self) -> u32 {
+        per_mille(self.max)
+    }
+}
+
+fn per_mille(cycles: u32) -> u32 {
+    (u64::from(cycles) * 1000 / u64::from(CYCLES_PER_FRAME)) as u32
+}
+
+struct RollingWindow {
+    samples: [u32; WINDOW],
+    next: usize,
+    len: usize,
+}
+
+impl RollingWindow {
+    const fn new() -> Self {
+        Self {
+            samples: [0; WINDOW],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, sample: u32) {
+        self.samples[self.next] = sample;
+        self.next = (self.next + 1) % WINDOW;
+        self.len = (self.len + 1).min(WINDOW);
+    }
+
+    fn stats(&self) -> Option<TimingStats> {
+        let samples = &self.samples[..self.len];
+
+        Some(TimingStats {
+            min: *samples.iter().min()?,
+            average: (samples.iter().map(|&x| u64::from(x)).sum::<u64>() / self.len as u64) as u32,
+            max: *samples.iter().max()?,
+        })
+    }
+}
+
+struct Section {
+    name: &'static str,
+    this_frame: u32,
+    history: RollingWindow,
+}
+
+struct CycleCounter<'a> {
+    low: &'a mut Timer,
+    high: &'a mut Timer,
+}
+
+impl<'a> CycleCounter<'a> {
+    fn new(low: &'a mut Timer, high: &'a mut Timer) -> Self {
+        high.set_overflow_amount(0)
+            .set_cascade(true)
+            .set_enabled(true);
+        low.set_overflow_amount(0)
+            .set_divider(Divider::Divider1)
+            .set_enabled(true);
+
+        Self { low, high }
+    }
+
+    fn now(&self) -> u32 {
+        loop {
+            let high = self.high.value();
+            let low = self.low.value();
+
+            // if the low timer overflowed between the reads, try again
+            if self.high.value() == high {
+                return (u32::from(high) << 16) | u32::from(low);
+            }
+        }
+    }
+}
+
+impl Drop for CycleCounter<'_> {
+    fn drop(&mut self) {
+        self.low.set_enabled(false);
+        self.high.set_enabled(false).set_cascade(false);
+    }
+}
+
+/// Times named sections of each frame. See the [module level
+/// documentation](self).
+pub struct FrameTiming<'a> {
+    counter: CycleCounter<'a>,
+    frame_start: u32,
+    frame: RollingWindow,
+    sections: Vec<Section>,
+    frames: usize,
+    missed_vblanks: usize,
+}
+
+impl<'a> FrameTiming<'a> {
+    /// Starts timing using timers 2 and 3, which can't be used for anything else
+    /// until this is dropped. The first frame starts now.
+    pub fn new(timers: &'a mut Timers<'_>) -> Self {
+        let counter = CycleCounter::new(&mut timers.timer2, &mut timers.timer3);
+        let frame_start = counter.now();
+
+        Self {
+            counter,
+            frame_start,
+            frame: RollingWindow::new(),
+            sections: Vec::new(),
+            frames: 0,
+            missed_vblanks: 0,
+        }
+    }
+
+    /// Runs `f`, adding the time it takes to the section called `name`. A
+    /// section can be timed more than once in a frame, in which case the times
+    /// are added together.
+    pub fn time<R>(&mut self, name: &'static str, f: impl FnOnce() -> R) -> R {
+        let start = self.counter.now();
+        let result = f();
+        let cycles = self.counter.now().wrapping_sub(start);
+
+        match self
+            .sections
+            .iter_mut()
+            .find(|section| section.name == name)
+        {
+            Some(section) => section.this_frame += cycles,
+            None => self.sections.push(Section {
+                name,
+                this_frame: cycles,
+                history: RollingWindow::new(),
+            }),
+        }
+
+        result
+    }
+
+    /// Finishes the current frame, waits for the next vblank and starts the next
+    /// frame. Use this instead of [`VBlank::wait_for_vblank`].
+    pub fn wait_for_vblank(&mut self, vblank: &VBlank) {
+        self.end_frame();
+        self.missed_vblanks += vblank.wait_for_vblank_and_count_missed();
+        self.frame_start = self.counter.now();
+    }
+
+    fn end_frame(&mut self) {
+        let now = self.counter.now();
+        self.frame.push(now.wrapping_sub(self.frame_start));
+
+        for section in &mut self.sections {
+            section.history.push(section.this_frame);
+            section.this_frame = 0;
+        }
+
+        self.frames += 1;
+    }
+
+    /// The number of frames which have been finished.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The number of vblanks which were missed because a frame took longer
+    /// than [`CYCLES_PER_FRAME`].
+    #[must_use]
+    pub fn missed_vblanks(&self) -> usize {
+        self.missed_vblanks
+    }
+
+    /// How long frames took from starting to waiting for vblank, or `None` if
+    /// no frames have finished yet.
+    #[must_use]
+    pub fn frame_stats(&self) -> Option<TimingStats> {
+        self.frame.stats()
+    }
+
+    /// How long the section called `name` took each frame, or `None` if it
+    /// hasn't been timed in a finished frame yet.
+    #[must_use]
+    pub fn section_stats(&self, name: &str) -> Option<TimingStats> {
+        self.sections
+            .iter()
+            .find(|section| section.name == name)?
+            .history
+            .stats()
+    }
+
+    /// The sections which have been timed in a finished frame, in the order
+    /// they were first timed.
+    pub fn sections(&self) -> impl Iterator<Item = (&'static str, TimingStats)> + '_ {
+        self.sections
+            .iter()
+            .filter_map(|section| Some((section.name, section.history.stats()?)))
+    }
+
+    /// Prints the statistics for the whole frame and each section in cycles and
+    /// as a percentage of [`CYCLES_PER_FRAME`], one line each.
+    pub fn print_summary(&self) -> Result<(), fmt::Error> {
+        debug_output::print(
+            format_args!(
+                "frame timing over {} frames, {} missed vblanks",
+                self.frames, self.missed_vblanks
+            ),
+            DebugLevel::Info,
+        )?;
+
+        for (name, stats) in self
+            .frame_stats()
+            .map(|stats| ("frame", stats))
+            .into_iter()
+            .chain(self.sections())
+        {
+            let average = stats.average_per_mille();
+
+            debug_output::print(
+                format_args!(
+                    "{name}: min {} avg {} max {} cycles ({}.{}% of frame)",
+                    stats.min,
+                    stats.average,
+                    stats.max,
+                    average / 10,
+                    average % 10
+                ),
+                DebugLevel::Info,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the average and maximum percentage of the frame used overall and
+    /// by each section to `bg` in `font`, starting at the tile `position`. The
+    /// colours are palette indices.
+    ///
+    /// Rendering text is slow, so only do this every few frames and leave it out
+    /// of any sections you're timing.
+    pub fn draw_overlay(
+        &self,
+        font: &Font,
+        bg: &mut RegularBackgroundTiles,
+        position: impl Into<Vector2D<u16>>,
+        foreground_colour: u8,
+        background_colour: u8,
+    ) {
+        let mut renderer = font.render_text(position);
+        let mut writer = renderer.writer(foreground_colour, background_colour, bg);
+
+        // writing to a text writer never fails
+        let _ = self.write_overlay(&mut writer);
+
+        writer.commit();
+    }
+
+    fn write_overlay(&self, output: &mut impl Write) -> fmt::Result {
+        if let Some(stats) = self.frame_stats() {
+            write_percentages(output, "frame", &stats)?;
+        }
+
+        for (name, stats) in self.sections() {
+            write_percentages(output, name, &stats)?;
+        }
+
+        writeln!(output, "missed {}", self.missed_vblanks)
+    }
+}
+
+fn write_percentages(output: &mut impl Write, name: &str, stats: &TimingStats) -> fmt::Result {
+    let average = stats.average_per_mille();
+    let max = stats.max_per_mille();
+
+    writeln!(
+        output,
+        "{name} {}.{}% max {}.{}%",
+        average / 10,
+        average % 10,
+        max / 10,
+        max % 10
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test_case]
+    fn rolling_window_forgets_old_frames(_gba: &mut crate::Gba) {
+        let mut window = RollingWindow::new();
+        assert_eq!(window.stats(), None);
+
+        window.push(CYCLES_PER_FRAME);
+        for _ in 0..WINDOW {
+            window.push(100);
+        }
+        window.push(400);
+
+        assert_eq!(
+            window.stats(),
+            Some(TimingStats {
+                min: 100,
+                average: (100 * (WINDOW as u32 - 1) + 400) / WINDOW as u32,
+                max: 400,
+            })
+        );
+    }
+
+    #[test_case]
+    fn sections_are_timed(gba: &mut crate::Gba) {
+        let mut timers = gba.timers.timers();
+        let mut timing = FrameTiming::new(&mut timers);
+
+        for _ in 0..2 {
+            let value = timing.time("work", || {
+                (0..1000).fold(0u32, |total, x| core::hint::black_box(total + x))
+            });
+            assert_eq!(value, 499_500);
+            timing.time("nothing", || {});
+            timing.end_frame();
+        }
+
+        assert_eq!(timing.frames(), 2);
+
+        let work = timing.section_stats("work").unwrap();
+        let nothing = timing.section_stats("nothing").unwrap();
+        let frame = timing.frame_stats().unwrap();
+        assert!(work.min > nothing.max);
+        assert!(frame.max >= work.max + nothing.max);
+        assert_eq!(timing.sections().count(), 2);
+
+        let mut overlay = String::new();
+        timing.write_overlay(&mut overlay).unwrap();
+        assert_eq!(overlay.lines().count(), 4);
+        assert!(overlay.starts_with("frame "));
+    }
+}
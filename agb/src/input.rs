@@ -2,6 +2,16 @@
 use crate::fixnum::Vector2D;
 use bitflags::bitflags;
 
+mod key_interrupt;
+mod replay;
+mod tracker;
+
+pub use key_interrupt::{
+    add_key_interrupt_handler, sleep_until_keys, KeyCondition, KeyInterruptHandler,
+};
+pub use replay::{InputRecorder, InputReplay};
+pub use tracker::{Combo, InputTracker};
+
 /// Tri-state enum. Allows for -1, 0 and +1.
 /// Useful if checking if the D-Pad is pointing left, right, or unpressed.
 ///
@@ -66,8 +76,6 @@ bitflags! {
 
 const BUTTON_INPUT: *mut u16 = (0x04000130) as *mut u16;
 
-// const BUTTON_INTERRUPT: *mut u16 = (0x04000132) as *mut u16;
-
 /// Helper to make it easy to get the current state of the GBA's buttons.
 ///
 /// # Example
@@ -121,6 +129,15 @@ impl ButtonController {
         self.current = !unsafe { BUTTON_INPUT.read_volatile() };
     }
 
+    /// Updates the state of the button controller as if `buttons` were the
+    /// buttons currently pressed, rather than reading them from the hardware.
+    /// Use this in place of [`update()`](ButtonController::update()) to drive
+    /// the controller from somewhere else, such as an [`InputReplay`].
+    pub fn update_with_buttons(&mut self, buttons: Button) {
+        self.previous = self.current;
+        self.current = buttons.bits() as u16;
+    }
+
     /// Returns [Tri::Positive] if right is pressed, [Tri::Negative] if left is pressed and [Tri::Zero] if neither or both are pressed.
     /// This is the normal behaviour you'll want if you're using orthogonal inputs.
     #[must_use]
@@ -0,0 +1,322 @@
+use critical_section::CriticalSection;
+
+use crate::{
+    display::DISPLAY_CONTROL,
+    interrupt::{add_interrupt_handler, Interrupt, InterruptHandler},
+    memory_mapped::MemoryMapped,
+    sound::dmg::{MASTER_SOUND_STATUS, MASTER_SOUND_VOLUME_ENABLE, MASTER_SOUND_VOLUME_MIXING},
+    syscall,
+};
+
+use super::{Button, BUTTON_INPUT};
+
+const KEY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0132) };
+const KEY_INTERRUPT_ENABLE: u16 = 1 << 14;
+
+/// Which of the buttons given to [`add_key_interrupt_handler`] have to be
+/// pressed for the interrupt to happen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCondition {
+    /// Any one of the buttons is pressed
+    Any,
+    /// All of the buttons are pressed at the same time
+    All,
+}
+
+fn key_control(buttons: Button, condition: KeyCondition) -> u16 {
+    assert!(
+        !buttons.is_empty(),
+        "a keypad interrupt needs at least one button"
+    );
+
+    let condition = match condition {
+        KeyCondition::Any => 0,
+        KeyCondition::All => 1 << 15,
+    };
+
+    buttons.bits() as u16 | KEY_INTERRUPT_ENABLE | condition
+}
+
+fn condition_met(buttons: Button, condition: KeyCondition) -> bool {
+    match condition {
+        KeyCondition::Any => pressed_buttons().intersects(buttons),
+        KeyCondition::All => pressed_buttons().contains(buttons),
+    }
+}
+
+fn pressed_buttons() -> Button {
+    Button::from_bits_truncate(u32::from(!unsafe { BUTTON_INPUT.read_volatile() }))
+}
+
+fn wait_for_release(buttons: Button) {
+    while pressed_buttons().intersects(buttons) {}
+}
+
+/// Keeps a keypad interrupt handler added with [`add_key_interrupt_handler`]
+/// alive. The keypad interrupt is turned off again when this is dropped.
+pub struct KeyInterruptHandler {
+    _handler: InterruptHandler,
+    _rearm_handler: InterruptHandler,
+    key_control: u16,
+}
+
+impl Drop for KeyInterruptHandler {
+    fn drop(&mut self) {
+        // leave the buttons alone if another handler has changed them since
+        if KEY_CONTROL.get() | KEY_INTERRUPT_ENABLE == self.key_control {
+            KEY_CONTROL.set(0);
+        }
+    }
+}
+
+/// Adds a handler for the keypad interrupt, which happens when any or all of
+/// `buttons` are pressed depending on `condition`, as long as the returned
+/// value is alive.
+///
+/// The hardware keeps raising the interrupt for as long as the buttons stay
+/// pressed, so it is turned off while the handler runs and turned back on at
+/// the first vblank after the buttons are released. So the handler is called
+/// once each time the buttons are pressed, apart from if they are pressed again
+/// before that vblank.
+///
+/// The hardware can only watch for one set of buttons at a time, so adding a
+/// handler replaces the buttons any other keypad interrupt handlers are
+/// waiting for.
+///
+/// # Safety
+/// The same as [`add_interrupt_handler`]. In particular, you *must not*
+/// allocate in the handler.
+///
+/// # Panics
+///
+/// If `buttons` is empty.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # fn foo() {
+/// use agb::input::{add_key_interrupt_handler, Button, KeyCondition};
+///
+/// let soft_reset = Button::A | Button::B | Button::START | Button::SELECT;
+/// // Safety: doesn't allocate
+/// let _handler = unsafe {
+///     add_key_interrupt_handler(soft_reset, KeyCondition::All, |_| {
+///         agb::println!("Soft reset pressed");
+///     })
+/// };
+/// # }
+/// ```
+#[must_use]
+pub unsafe fn add_key_interrupt_handler(
+    buttons: Button,
+    condition: KeyCondition,
+    handler: impl Fn(CriticalSection) + Send + Sync + 'static,
+) -> KeyInterruptHandler {
+    let key_control = key_control(buttons, condition);
+    let disarmed = key_control & !KEY_INTERRUPT_ENABLE;
+
+    let handler = unsafe {
+        add_interrupt_handler(Interrupt::Keypad, move |cs| {
+            KEY_CONTROL.set(disarmed);
+            handler(cs);
+        })
+    };
+    // Safety: doesn't allocate
+    let rearm_handler = unsafe {
+        add_interrupt_handler(Interrupt::VBlank, move |_| {
+            if KEY_CONTROL.get() == disarmed && !condition_met(buttons, condition) {
+                KEY_CONTROL.set(key_control);
+            }
+        })
+    };
+    KEY_CONTROL.set(key_control);
+
+    KeyInterruptHandler {
+        _handler: handler,
+        _rearm_handler: rearm_handler,
+        key_control,
+    }
+}
+
+/// The registers which [`sleep_until_keys`] changes, so they can be put back
+/// after waking up.
+struct SleepState {
+    key_control: u16,
+    display_control: u16,
+    sound_status: u16,
+    sound_volume_enable: u16,
+    sound_volume_mixing: u16,
+}
+
+impl SleepState {
+    /// Saves the registers and turns off the screen and sound.
+    fn enter() -> Self {
+        let state = Self {
+            key_control: KEY_CONTROL.get(),
+            display_control: DISPLAY_CONTROL.get(),
+            sound_status: MASTER_SOUND_STATUS.get(),
+            sound_volume_enable: MASTER_SOUND_VOLUME_ENABLE.get(),
+            sound_volume_mixing: MASTER_SOUND_VOLUME_MIXING.get(),
+        };
+
+        // the display has to be in forced blank for it to turn off while stopped
+        DISPLAY_CONTROL.set(state.display_control | (1 << 7));
+        MASTER_SOUND_STATUS.set(0);
+
+        state
+    }
+
+    fn exit(self) {
+        KEY_CONTROL.set(self.key_control);
+
+        // the other sound registers can only be written once sound is turned on
+        MASTER_SOUND_STATUS.set(self.sound_status);
+        MASTER_SOUND_VOLUME_ENABLE.set(self.sound_volume_enable);
+        MASTER_SOUND_VOLUME_MIXING.set(self.sound_volume_mixing);
+        DISPLAY_CONTROL.set(self.display_control);
+    }
+}
+
+/// Puts the GBA into its low power sleep mode until all of `buttons` are
+/// pressed at the same time.
+///
+/// This waits for the buttons to be released before going to sleep and again
+/// after waking up, so the same buttons can be used to go to sleep and to wake
+/// up. While asleep the screen and sound are turned off and nothing else runs,
+/// including interrupts and timers, so the game carries on from where it was
+/// when it wakes up.
+///
+/// Turning the sound off resets the registers of the [`dmg`](crate::sound::dmg)
+/// sound channels, so any sound playing on them has to be started again after
+/// waking up. The [`mixer`](crate::sound::mixer) carries on as before.
+///
+/// # Panics
+///
+/// If `buttons` is empty.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # fn foo() {
+/// use agb::input::{sleep_until_keys, Button, ButtonController};
+///
+/// let sleep = Button::L | Button::R | Button::SELECT;
+/// let mut input = ButtonController::new();
+///
+/// loop {
+///     input.update();
+///
+///     if sleep.iter().all(|button| input.is_pressed(button)) {
+///         sleep_until_keys(sleep);
+///     }
+/// }
+/// # }
+/// ```
+pub fn sleep_until_keys(buttons: Button) {
+    let wake_control = key_control(buttons, KeyCondition::All);
+
+    wait_for_release(buttons);
+
+    let state = SleepState::enter();
+
+    {
+        // Safety: doesn't allocate
+        let _wake_handler = unsafe { add_interrupt_handler(Interrupt::Keypad, |_| {}) };
+        KEY_CONTROL.set(wake_control);
+
+        // the serial and game pak interrupts can also wake the GBA up
+        while !pressed_buttons().contains(buttons) {
+            syscall::stop();
+        }
+    }
+
+    state.exit();
+
+    wait_for_release(buttons);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn key_control_register(_gba: &mut crate::Gba) {
+        assert_eq!(
+            key_control(Button::A | Button::START, KeyCondition::Any),
+            0b0100_0000_0000_1001
+        );
+        assert_eq!(
+            key_control(Button::L | Button::R, KeyCondition::All),
+            0b1100_0011_0000_0000
+        );
+    }
+
+    #[test_case]
+    fn dropping_the_handler_turns_off_the_interrupt(_gba: &mut crate::Gba) {
+        let handler = unsafe { add_key_interrupt_handler(Button::A, KeyCondition::Any, |_| {}) };
+        assert_eq!(KEY_CONTROL.get(), key_control(Button::A, KeyCondition::Any));
+
+        drop(handler);
+        assert_eq!(KEY_CONTROL.get(), 0);
+    }
+
+    #[test_case]
+    fn the_interrupt_is_rearmed_once_the_buttons_are_released(_gba: &mut crate::Gba) {
+        let armed = key_control(Button::A, KeyCondition::Any);
+        let handler = unsafe { add_key_interrupt_handler(Button::A, KeyCondition::Any, |_| {}) };
+
+        // as if the handler had been called
+        KEY_CONTROL.set(armed & !KEY_INTERRUPT_ENABLE);
+        crate::interrupt::VBlank::get().wait_for_vblank();
+        assert_eq!(KEY_CONTROL.get(), armed);
+
+        // a disarmed handler is still turned off when dropped
+        KEY_CONTROL.set(armed & !KEY_INTERRUPT_ENABLE);
+        drop(handler);
+        assert_eq!(KEY_CONTROL.get(), 0);
+    }
+
+    #[test_case]
+    fn sleeping_restores_the_registers(_gba: &mut crate::Gba) {
+        let original = [
+            KEY_CONTROL.get(),
+            DISPLAY_CONTROL.get(),
+            MASTER_SOUND_STATUS.get(),
+            MASTER_SOUND_VOLUME_ENABLE.get(),
+            MASTER_SOUND_VOLUME_MIXING.get(),
+        ];
+
+        let display_control = 0b0001_0001_0100_0000;
+        let volume_enable = 0b1111_1111_0011_0101;
+        let volume_mixing = 0b0000_0011_0000_0110;
+
+        KEY_CONTROL.set(key_control(Button::B, KeyCondition::Any));
+        DISPLAY_CONTROL.set(display_control);
+        MASTER_SOUND_STATUS.set(1 << 7);
+        MASTER_SOUND_VOLUME_ENABLE.set(volume_enable);
+        MASTER_SOUND_VOLUME_MIXING.set(volume_mixing);
+
+        let state = SleepState::enter();
+        assert_eq!(DISPLAY_CONTROL.get(), display_control | (1 << 7));
+        assert_eq!(MASTER_SOUND_STATUS.get() & (1 << 7), 0);
+
+        KEY_CONTROL.set(key_control(Button::L | Button::R, KeyCondition::All));
+        state.exit();
+
+        assert_eq!(KEY_CONTROL.get(), key_control(Button::B, KeyCondition::Any));
+        assert_eq!(DISPLAY_CONTROL.get(), display_control);
+        assert_eq!(MASTER_SOUND_STATUS.get() & (1 << 7), 1 << 7);
+        assert_eq!(MASTER_SOUND_VOLUME_ENABLE.get(), volume_enable);
+        assert_eq!(MASTER_SOUND_VOLUME_MIXING.get(), volume_mixing);
+
+        MASTER_SOUND_STATUS.set(original[2]);
+        MASTER_SOUND_VOLUME_ENABLE.set(original[3]);
+        MASTER_SOUND_VOLUME_MIXING.set(original[4]);
+        DISPLAY_CONTROL.set(original[1]);
+        KEY_CONTROL.set(original[0]);
+    }
+}
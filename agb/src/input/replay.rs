@@ -0,0 +1,355 @@
+use core::fmt::Write;
+
+use alloc::{string::String, vec::Vec};
+
+use crate::interrupt::vblank_count;
+
+use super::{Button, ButtonController};
+
+const BUTTON_MASK: u16 = (1 << 10) - 1;
+const MAX_RUN: u16 = 1 << 6;
+const LOG_PREFIX: &str = "input-recording: ";
+
+/// Records which buttons are pressed every frame, so they can be played back
+/// later with [`InputReplay`].
+///
+/// The recording is a run-length encoded list of little endian `u16`s. The
+/// bottom 10 bits of each are the buttons, in the same order as [`Button`],
+/// and the top 6 bits are one less than the number of frames they were pressed
+/// for. A game where the buttons don't change much takes very little space, so
+/// recordings can be kept in save memory or printed to the debug output with
+/// [`print`](Self::print).
+///
+/// Playing back a recording only gives the same result if the game doesn't
+/// depend on anything else which changes between runs, so seed any
+/// [`RandomNumberGenerator`](crate::rng::RandomNumberGenerator) with a fixed
+/// value.
+///
+/// Frames are counted in vblanks since the game started, so
+/// [`record`](Self::record) records the buttons once for every vblank since it
+/// was last called. A frame which takes too long is then recorded for as long
+/// as it was on screen. The first call records no buttons pressed for every
+/// vblank before it, so frame `n` of the recording is always the one after the
+/// `n`th vblank. This is how the mgba test runner and [`InputReplay::update`]
+/// play it back, so recordings line up with the game however long it took to
+/// start recording.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// use agb::input::{ButtonController, InputRecorder};
+///
+/// # fn main() {
+/// let mut input = ButtonController::new();
+/// let mut recorder = InputRecorder::new();
+///
+/// for _ in 0..600 {
+///     input.update();
+///     recorder.record(&input);
+///     // run the game
+/// }
+///
+/// recorder.print();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InputRecorder {
+    data: Vec<u8>,
+    buttons: u16,
+    run: u16,
+    last_vblank: Option<usize>,
+}
+
+impl InputRecorder {
+    /// Creates an empty recording.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            buttons: 0,
+            run: 0,
+            last_vblank: None,
+        }
+    }
+
+    /// Records the buttons currently pressed on `controller` for every vblank
+    /// since this was last called. The first time this is called, no buttons
+    /// are recorded for every vblank since the game started. Call this once per
+    /// frame.
+    pub fn record(&mut self, controller: &ButtonController) {
+        let buttons = Button::from_bits_truncate(u32::from(controller.current));
+        self.record_at(vblank_count(), buttons);
+    }
+
+    fn record_at(&mut self, vblank: usize, buttons: Button) {
+        let frames = match self.last_vblank {
+            Some(last_vblank) => vblank.wrapping_sub(last_vblank).max(1),
+            None => {
+                for _ in 0..vblank {
+                    self.record_buttons(Button::empty());
+                }
+
+                1
+            }
+        };
+        self.last_vblank = Some(vblank);
+
+        for _ in 0..frames {
+            self.record_buttons(buttons);
+        }
+    }
+
+    /// Records `buttons` as being pressed for the next frame, without counting
+    /// vblanks.
+    pub fn record_buttons(&mut self, buttons: Button) {
+        let buttons = buttons.bits() as u16 & BUTTON_MASK;
+
+        if self.run > 0 && (buttons != self.buttons || self.run == MAX_RUN) {
+            self.push_run();
+        }
+
+        self.buttons = buttons;
+        self.run += 1;
+    }
+
+    fn push_run(&mut self) {
+        let encoded = self.buttons | ((self.run - 1) << 10);
+        self.data.extend_from_slice(&encoded.to_le_bytes());
+        self.run = 0;
+    }
+
+    /// The recording so far.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut recording = self.clone();
+        if recording.run > 0 {
+            recording.push_run();
+        }
+
+        recording.data
+    }
+
+    /// Prints the recording to the [debug output](crate::debug_output) as hex,
+    /// split over several lines each starting with `input-recording: `. The
+    /// mgba test runner can play these back with its `--input` option.
+    pub fn print(&self) {
+        for chunk in self.to_bytes().chunks(64) {
+            let mut line = String::from(LOG_PREFIX);
+            for byte in chunk {
+                let _ = write!(line, "{byte:02x}");
+            }
+
+            crate::println!("{}", line);
+        }
+    }
+}
+
+/// Plays back a recording made by an [`InputRecorder`] through a
+/// [`ButtonController`].
+///
+/// Like the recorder, [`update`](Self::update) counts vblanks since the game
+/// started, so it plays the frame of the recording for the current vblank. The
+/// first update skips the frames for the vblanks before it, and a frame which
+/// takes too long skips the frames of the recording that would have happened in
+/// the meantime. The mgba test runner plays recordings back in the same way.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// use agb::input::{ButtonController, InputReplay};
+///
+/// static DEMO: &[u8] = &[0x00, 0xfc, 0x01, 0x10];
+///
+/// # fn main() {
+/// let mut input = ButtonController::new();
+/// let mut replay = InputReplay::new(DEMO);
+///
+/// loop {
+///     if !replay.update(&mut input) {
+///         // the recording has finished, so the real buttons are used from now on
+///     }
+///     // run the game
+/// }
+/// # }
+/// ```
+pub struct InputReplay<'a> {
+    data: &'a [u8],
+    buttons: u16,
+    remaining: u16,
+    last_vblank: Option<usize>,
+}
+
+impl<'a> InputReplay<'a> {
+    /// Plays back `recording`. A trailing odd byte is ignored.
+    #[must_use]
+    pub fn new(recording: &'a [u8]) -> Self {
+        Self {
+            data: recording,
+            buttons: 0,
+            remaining: 0,
+            last_vblank: None,
+        }
+    }
+
+    /// The buttons pressed in the next frame of the recording, or `None` if it
+    /// has finished. This doesn't count vblanks.
+    pub fn next_frame(&mut self) -> Option<Button> {
+        if self.remaining == 0 {
+            let (&[low, high], rest) = self.data.split_first_chunk()?;
+            self.data = rest;
+
+            let encoded = u16::from_le_bytes([low, high]);
+            self.buttons = encoded & BUTTON_MASK;
+            self.remaining = (encoded >> 10) + 1;
+        }
+
+        self.remaining -= 1;
+        Some(Button::from_bits_truncate(u32::from(self.buttons)))
+    }
+
+    /// Whether every frame of the recording has been played.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0 && self.data.len() < 2
+    }
+
+    /// Updates `controller` with the next frame of the recording instead of
+    /// the real buttons, and returns true. Once the recording has finished this
+    /// updates it with the real buttons and returns false. Call this once per
+    /// frame.
+    pub fn update(&mut self, controller: &mut ButtonController) -> bool {
+        self.update_at(vblank_count(), controller)
+    }
+
+    fn update_at(&mut self, vblank: usize, controller: &mut ButtonController) -> bool {
+        // skip the frames of any vblanks missed since the last update, or since
+        // the game started for the first one, which the recorder recorded as well
+        let skipped = match self.last_vblank {
+            Some(last_vblank) => vblank.wrapping_sub(last_vblank).saturating_sub(1),
+            None => vblank,
+        };
+        for _ in 0..skipped {
+            self.next_frame();
+        }
+        self.last_vblank = Some(vblank);
+
+        match self.next_frame() {
+            Some(buttons) => {
+                controller.update_with_buttons(buttons);
+                true
+            }
+            None => {
+                controller.update();
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn recordings_round_trip(_gba: &mut crate::Gba) {
+        let mut frames = Vec::new();
+        frames.extend(core::iter::repeat_n(Button::empty(), 100));
+        frames.extend(core::iter::repeat_n(Button::A | Button::RIGHT, 3));
+        frames.push(Button::L);
+
+        let mut recorder = InputRecorder::new();
+        for &buttons in &frames {
+            recorder.record_buttons(buttons);
+        }
+
+        let recording = recorder.to_bytes();
+        // 100 empty frames take two runs, then one run each for the others
+        assert_eq!(recording.len(), 4 * 2);
+
+        let mut replay = InputReplay::new(&recording);
+        for &buttons in &frames {
+            assert!(!replay.is_finished());
+            assert_eq!(replay.next_frame(), Some(buttons));
+        }
+
+        assert!(replay.is_finished());
+        assert_eq!(replay.next_frame(), None);
+    }
+
+    #[test_case]
+    fn replays_drive_the_controller(_gba: &mut crate::Gba) {
+        let mut recorder = InputRecorder::new();
+        recorder.record_buttons(Button::empty());
+        recorder.record_buttons(Button::B);
+        let recording = recorder.to_bytes();
+
+        let mut controller = ButtonController::new();
+        let mut replay = InputReplay::new(&recording);
+
+        assert!(replay.update_at(0, &mut controller));
+        assert!(replay.update_at(1, &mut controller));
+        assert!(controller.is_just_pressed(Button::B));
+        assert!(!replay.update_at(2, &mut controller));
+    }
+
+    #[test_case]
+    fn recording_counts_vblanks(_gba: &mut crate::Gba) {
+        let mut recorder = InputRecorder::new();
+        recorder.record_at(10, Button::A);
+        // a frame which took two vblanks
+        recorder.record_at(12, Button::B);
+
+        let recording = recorder.to_bytes();
+        let mut replay = InputReplay::new(&recording);
+        let frames: Vec<_> = core::iter::from_fn(|| replay.next_frame()).collect();
+
+        assert_eq!(frames.len(), 13);
+        assert!(frames[..10].iter().all(Button::is_empty));
+        assert_eq!(frames[10..], [Button::A, Button::B, Button::B]);
+    }
+
+    #[test_case]
+    fn recordings_start_from_the_vblank_they_were_made_at(_gba: &mut crate::Gba) {
+        let mut recorder = InputRecorder::new();
+        recorder.record_at(5, Button::B);
+        recorder.record_at(6, Button::A);
+        recorder.record_at(8, Button::empty());
+        let recording = recorder.to_bytes();
+
+        let mut controller = ButtonController::new();
+        let mut replay = InputReplay::new(&recording);
+
+        assert!(replay.update_at(5, &mut controller));
+        assert!(controller.is_just_pressed(Button::B));
+        assert!(replay.update_at(6, &mut controller));
+        assert!(controller.is_just_pressed(Button::A));
+        assert!(replay.update_at(8, &mut controller));
+        assert!(controller.is_released(Button::A));
+        assert!(!replay.update_at(9, &mut controller));
+    }
+
+    #[test_case]
+    fn recording_uses_the_current_vblank(_gba: &mut crate::Gba) {
+        let vblank = crate::interrupt::VBlank::get();
+        vblank.wait_for_vblank();
+
+        let mut controller = ButtonController::new();
+        controller.update_with_buttons(Button::START);
+
+        let before = vblank_count();
+        let mut recorder = InputRecorder::new();
+        recorder.record(&controller);
+        let after = vblank_count();
+
+        let recording = recorder.to_bytes();
+        let mut replay = InputReplay::new(&recording);
+        let frames: Vec<_> = core::iter::from_fn(|| replay.next_frame()).collect();
+
+        assert!(before > 0);
+        assert!((before + 1..=after + 1).contains(&frames.len()));
+        assert_eq!(frames.last(), Some(&Button::START));
+        assert!(frames[..frames.len() - 1].iter().all(Button::is_empty));
+    }
+}
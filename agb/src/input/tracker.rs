@@ -0,0 +1,375 @@
+use super::{Button, ButtonController};
+
+/// The number of frames of button presses an [`InputTracker`] remembers.
+const HISTORY_LENGTH: usize = 64;
+
+const BUTTON_COUNT: usize = 10;
+const DIRECTIONS: Button = Button::UP
+    .union(Button::DOWN)
+    .union(Button::LEFT)
+    .union(Button::RIGHT);
+
+fn button_index(button: Button) -> usize {
+    assert!(
+        button.bits().count_ones() == 1,
+        "expected a single button but got {button:?}"
+    );
+
+    button.bits().trailing_zeros() as usize
+}
+
+/// A sequence of inputs which have to be entered in order, such as down,
+/// down-forward, forward + A, for use with [`InputTracker::is_combo_performed`].
+///
+/// Each step is the direction the d-pad has to be pointing in, the buttons
+/// which have to be pressed, or both. A step with a direction is entered on the
+/// frame the d-pad starts pointing in exactly that direction, or on the frame
+/// its buttons are pressed while the d-pad is pointing that way. A step with
+/// only buttons is entered on the frame they are all pressed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// use agb::input::{Button, Combo};
+///
+/// // a quarter circle forward punch for a character facing right
+/// static FIREBALL: Combo = Combo::new(
+///     &[
+///         Button::DOWN,
+///         Button::DOWN.union(Button::RIGHT),
+///         Button::RIGHT.union(Button::A),
+///     ],
+///     8,
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Combo<'a> {
+    steps: &'a [Button],
+    max_gap: u16,
+}
+
+impl<'a> Combo<'a> {
+    /// Creates a combo where each step has to be entered at most `max_gap`
+    /// frames after the previous one.
+    ///
+    /// # Panics
+    ///
+    /// If there are no steps, or if `max_gap` is 0.
+    #[must_use]
+    pub const fn new(steps: &'a [Button], max_gap: u16) -> Self {
+        assert!(!steps.is_empty(), "a combo needs at least one step");
+        assert!(max_gap > 0, "the gap between combo steps can't be 0");
+
+        Self { steps, max_gap }
+    }
+
+    /// The steps of the combo.
+    #[must_use]
+    pub const fn steps(&self) -> &'a [Button] {
+        self.steps
+    }
+
+    /// The most frames allowed between one step and the next.
+    #[must_use]
+    pub const fn max_gap(&self) -> u16 {
+        self.max_gap
+    }
+}
+
+/// Adds input buffering, auto-repeat, hold durations and combo detection on top
+/// of a [`ButtonController`]. Call [`update`](InputTracker::update) every frame
+/// after updating the controller.
+///
+/// Methods which take a single [`Button`] panic if they are given more than one.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// use agb::input::{Button, ButtonController, InputTracker};
+///
+/// # fn main() {
+/// let mut input = ButtonController::new();
+/// let mut tracker = InputTracker::new(&input);
+///
+/// loop {
+///     input.update();
+///     tracker.update(&input);
+///
+///     // a jump pressed up to 5 frames before landing still counts
+///     # let on_ground = true;
+///     if on_ground && tracker.consume_buffered(Button::A, 5) {
+///         // jump
+///     }
+///
+///     if tracker.is_repeated(Button::DOWN) {
+///         // move down a menu item, repeating while down is held
+///     }
+/// }
+/// # }
+/// ```
+pub struct InputTracker {
+    frame: u32,
+    history: [u16; HISTORY_LENGTH],
+    last_pressed: [u32; BUTTON_COUNT],
+    consumed: u16,
+    repeat_delay: u32,
+    repeat_rate: u32,
+}
+
+impl InputTracker {
+    /// Creates a tracker starting with the buttons currently pressed on
+    /// `controller`. Buttons which are already held count as having been
+    /// pressed this frame.
+    #[must_use]
+    pub fn new(controller: &ButtonController) -> Self {
+        let mut history = [0; HISTORY_LENGTH];
+        history[0] = controller.current;
+
+        Self {
+            frame: 0,
+            history,
+            last_pressed: [0; BUTTON_COUNT],
+            // buttons which haven't been pressed yet have nothing to buffer
+            consumed: !controller.current & ((1 << BUTTON_COUNT) - 1),
+            repeat_delay: 20,
+            repeat_rate: 4,
+        }
+    }
+
+    /// Records the buttons pressed on `controller` for this frame.
+    pub fn update(&mut self, controller: &ButtonController) {
+        self.record(controller.current);
+    }
+
+    fn record(&mut self, buttons: u16) {
+        let previous = self.buttons_at(0);
+
+        self.frame += 1;
+        self.history[self.frame as usize % HISTORY_LENGTH] = buttons;
+
+        let just_pressed = buttons & !previous;
+        for (index, last_pressed) in self.last_pressed.iter_mut().enumerate() {
+            if just_pressed & (1 << index) != 0 {
+                *last_pressed = self.frame;
+            }
+        }
+
+        self.consumed &= !just_pressed;
+    }
+
+    /// The buttons pressed `frames_ago` frames ago, or nothing if that is
+    /// before the tracker was created or further back than it remembers.
+    fn buttons_at(&self, frames_ago: u32) -> u16 {
+        if frames_ago > self.frame || frames_ago as usize >= HISTORY_LENGTH {
+            return 0;
+        }
+
+        self.history[(self.frame - frames_ago) as usize % HISTORY_LENGTH]
+    }
+
+    /// Sets how many frames a button has to be held before it starts repeating,
+    /// and how many frames there are between each repeat after that. This
+    /// starts as a delay of 20 frames and a rate of 4.
+    ///
+    /// # Panics
+    ///
+    /// If `rate` is 0.
+    pub fn set_repeat(&mut self, delay: u32, rate: u32) -> &mut Self {
+        assert!(rate > 0, "the repeat rate can't be 0");
+
+        self.repeat_delay = delay;
+        self.repeat_rate = rate;
+
+        self
+    }
+
+    /// The number of frames `button` has been held for, including this one, or
+    /// 0 if it isn't pressed.
+    #[must_use]
+    pub fn held_frames(&self, button: Button) -> u32 {
+        let index = button_index(button);
+
+        if self.buttons_at(0) & (1 << index) == 0 {
+            0
+        } else {
+            self.frame - self.last_pressed[index] + 1
+        }
+    }
+
+    /// Returns true on the frame `button` is pressed, and then again at the
+    /// [repeat rate](Self::set_repeat) once it has been held for longer than
+    /// the repeat delay. Useful for scrolling through menus.
+    #[must_use]
+    pub fn is_repeated(&self, button: Button) -> bool {
+        let held = self.held_frames(button);
+
+        held == 1
+            || (held > self.repeat_delay
+                && (held - self.repeat_delay - 1).is_multiple_of(self.repeat_rate))
+    }
+
+    /// Returns true if `button` was pressed in the last `frames` frames,
+    /// including this one, and that press hasn't been
+    /// [consumed](Self::consume_buffered).
+    #[must_use]
+    pub fn is_buffered(&self, button: Button, frames: u32) -> bool {
+        let index = button_index(button);
+
+        self.consumed & (1 << index) == 0 && self.frame - self.last_pressed[index] < frames
+    }
+
+    /// Returns true and consumes the press if `button` [is
+    /// buffered](Self::is_buffered), so the same press doesn't count twice.
+    pub fn consume_buffered(&mut self, button: Button, frames: u32) -> bool {
+        if self.is_buffered(button, frames) {
+            self.consumed |= 1 << button_index(button);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether `step` of a combo was entered `frames_ago` frames ago.
+    fn is_step_entered(&self, step: Button, frames_ago: u32) -> bool {
+        let step = step.bits() as u16;
+        let directions = DIRECTIONS.bits() as u16;
+
+        let current = self.buttons_at(frames_ago);
+        let previous = self.buttons_at(frames_ago + 1);
+
+        let step_direction = step & directions;
+        let step_buttons = step & !directions;
+
+        if step_direction != 0 && current & directions != step_direction {
+            return false;
+        }
+
+        if step_buttons != 0 {
+            current & step_buttons == step_buttons && previous & step_buttons != step_buttons
+        } else {
+            previous & directions != step_direction
+        }
+    }
+
+    /// Returns true on the frame the last step of `combo` is entered, as long
+    /// as each of the steps before it were entered in order with at most the
+    /// combo's [gap](Combo::max_gap) between them. Only the last 64 frames are
+    /// remembered, so longer combos are never performed.
+    #[must_use]
+    pub fn is_combo_performed(&self, combo: &Combo) -> bool {
+        let (&last, earlier) = combo
+            .steps
+            .split_last()
+            .expect("combos have at least one step");
+
+        if !self.is_step_entered(last, 0) {
+            return false;
+        }
+
+        // go backwards, taking the latest frame each step could have been
+        // entered on to leave the most room for the steps before it
+        let mut frames_ago = 0;
+        for &step in earlier.iter().rev() {
+            let Some(entered) = (frames_ago + 1..=frames_ago + u32::from(combo.max_gap))
+                .find(|&frame| self.is_step_entered(step, frame))
+            else {
+                return false;
+            };
+
+            frames_ago = entered;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(frames: &[Button]) -> InputTracker {
+        let mut tracker = InputTracker::new(&ButtonController {
+            previous: 0,
+            current: 0,
+        });
+
+        for buttons in frames {
+            tracker.record(buttons.bits() as u16);
+        }
+
+        tracker
+    }
+
+    #[test_case]
+    fn repeat_and_hold(_gba: &mut crate::Gba) {
+        let mut tracker = tracker(&[]);
+        tracker.set_repeat(3, 2);
+
+        let repeats: [bool; 9] = core::array::from_fn(|_| {
+            tracker.record(Button::DOWN.bits() as u16);
+            tracker.is_repeated(Button::DOWN)
+        });
+
+        assert_eq!(
+            repeats,
+            [true, false, false, true, false, true, false, true, false]
+        );
+        assert_eq!(tracker.held_frames(Button::DOWN), 9);
+        assert_eq!(tracker.held_frames(Button::UP), 0);
+    }
+
+    #[test_case]
+    fn buffered_presses_are_consumed(_gba: &mut crate::Gba) {
+        let empty = Button::empty();
+        let mut tracker = tracker(&[Button::A, empty, empty]);
+
+        assert!(!tracker.is_buffered(Button::A, 2));
+        assert!(tracker.is_buffered(Button::A, 3));
+        assert!(tracker.consume_buffered(Button::A, 3));
+        assert!(!tracker.consume_buffered(Button::A, 3));
+    }
+
+    #[test_case]
+    fn buttons_held_at_the_start_are_buffered(_gba: &mut crate::Gba) {
+        let mut tracker = InputTracker::new(&ButtonController {
+            previous: 0,
+            current: Button::B.bits() as u16,
+        });
+
+        assert!(tracker.is_buffered(Button::B, 1));
+        assert!(!tracker.is_buffered(Button::A, 1));
+
+        tracker.record(Button::B.bits() as u16);
+        assert!(tracker.consume_buffered(Button::B, 2));
+        assert!(!tracker.is_buffered(Button::B, 2));
+    }
+
+    #[test_case]
+    fn combos(_gba: &mut crate::Gba) {
+        let steps = [
+            Button::DOWN,
+            Button::DOWN.union(Button::RIGHT),
+            Button::RIGHT.union(Button::A),
+        ];
+        let combo = Combo::new(&steps, 3);
+
+        let empty = Button::empty();
+        let down = Button::DOWN;
+        let down_right = Button::DOWN | Button::RIGHT;
+        let right = Button::RIGHT;
+
+        let performed = tracker(&[down, down, down_right, right, right | Button::A]);
+        assert!(performed.is_combo_performed(&combo));
+
+        let too_slow = tracker(&[down, down_right, right, right, right, right | Button::A]);
+        assert!(!too_slow.is_combo_performed(&combo));
+
+        let out_of_order = tracker(&[down_right, down, right | Button::A]);
+        assert!(!out_of_order.is_combo_performed(&combo));
+
+        let not_this_frame = tracker(&[down, down_right, right | Button::A, empty]);
+        assert!(!not_this_frame.is_combo_performed(&combo));
+    }
+}
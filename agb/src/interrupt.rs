@@ -67,6 +67,7 @@ impl Interrupt {
 }
 
 const ENABLED_INTERRUPTS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x04000200) };
+const INTERRUPTS_REQUESTED: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x04000202) };
 const INTERRUPTS_ENABLED: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x04000208) };
 
 struct Disable {
@@ -91,6 +92,14 @@ fn disable_interrupts() {
     INTERRUPTS_ENABLED.set(0);
 }
 
+/// Turns off every interrupt and acknowledges any which are waiting to happen.
+/// Nothing turns them back on, so this is only useful when resetting.
+pub(crate) fn disable_all_interrupts() {
+    disable_interrupts();
+    ENABLED_INTERRUPTS.set(0);
+    INTERRUPTS_REQUESTED.set(u16::MAX);
+}
+
 struct InterruptRoot {
     next: Cell<*const InterruptInner>,
     count: Cell<i32>,
@@ -302,6 +311,23 @@ unsafe impl critical_section::Impl for MyCriticalSection {
 static NUM_VBLANKS: AtomicUsize = AtomicUsize::new(0); // overflows after 2.27 years
 static HAS_CREATED_INTERRUPT: AtomicBool = AtomicBool::new(false);
 
+/// The number of vblanks since this was first called or a [`VBlank`] was first
+/// created, whichever came first. This happens when the game starts, before the
+/// first vblank.
+pub(crate) fn vblank_count() -> usize {
+    if !HAS_CREATED_INTERRUPT.swap(true, Ordering::SeqCst) {
+        // safety: we don't allocate in the interrupt
+        let handler = unsafe {
+            add_interrupt_handler(Interrupt::VBlank, |_| {
+                NUM_VBLANKS.store(NUM_VBLANKS.load(Ordering::SeqCst) + 1, Ordering::SeqCst);
+            })
+        };
+        core::mem::forget(handler);
+    }
+
+    NUM_VBLANKS.load(Ordering::SeqCst)
+}
+
 #[non_exhaustive]
 pub struct VBlank {
     last_waited_number: Cell<usize>,
@@ -312,32 +338,31 @@ impl VBlank {
     /// interrupt syscall.
     #[must_use]
     pub fn get() -> Self {
-        if !HAS_CREATED_INTERRUPT.swap(true, Ordering::SeqCst) {
-            // safety: we don't allocate in the interrupt
-            let handler = unsafe {
-                add_interrupt_handler(Interrupt::VBlank, |_| {
-                    NUM_VBLANKS.store(NUM_VBLANKS.load(Ordering::SeqCst) + 1, Ordering::SeqCst);
-                })
-            };
-            core::mem::forget(handler);
-        }
-
         VBlank {
-            last_waited_number: Cell::new(NUM_VBLANKS.load(Ordering::SeqCst)),
+            last_waited_number: Cell::new(vblank_count()),
         }
     }
     /// Pauses CPU until vblank interrupt is triggered where code execution is
     /// resumed.
     pub fn wait_for_vblank(&self) {
+        self.wait_for_vblank_and_count_missed();
+    }
+
+    /// Waits for vblank in the same way as [`wait_for_vblank`](Self::wait_for_vblank),
+    /// and returns the number of vblanks that were missed since the last time
+    /// this was called. This is 0 unless the frame took too long, in which case
+    /// it doesn't wait and the next frame starts straight away.
+    pub fn wait_for_vblank_and_count_missed(&self) -> usize {
         let last_waited_number = self.last_waited_number.get();
-        self.last_waited_number
-            .set(NUM_VBLANKS.load(Ordering::SeqCst) + 1);
+        let current_number = NUM_VBLANKS.load(Ordering::SeqCst);
+        self.last_waited_number.set(current_number + 1);
 
-        if last_waited_number < NUM_VBLANKS.load(Ordering::SeqCst) {
-            return;
+        if last_waited_number < current_number {
+            return current_number - last_waited_number;
         }
 
         crate::syscall::wait_for_vblank();
+        0
     }
 }
 
@@ -352,6 +377,9 @@ impl VBlank {
 /// With that out of the way, the current version will, in mgba, output the
 /// program counter at regular intervals. This can be used to see hot functions
 /// using, for example, addr2line.
+///
+/// The `profiler` module has a sampling profiler which also records the callers
+/// and works with `agb-debug profile`.
 pub fn profiler(timer: &mut crate::timer::Timer, period: u16) -> InterruptHandler {
     timer.set_interrupt(true);
     timer.set_overflow_amount(period);
@@ -19,6 +19,14 @@ InterruptHandler:
     ldr r3, =agb_rs__program_counter
     str r1, [r3]
 
+    @ save the frame pointer of the interrupted code, which is r7 in thumb code
+    @ and r11 in arm code
+    mrs r1, spsr
+    tst r1, #0x20
+    ldr r3, =agb_rs__frame_pointer
+    strne r7, [r3]
+    streq r11, [r3]
+
     @ change to system mode
     mrs r1, cpsr
     orr r1, r1, #0xD
@@ -55,3 +63,9 @@ InterruptHandler:
     .balign 4
 agb_rs__program_counter:
     .word 0
+
+.section .iwram.frame_pointer
+    .global agb_rs__frame_pointer
+    .balign 4
+agb_rs__frame_pointer:
+    .word 0
@@ -104,6 +104,20 @@
 /// include_background_gfx!(generated_background, "000000", DATA => "$OUT_DIR/generated_background.aseprite");
 /// ```
 ///
+/// By default every tile of the image is included in order. Putting `deduplicate` before the
+/// file name only includes identical tiles once, including flipped copies when using 16 colours,
+/// with the tile settings saying which tile goes where.
+///
+/// Putting `map` before the file name deduplicates the tiles in the same way, but produces a
+/// [`TileMap`][crate::display::tile_data::TileMap] instead which also knows the size of the
+/// image. This lets you use images of any size, and copy any part of them to a background.
+///
+/// ```rust,ignore
+/// agb::include_background_gfx!(backgrounds, "2ce8f4", title => map "gfx/title.png");
+///
+/// backgrounds::title.draw_to(&mut bg, (0, 0));
+/// ```
+///
 /// You can also make the exported background a public module which will allow other modules access them. The following
 /// will declare `water_tiles` as a `pub mod` rather than a `mod`.
 ///
@@ -123,14 +137,121 @@ pub use agb_image_converter::include_font as include_font_inner;
 #[doc(hidden)]
 pub use agb_image_converter::include_colours_inner;
 
+/// Imports a map made with the [Tiled](https://www.mapeditor.org) map editor from a tmx or tmj file.
+///
+/// The map must be orthogonal, finite and use 8x8 tiles from tilesets which are based on a
+/// single image. Tile layers can use csv or uncompressed base64 encoding, and tiles may be
+/// flipped but not rotated. Layers inside groups are imported as if they weren't in a group.
+///
+/// The generated module contains:
+///
+/// * `PALETTES` and `TILES` which contain the palettes and every tile from every tileset the
+///   map uses, with the tile ids matching those in the tileset,
+/// * a [`MapLayer`](display::tiled::MapLayer) for each tile layer and a
+///   [`MapObjectLayer`](display::tiled::MapObjectLayer) for each object layer, named after the
+///   layer in upper case, along with `TILE_LAYERS` and `OBJECT_LAYERS` listing them in order,
+/// * `PROPERTIES` containing the custom properties of the map, and `WIDTH` and `HEIGHT`
+///   giving the size of the map in tiles.
+///
+/// The class and custom properties of tiles in the tileset can be looked up by position in
+/// each layer, which is useful for things like collision.
+///
+/// ```rust,ignore
+/// agb::include_tiled_map!(level, "maps/level1.tmx");
+///
+/// VRAM_MANAGER.set_background_palettes(level::PALETTES);
+///
+/// for y in 0..level::HEIGHT as i32 {
+///     for x in 0..level::WIDTH as i32 {
+///         bg.set_tile((x, y), &level::TILES.tiles, level::GROUND.tile_setting((x, y)));
+///     }
+/// }
+///
+/// let is_solid = level::GROUND.tile_properties(player_tile).flag("solid");
+/// let spawn = level::OBJECTS.object("player").unwrap().position();
+/// ```
+///
+/// Like [`include_background_gfx!`], you can pass a transparent colour as a string before
+/// the path, make the module public with `pub` and include from the out directory using the
+/// `$OUT_DIR` token.
+pub use agb_image_converter::include_tiled_map;
+
+/// Imports the levels of a project made with the [LDtk](https://ldtk.io) level editor.
+///
+/// Every tile layer, auto-layer and IntGrid layer with auto-layer tiles must use an 8x8 grid
+/// and a tileset with 8x8 tiles. Levels saved in separate files are supported, as are
+/// projects with multiple worlds. Only one tile can be shown in each cell of a layer, so if
+/// tiles are stacked in LDtk only the top-most one is imported.
+///
+/// The generated module contains:
+///
+/// * `PALETTES` and `TILES` which contain the palettes and every tile from every tileset used
+///   by a tile layer,
+/// * a [`MapLevel`](display::tiled::MapLevel) for each level named after the identifier of
+///   the level in upper case, along with `LEVELS` listing every level in order.
+///
+/// Each level contains its tile layers as [`MapLayer`](display::tiled::MapLayer)s, its IntGrid
+/// layers as [`MapIntGridLayer`](display::tiled::MapIntGridLayer)s, its entities as
+/// [`MapObject`](display::tiled::MapObject)s and links to its neighbouring levels. Custom
+/// fields of levels and entities become properties, and the enum tags of tiles become flags
+/// in the tile properties with the first tag as the tile's class.
+///
+/// ```rust,ignore
+/// agb::include_ldtk!(world, "maps/world.ldtk");
+///
+/// let level = &world::LEVEL_0;
+/// let background = level.layer("Background").unwrap();
+/// let is_wall = level.int_grid("Collision").unwrap().value_at_pixel(player_position) == 1;
+///
+/// if let Some(next) = level.neighbours_in(NeighbourDirection::East).next() {
+///     // move to the next room
+/// }
+/// ```
+///
+/// Like [`include_background_gfx!`], you can pass a transparent colour as a string before
+/// the path, make the module public with `pub` and include from the out directory using the
+/// `$OUT_DIR` token.
+pub use agb_image_converter::include_ldtk;
+
+/// Includes a ttf font for use with the text renderers.
+///
+/// By default, every glyph in the font is included. If you pass the path to a string table
+/// as used by [`include_strings!`], only the characters which appear in that table are
+/// included, and compilation fails if the font is missing any of them. You can further
+/// restrict this to the characters used by specific languages in the table, which is useful
+/// when each language needs its own font.
+///
+/// ```rust,ignore
+/// static FONT: Font = include_font!("fnt/ark-pixel-10px-proportional-ja.ttf", 10);
+/// static LATIN_FONT: Font = include_font!("fnt/font.ttf", 10, "strings.csv", "en", "fr");
+/// ```
 #[macro_export]
 macro_rules! include_font {
-    ($font_path: literal, $font_size: literal) => {{
+    ($font_path: literal, $font_size: literal $(, $strings_path: literal $(, $language: literal)*)?) => {{
         use $crate::display;
-        $crate::include_font_inner!($font_path, $font_size)
+        $crate::include_font_inner!($font_path, $font_size $(, $strings_path $(, $language)*)?)
     }};
 }
 
+/// Includes a table of translated strings from a csv file for use with the
+/// [`localisation`] module.
+///
+/// The first argument is the name of the module to generate, which can optionally be
+/// made public with `pub`. The module will contain a [`StringTable`](localisation::StringTable)
+/// called `TABLE`, a `languages` module with a [`LanguageId`](localisation::LanguageId)
+/// constant for each language and a `keys` module with a [`StringId`](localisation::StringId)
+/// constant for each key, both named in upper case.
+///
+/// ```rust,ignore
+/// agb::include_strings!(strings, "strings.csv");
+///
+/// strings::TABLE.set_language(strings::languages::FR);
+/// write!(renderer, "{}", strings::TABLE.format(strings::keys::GREETING, &["Ferris".into()]))?;
+/// ```
+///
+/// Including from the out directory is supported through the `$OUT_DIR` token.
+pub use agb_image_converter::include_strings;
+
 /// This macro declares the entry point to your game written using `agb`.
 ///
 /// It is already included in the template, but your `main` function must be annotated with `#[agb::entry]`, takes 1 argument and never returns.
@@ -158,23 +279,33 @@ mod agb_alloc;
 mod agbabi;
 #[cfg(feature = "backtrace")]
 mod backtrace;
+pub mod debug_output;
 /// Implements everything relating to things that are displayed on screen.
 pub mod display;
 /// Provides access to the GBA's direct memory access (DMA) which is used for advanced effects
 pub mod dma;
+pub mod frame_timing;
 /// Button inputs to the system.
 pub mod input;
 /// Interacting with the GBA interrupts
 pub mod interrupt;
+pub mod localisation;
+/// The [`log`] crate, which agb's [`logger`] writes to the emulator's debug output.
+#[doc(inline)]
+pub use log;
+pub mod logger;
 mod memory_mapped;
 /// Implements logging to the mgba emulator.
 pub mod mgba;
+pub mod multiboot;
 #[doc(inline)]
 pub use agb_fixnum as fixnum;
 /// Contains an implementation of a hashmap which suits the gameboy advance's hardware.
 pub use agb_hashmap as hash_map;
 #[cfg(feature = "backtrace")]
 mod panics_render;
+#[cfg(feature = "backtrace")]
+pub mod profiler;
 /// Simple random number generator
 pub mod rng;
 pub mod save;
@@ -215,17 +346,13 @@ fn panic_implementation(info: &core::panic::PanicInfo) -> ! {
     let frames = backtrace::unwind_exception();
 
     #[cfg(feature = "testing")]
-    if let Some(mut mgba) = mgba::Mgba::new() {
-        let _ = mgba.print(format_args!("[failed]"), mgba::DebugLevel::Error);
-    }
+    let _ = debug_output::print(format_args!("[failed]"), mgba::DebugLevel::Error);
 
     #[cfg(feature = "backtrace")]
     crate::panics_render::render_backtrace(&frames, info);
 
     #[cfg(not(feature = "backtrace"))]
-    if let Some(mut mgba) = mgba::Mgba::new() {
-        let _ = mgba.print(format_args!("{info}"), mgba::DebugLevel::Fatal);
-    }
+    let _ = debug_output::print(format_args!("{info}"), mgba::DebugLevel::Fatal);
 
     #[cfg(not(feature = "backtrace"))]
     loop {
@@ -239,12 +366,10 @@ fn avoid_double_panic(info: &core::panic::PanicInfo) {
     static IS_PANICKING: portable_atomic::AtomicBool = portable_atomic::AtomicBool::new(false);
 
     if IS_PANICKING.load(portable_atomic::Ordering::SeqCst) {
-        if let Some(mut mgba) = mgba::Mgba::new() {
-            let _ = mgba.print(
-                format_args!("Double panic: {info}"),
-                mgba::DebugLevel::Fatal,
-            );
-        }
+        let _ = debug_output::print(
+            format_args!("Double panic: {info}"),
+            mgba::DebugLevel::Fatal,
+        );
         loop {
             syscall::halt();
         }
@@ -298,11 +423,72 @@ impl Gba {
     /// May only be called a single time. It is not needed to call this due to
     /// it being called internally by the [`entry`] macro.
     pub unsafe fn new_in_entry() -> Self {
+        // start counting vblanks straight away, so input recordings count frames
+        // from when the game started
+        interrupt::vblank_count();
         VRAM_MANAGER.initialise();
 
         Self::single_new()
     }
 
+    /// Restarts the game from the start, so the [`entry`] function runs again
+    /// as if the GBA had just been turned on. The memory and registers in
+    /// `clear` are cleared first.
+    ///
+    /// Sound, timers, DMA and interrupts are all stopped before resetting, and
+    /// static variables and the allocators go back to how they were when the
+    /// game started. Anything not in `clear`, such as what is on the screen,
+    /// is left as it is. Save data is not touched.
+    ///
+    /// For multiboot games, static variables which don't start as zero keep the
+    /// values they had before the reset.
+    ///
+    /// # Panics
+    ///
+    /// If `clear` contains [`RamResetFlags::EWRAM`](syscall::RamResetFlags::EWRAM)
+    /// in a multiboot game, since that would remove the game itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #![no_std]
+    /// # #![no_main]
+    /// use agb::{input::{Button, ButtonController}, syscall::RamResetFlags};
+    ///
+    /// # fn foo(gba: &mut agb::Gba) {
+    /// let soft_reset = Button::A | Button::B | Button::START | Button::SELECT;
+    /// let mut input = ButtonController::new();
+    ///
+    /// loop {
+    ///     input.update();
+    ///
+    ///     if soft_reset.iter().all(|button| input.is_pressed(button)) {
+    ///         gba.soft_reset(RamResetFlags::VRAM | RamResetFlags::OAM);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn soft_reset(&mut self, clear: syscall::RamResetFlags) -> ! {
+        // Where SoftReset restarts from, 0 for the cartridge or anything else
+        // for ewram
+        const RESTART_FROM_EWRAM: memory_mapped::MemoryMapped<u8> =
+            unsafe { memory_mapped::MemoryMapped::new(0x0300_7FFA) };
+
+        assert!(
+            !(cfg!(feature = "multiboot") && clear.contains(syscall::RamResetFlags::EWRAM)),
+            "multiboot games can't clear ewram when resetting"
+        );
+
+        interrupt::disable_all_interrupts();
+        dma::stop_all_dma();
+        timer::stop_all_timers();
+        sound::dmg::MASTER_SOUND_STATUS.set(0);
+
+        RESTART_FROM_EWRAM.set(u8::from(cfg!(feature = "multiboot")));
+
+        unsafe { syscall::soft_reset(clear) }
+    }
+
     const unsafe fn single_new() -> Self {
         Self {
             display: display::Display::new(),
@@ -354,8 +540,7 @@ pub mod test_runner {
         T: Fn(&mut Gba),
     {
         fn run(&self, gba: &mut Gba) {
-            let mut mgba = mgba::Mgba::new().unwrap();
-            mgba.print(
+            debug_output::print(
                 format_args!("{}...", core::any::type_name::<T>()),
                 mgba::DebugLevel::Info,
             )
@@ -364,8 +549,7 @@ pub mod test_runner {
             self(gba);
             mgba::test_runner_measure_cycles();
 
-            mgba.print(format_args!("[ok]"), mgba::DebugLevel::Info)
-                .unwrap();
+            debug_output::print(format_args!("[ok]"), mgba::DebugLevel::Info).unwrap();
         }
     }
 
@@ -373,8 +557,7 @@ pub mod test_runner {
 
     #[doc(hidden)]
     pub fn test_runner(tests: &[&dyn Testable]) {
-        let mut mgba = mgba::Mgba::new().unwrap();
-        mgba.print(
+        debug_output::print(
             format_args!("Running {} tests", tests.len()),
             mgba::DebugLevel::Info,
         )
@@ -386,7 +569,7 @@ pub mod test_runner {
             test.run(gba);
         }
 
-        mgba.print(
+        debug_output::print(
             format_args!("Tests finished successfully"),
             mgba::DebugLevel::Info,
         )
@@ -434,6 +617,15 @@ pub(crate) fn program_counter_before_interrupt() -> u32 {
     unsafe { agb_rs__program_counter }
 }
 
+#[cfg(feature = "backtrace")]
+#[inline(never)]
+pub(crate) fn frame_pointer_before_interrupt() -> u32 {
+    extern "C" {
+        static mut agb_rs__frame_pointer: u32;
+    }
+    unsafe { agb_rs__frame_pointer }
+}
+
 #[cfg(test)]
 mod test {
     use core::ptr::addr_of_mut;
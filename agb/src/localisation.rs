@@ -0,0 +1,402 @@
+//! # Localisation
+//!
+//! Support for displaying the same text in multiple languages. Translations are stored in a
+//! csv file which gets converted into a [`StringTable`] in ROM at compile time using the
+//! [`include_strings!`](crate::include_strings) macro.
+//!
+//! The first row of the csv file must be `key` followed by the code of each language
+//! (e.g. `en`, `fr`, `ja`). Each further row contains a key followed by the translation of
+//! that key in each language:
+//!
+//! ```csv
+//! key,en,fr
+//! greeting,"Hello, {0}!","Bonjour, {0} !"
+//! apples,"{0:# apple|# apples}","{0:# pomme|# pommes}"
+//! ```
+//!
+//! Translations can contain the following placeholders:
+//!
+//! * `{n}` is replaced with argument `n`.
+//! * `{n:form|form|...}` picks one of the forms based on the [`PluralRule`] of the current
+//!   language and the value of argument `n`. Any `#` in the chosen form is replaced with
+//!   the argument.
+//! * `{{` and `}}` output a literal `{` and `}`.
+//!
+//! The number of arguments used by each key is checked to be the same across every language
+//! at compile time, as is the number of forms in each plural placeholder matching what the
+//! [`PluralRule`] of the language needs.
+
+use core::fmt::{self, Display, Write};
+
+use portable_atomic::{AtomicU8, Ordering};
+
+/// Identifies a language in a [`StringTable`]. Constants for each language are generated by
+/// [`include_strings!`](crate::include_strings) in the `languages` module with the upper case
+/// language code as the name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LanguageId(u8);
+
+impl LanguageId {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(index: u8) -> Self {
+        Self(index)
+    }
+}
+
+/// Identifies a string in a [`StringTable`]. Constants for each key are generated by
+/// [`include_strings!`](crate::include_strings) in the `keys` module with the upper case key
+/// as the name, so these remain stable even if rows in the translation file get reordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringId(u16);
+
+impl StringId {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(index: u16) -> Self {
+        Self(index)
+    }
+}
+
+/// How a language picks between plural forms in a `{n:form|form|...}` placeholder.
+/// The rule for each language is chosen from its language code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralRule {
+    /// A single form is used regardless of number (Japanese, Chinese, Korean...).
+    Invariant,
+    /// `one` for 1, `other` otherwise (English, German, Spanish...).
+    OneOther,
+    /// `one` for 0 and 1, `other` otherwise (French, Portuguese).
+    OneIncludesZero,
+    /// `one`, `few` and `many` forms as used by Russian, Ukrainian and related languages.
+    EastSlavic,
+    /// `one`, `few` and `many` forms as used by Polish.
+    Polish,
+    /// `one`, `few` and `other` forms as used by Czech and Slovak.
+    Czech,
+}
+
+impl PluralRule {
+    /// The index of the plural form to use for the given number.
+    #[must_use]
+    pub fn form(self, n: i32) -> usize {
+        let n = n.unsigned_abs();
+        let (tens, hundreds) = (n % 10, n % 100);
+
+        match self {
+            PluralRule::Invariant => 0,
+            PluralRule::OneOther => usize::from(n != 1),
+            PluralRule::OneIncludesZero => usize::from(n > 1),
+            PluralRule::EastSlavic => {
+                if tens == 1 && hundreds != 11 {
+                    0
+                } else if (2..=4).contains(&tens) && !(12..=14).contains(&hundreds) {
+                    1
+                } else {
+                    2
+                }
+            }
+            PluralRule::Polish => {
+                if n == 1 {
+                    0
+                } else if (2..=4).contains(&tens) && !(12..=14).contains(&hundreds) {
+                    1
+                } else {
+                    2
+                }
+            }
+            PluralRule::Czech => match n {
+                1 => 0,
+                2..=4 => 1,
+                _ => 2,
+            },
+        }
+    }
+}
+
+/// A language available in a [`StringTable`].
+pub struct Language {
+    code: &'static str,
+    plural_rule: PluralRule,
+}
+
+impl Language {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(code: &'static str, plural_rule: PluralRule) -> Self {
+        Self { code, plural_rule }
+    }
+
+    /// The language code as written in the header of the translation file.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// The plural rule used by this language.
+    #[must_use]
+    pub fn plural_rule(&self) -> PluralRule {
+        self.plural_rule
+    }
+}
+
+/// An argument which can be substituted into a localised string.
+#[derive(Clone, Copy)]
+pub enum Argument<'a> {
+    /// A number. These can be used to select plural forms.
+    Number(i32),
+    /// Some text, for example a player's name.
+    Text(&'a str),
+    /// Anything else which can be displayed. Any plural form selected by this
+    /// argument will be the last one.
+    Display(&'a dyn Display),
+}
+
+impl From<i32> for Argument<'_> {
+    fn from(value: i32) -> Self {
+        Argument::Number(value)
+    }
+}
+
+impl From<u16> for Argument<'_> {
+    fn from(value: u16) -> Self {
+        Argument::Number(value.into())
+    }
+}
+
+impl From<u8> for Argument<'_> {
+    fn from(value: u8) -> Self {
+        Argument::Number(value.into())
+    }
+}
+
+impl<'a> From<&'a str> for Argument<'a> {
+    fn from(value: &'a str) -> Self {
+        Argument::Text(value)
+    }
+}
+
+impl Display for Argument<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Number(n) => write!(f, "{n}"),
+            Argument::Text(text) => f.write_str(text),
+            Argument::Display(display) => display.fmt(f),
+        }
+    }
+}
+
+/// A table of every string in every language, generated by
+/// [`include_strings!`](crate::include_strings). The table keeps track of the current
+/// language, so changing it with [`set_language`](StringTable::set_language) affects all
+/// strings fetched from it afterwards.
+pub struct StringTable {
+    languages: &'static [Language],
+    strings: &'static [&'static str],
+    num_keys: usize,
+    current_language: AtomicU8,
+}
+
+impl StringTable {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        languages: &'static [Language],
+        strings: &'static [&'static str],
+        num_keys: usize,
+    ) -> Self {
+        assert!(
+            languages.len() * num_keys == strings.len(),
+            "Every key must have a translation for every language"
+        );
+
+        Self {
+            languages,
+            strings,
+            num_keys,
+            current_language: AtomicU8::new(0),
+        }
+    }
+
+    /// The languages available in this table, in the order they appear in the translation file.
+    #[must_use]
+    pub fn languages(&self) -> &'static [Language] {
+        self.languages
+    }
+
+    /// Changes the language that strings are fetched in.
+    pub fn set_language(&self, language: LanguageId) {
+        assert!(
+            (language.0 as usize) < self.languages.len(),
+            "Language does not belong to this table"
+        );
+
+        self.current_language.store(language.0, Ordering::SeqCst);
+    }
+
+    /// The language strings are currently fetched in. Defaults to the first language in
+    /// the translation file.
+    #[must_use]
+    pub fn language(&self) -> LanguageId {
+        LanguageId(self.current_language.load(Ordering::SeqCst))
+    }
+
+    /// The raw translation of `id` in the current language, including any placeholders.
+    #[must_use]
+    pub fn get(&self, id: StringId) -> &'static str {
+        self.get_in(id, self.language())
+    }
+
+    /// The raw translation of `id` in the given language, including any placeholders.
+    #[must_use]
+    pub fn get_in(&self, id: StringId, language: LanguageId) -> &'static str {
+        self.strings[language.0 as usize * self.num_keys + id.0 as usize]
+    }
+
+    /// Returns the translation of `id` in the current language with all the placeholders
+    /// filled in with `args`. The result can be written anywhere which accepts a [`Display`],
+    /// for example a text renderer via [`write!`].
+    ///
+    /// ```rust,ignore
+    /// write!(writer, "{}", strings::TABLE.format(strings::APPLES, &[apple_count.into()]))?;
+    /// ```
+    #[must_use]
+    pub fn format<'a>(&self, id: StringId, args: &'a [Argument<'a>]) -> LocalisedString<'a> {
+        let language = self.language();
+
+        LocalisedString {
+            format: self.get_in(id, language),
+            plural_rule: self.languages[language.0 as usize].plural_rule,
+            args,
+        }
+    }
+
+    /// Writes the translation of `id` in the current language to `output`, filling in all
+    /// the placeholders with `args`.
+    pub fn write_to(
+        &self,
+        output: &mut impl Write,
+        id: StringId,
+        args: &[Argument<'_>],
+    ) -> fmt::Result {
+        write!(output, "{}", self.format(id, args))
+    }
+}
+
+/// A translated string with its placeholders filled in. Created by [`StringTable::format`].
+pub struct LocalisedString<'a> {
+    format: &'static str,
+    plural_rule: PluralRule,
+    args: &'a [Argument<'a>],
+}
+
+impl LocalisedString<'_> {
+    fn argument(&self, index: &str) -> Result<&Argument<'_>, fmt::Error> {
+        index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.args.get(index))
+            .ok_or(fmt::Error)
+    }
+}
+
+impl Display for LocalisedString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.format;
+
+        while let Some(position) = rest.find(['{', '}']) {
+            f.write_str(&rest[..position])?;
+
+            let brace = &rest[position..=position];
+            rest = &rest[position + 1..];
+
+            // the format was validated at compile time, so unmatched closing braces
+            // can only be escaped ones
+            if brace == "}" || rest.starts_with('{') {
+                f.write_str(brace)?;
+                rest = &rest[1..];
+                continue;
+            }
+
+            let end = rest.find('}').ok_or(fmt::Error)?;
+            let placeholder = &rest[..end];
+            rest = &rest[end + 1..];
+
+            match placeholder.split_once(':') {
+                None => self.argument(placeholder)?.fmt(f)?,
+                Some((index, forms)) => {
+                    let argument = self.argument(index)?;
+                    let form_count = forms.split('|').count();
+                    let form = match argument {
+                        Argument::Number(n) => self.plural_rule.form(*n),
+                        _ => form_count - 1,
+                    };
+
+                    let form = forms.split('|').nth(form.min(form_count - 1)).unwrap();
+
+                    for (i, part) in form.split('#').enumerate() {
+                        if i != 0 {
+                            argument.fmt(f)?;
+                        }
+
+                        f.write_str(part)?;
+                    }
+                }
+            }
+        }
+
+        f.write_str(rest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Gba;
+    use alloc::string::String;
+
+    static LANGUAGES: &[Language] = &[
+        Language::new("en", PluralRule::OneOther),
+        Language::new("fr", PluralRule::OneIncludesZero),
+    ];
+
+    static TABLE: StringTable = StringTable::new(
+        LANGUAGES,
+        &[
+            "Hello, {0}!",
+            "{1}: {0:# apple|# apples} {{{0}}}",
+            "Bonjour, {0} !",
+            "{1} : {0:# pomme|# pommes} {{{0}}}",
+        ],
+        2,
+    );
+
+    const GREETING: StringId = StringId::new(0);
+    const APPLES: StringId = StringId::new(1);
+
+    #[test_case]
+    fn formats_arguments_and_plurals(_gba: &mut Gba) {
+        let mut output = String::new();
+
+        TABLE
+            .write_to(&mut output, GREETING, &["Ferris".into()])
+            .unwrap();
+        assert_eq!(output, "Hello, Ferris!");
+
+        output.clear();
+        TABLE
+            .write_to(&mut output, APPLES, &[0.into(), "Basket".into()])
+            .unwrap();
+        assert_eq!(output, "Basket: 0 apples {0}");
+
+        TABLE.set_language(LanguageId::new(1));
+
+        output.clear();
+        TABLE
+            .write_to(&mut output, APPLES, &[0.into(), "Panier".into()])
+            .unwrap();
+        assert_eq!(output, "Panier : 0 pomme {0}");
+
+        TABLE.set_language(LanguageId::new(0));
+    }
+}
@@ -0,0 +1,403 @@
+//! A logger for the [`log`] crate which writes to the emulator's debug output.
+//!
+//! Call [`init`] once at the start of your game, and then use the macros from
+//! the `log` crate, such as `log::info!`, anywhere in your game. The level of
+//! each message is passed on to the [`debug_output`], and messages are tagged
+//! with the module they came from.
+//!
+//! When there is nowhere to write messages, such as on real hardware, the most
+//! recent messages are kept in a small buffer in RAM instead. These are shown
+//! on the crash screen if the game panics, and can be copied out with
+//! [`copy_recent_logs`] so you can write them to save memory.
+//!
+//! Messages can be filtered at runtime for the whole game with [`set_level`] or
+//! for a single module and its children with [`set_module_level`]. Messages can
+//! also be removed completely at compile time by turning on one of the
+//! `max_level_*` or `release_max_level_*` features of the `log` crate in your
+//! game's `Cargo.toml`. These apply to the whole game, since the macros from the
+//! `log` crate only check that level at compile time, so filtering by module
+//! only happens at runtime.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::{log::LevelFilter, logger};
+//!
+//! # fn foo() {
+//! logger::init().expect("no other logger should be set");
+//! logger::set_level(LevelFilter::Info);
+//! logger::set_module_level("my_game::physics", LevelFilter::Trace);
+//!
+//! agb::log::info!("The game has started");
+//! # }
+//! ```
+
+use core::{
+    cell::RefCell,
+    fmt::{self, Write},
+};
+
+use alloc::vec::Vec;
+use critical_section::{CriticalSection, Mutex};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::{debug_output, mgba::DebugLevel};
+
+/// The number of bytes of messages kept when there is no debug output.
+pub const RECENT_LOGS_SIZE: usize = 1024;
+
+/// The longest message kept when there is no debug output, including the
+/// newline at the end. Longer messages are cut off.
+const MAX_MESSAGE_SIZE: usize = 128;
+
+struct Filters {
+    default: LevelFilter,
+    modules: Vec<(&'static str, LevelFilter)>,
+}
+
+impl Filters {
+    const fn new() -> Self {
+        Self {
+            default: LevelFilter::Info,
+            modules: Vec::new(),
+        }
+    }
+
+    /// The level for `target`, which comes from the most specific module filter
+    /// that applies to it.
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| is_in_module(target, module))
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |&(_, level)| level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|&(_, level)| level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+fn is_in_module(target: &str, module: &str) -> bool {
+    target
+        .strip_prefix(module)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+struct RecentLogs {
+    buffer: [u8; RECENT_LOGS_SIZE],
+    start: usize,
+    len: usize,
+}
+
+impl RecentLogs {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; RECENT_LOGS_SIZE],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        let end = (self.start + self.len) % RECENT_LOGS_SIZE;
+        self.buffer[end] = byte;
+
+        if self.len == RECENT_LOGS_SIZE {
+            self.start = (self.start + 1) % RECENT_LOGS_SIZE;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.push(byte);
+        }
+    }
+
+    fn bytes(&self) -> RecentLogBytes<'_> {
+        RecentLogBytes {
+            logs: self,
+            index: 0,
+        }
+    }
+
+    fn copy_to(&self, output: &mut [u8]) -> usize {
+        let length = self.len.min(output.len());
+        // keep the newest messages if they don't all fit
+        let skip = self.len - length;
+
+        for (byte, log_byte) in output[..length].iter_mut().zip(self.bytes().skip(skip)) {
+            *byte = log_byte;
+        }
+
+        length
+    }
+}
+
+/// The bytes of the messages in the recent logs, oldest first.
+#[derive(Clone)]
+pub(crate) struct RecentLogBytes<'a> {
+    logs: &'a RecentLogs,
+    index: usize,
+}
+
+impl Iterator for RecentLogBytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.index == self.logs.len {
+            return None;
+        }
+
+        let byte = self.logs.buffer[(self.logs.start + self.index) % RECENT_LOGS_SIZE];
+        self.index += 1;
+        Some(byte)
+    }
+}
+
+/// A message formatted on the stack, so that formatting it doesn't happen
+/// while the recent logs are locked.
+struct Message {
+    bytes: [u8; MAX_MESSAGE_SIZE],
+    len: usize,
+}
+
+impl Message {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; MAX_MESSAGE_SIZE],
+            len: 0,
+        }
+    }
+
+    fn with_newline(&mut self) -> &[u8] {
+        self.bytes[self.len] = b'\n';
+        &self.bytes[..=self.len]
+    }
+}
+
+impl Write for Message {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // leave space for the newline, and don't cut a character in half
+        let mut length = s.len().min(MAX_MESSAGE_SIZE - 1 - self.len);
+        while !s.is_char_boundary(length) {
+            length -= 1;
+        }
+
+        self.bytes[self.len..self.len + length].copy_from_slice(&s.as_bytes()[..length]);
+        self.len += length;
+
+        Ok(())
+    }
+}
+
+static FILTERS: Mutex<RefCell<Filters>> = Mutex::new(RefCell::new(Filters::new()));
+static RECENT_LOGS: Mutex<RefCell<RecentLogs>> = Mutex::new(RefCell::new(RecentLogs::new()));
+
+struct Logger;
+
+static LOGGER: Logger = Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        critical_section::with(|cs| {
+            metadata.level() <= FILTERS.borrow_ref(cs).level(metadata.target())
+        })
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let output = debug_output::output();
+
+        if output.is_enabled() {
+            let level = match record.level() {
+                Level::Error => DebugLevel::Error,
+                Level::Warn => DebugLevel::Warning,
+                Level::Info => DebugLevel::Info,
+                Level::Debug | Level::Trace => DebugLevel::Debug,
+            };
+
+            let _ = output.print(
+                format_args!("{}: {}", record.target(), record.args()),
+                level,
+            );
+        } else {
+            let mut message = Message::new();
+            let _ = write!(
+                message,
+                "{} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+
+            critical_section::with(|cs| {
+                RECENT_LOGS
+                    .borrow_ref_mut(cs)
+                    .extend(message.with_newline());
+            });
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn update_max_level(cs: CriticalSection) {
+    let max_level = FILTERS.borrow_ref(cs).max_level();
+    // SAFETY: this is inside a critical section, so nothing else can be setting it
+    unsafe { log::set_max_level_racy(max_level) };
+}
+
+/// Sets agb's logger as the logger for the `log` crate. This fails if a logger
+/// has already been set.
+pub fn init() -> Result<(), SetLoggerError> {
+    critical_section::with(|cs| {
+        // SAFETY: this is inside a critical section, so nothing else can be setting it
+        unsafe { log::set_logger_racy(&LOGGER)? };
+        update_max_level(cs);
+
+        Ok(())
+    })
+}
+
+/// Sets the level of messages to show from modules which don't have their own
+/// level set with [`set_module_level`]. This starts as [`LevelFilter::Info`].
+pub fn set_level(level: LevelFilter) {
+    critical_section::with(|cs| {
+        FILTERS.borrow_ref_mut(cs).default = level;
+        update_max_level(cs);
+    });
+}
+
+/// Sets the level of messages to show from `module` and any modules inside it,
+/// such as `"my_game::physics"`. The most specific module's level is used.
+pub fn set_module_level(module: &'static str, level: LevelFilter) {
+    critical_section::with(|cs| {
+        let mut filters = FILTERS.borrow_ref_mut(cs);
+
+        match filters.modules.iter_mut().find(|(name, _)| *name == module) {
+            Some((_, existing)) => *existing = level,
+            None => filters.modules.push((module, level)),
+        }
+
+        drop(filters);
+        update_max_level(cs);
+    });
+}
+
+/// Copies the messages that were logged while there was no debug output into
+/// `output`, oldest first, and returns the number of bytes copied. If they
+/// don't all fit, the newest ones are copied. Each message ends with a newline.
+///
+/// Once the buffer is full the oldest messages get overwritten, so the first
+/// message may be cut off.
+pub fn copy_recent_logs(output: &mut [u8]) -> usize {
+    critical_section::with(|cs| {
+        // this can be called from the crash screen, which could have been
+        // caused by a panic while logging
+        RECENT_LOGS
+            .borrow(cs)
+            .try_borrow()
+            .map_or(0, |logs| logs.copy_to(output))
+    })
+}
+
+/// Calls `f` with the messages that were logged while there was no debug
+/// output, oldest first, without copying them out of the buffer. Returns `None`
+/// if they can't be read because this was called while logging.
+pub(crate) fn with_recent_logs<T>(f: impl FnOnce(RecentLogBytes) -> T) -> Option<T> {
+    critical_section::with(|cs| {
+        RECENT_LOGS
+            .borrow(cs)
+            .try_borrow()
+            .ok()
+            .map(|logs| f(logs.bytes()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn module_filters(_gba: &mut crate::Gba) {
+        let mut filters = Filters::new();
+        filters.modules.push(("game", LevelFilter::Warn));
+        filters.modules.push(("game::physics", LevelFilter::Trace));
+
+        assert_eq!(filters.level("agb::display"), LevelFilter::Info);
+        assert_eq!(filters.level("game"), LevelFilter::Warn);
+        assert_eq!(filters.level("game::sound"), LevelFilter::Warn);
+        assert_eq!(
+            filters.level("game::physics::collision"),
+            LevelFilter::Trace
+        );
+        assert_eq!(filters.level("game_two"), LevelFilter::Info);
+        assert_eq!(filters.max_level(), LevelFilter::Trace);
+    }
+
+    #[test_case]
+    fn recent_logs_keep_the_newest_bytes(_gba: &mut crate::Gba) {
+        let mut logs = RecentLogs::new();
+        for i in 0..RECENT_LOGS_SIZE + 10 {
+            logs.push(i as u8);
+        }
+
+        let mut output = [0; 4];
+        assert_eq!(logs.copy_to(&mut output), 4);
+        let last = (RECENT_LOGS_SIZE + 10) as u8;
+        assert_eq!(
+            output,
+            [
+                last.wrapping_sub(4),
+                last.wrapping_sub(3),
+                last.wrapping_sub(2),
+                last.wrapping_sub(1)
+            ]
+        );
+    }
+
+    #[test_case]
+    fn long_messages_are_cut_off_at_a_character(_gba: &mut crate::Gba) {
+        let mut message = Message::new();
+        for _ in 0..MAX_MESSAGE_SIZE {
+            let _ = write!(message, "é");
+        }
+
+        let bytes = message.with_newline();
+        assert_eq!(bytes.len(), MAX_MESSAGE_SIZE - 1);
+        assert_eq!(bytes.last(), Some(&b'\n'));
+        assert!(core::str::from_utf8(bytes).is_ok());
+    }
+
+    #[test_case]
+    fn messages_can_log_while_being_formatted(_gba: &mut crate::Gba) {
+        struct LogsWhenDisplayed;
+
+        impl core::fmt::Display for LogsWhenDisplayed {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                LOGGER.log(&Record::builder().args(format_args!("inner")).build());
+                f.write_str("outer")
+            }
+        }
+
+        debug_output::set_output(&debug_output::NoOutput);
+        LOGGER.log(
+            &Record::builder()
+                .args(format_args!("{}", LogsWhenDisplayed))
+                .build(),
+        );
+        debug_output::set_output(debug_output::detect());
+
+        let logs = with_recent_logs(|bytes| bytes.collect::<Vec<_>>()).unwrap();
+        assert!(logs.ends_with(b"INFO : inner\nINFO : outer\n"));
+    }
+}
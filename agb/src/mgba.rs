@@ -84,9 +84,7 @@ impl core::fmt::Write for MgbaWriter {
 macro_rules! println {
     ($( $x:expr ),*) => {
         {
-            if let Some(mut mgba) = $crate::mgba::Mgba::new() {
-                let _ = mgba.print(format_args!($($x,)*), $crate::mgba::DebugLevel::Info);
-            }
+            let _ = $crate::debug_output::print(format_args!($($x,)*), $crate::mgba::DebugLevel::Info);
         }
     };
 }
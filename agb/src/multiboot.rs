@@ -0,0 +1,401 @@
+//! Sending a game to other consoles over the link cable.
+//!
+//! A console with a cartridge can boot up to three other consoles without
+//! cartridges by sending them a multiboot image, which is a game built with the
+//! `multiboot` feature. This is how single pak multiplayer works. The other
+//! consoles need to be turned on without a cartridge in, and the console
+//! sending the game needs to be plugged into the purple end of the cable.
+//!
+//! The handshake with the other consoles happens a step at a time in
+//! [`MultibootHost::update`], which you should call once per frame so that you
+//! can show progress while it runs. The image itself is sent by the BIOS, which
+//! stops everything else until it has finished.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::multiboot::{MultibootHost, MultibootStatus};
+//!
+//! # fn foo(image: &'static [u8]) {
+//! let vblank = agb::interrupt::VBlank::get();
+//! let mut host = MultibootHost::new(image).expect("invalid multiboot image");
+//!
+//! loop {
+//!     vblank.wait_for_vblank();
+//!
+//!     match host.update() {
+//!         Ok(MultibootStatus::WaitingForClients { clients }) => {
+//!             // let the player start once someone has connected
+//!             if clients != 0 {
+//!                 host.start();
+//!             }
+//!         }
+//!         Ok(MultibootStatus::Complete) => break,
+//!         Ok(_) => {}
+//!         Err(error) => {
+//!             agb::println!("failed to send the game: {:?}", error);
+//!             break;
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::{memory_mapped::MemoryMapped, syscall};
+
+const SIO_MULTI: [MemoryMapped<u16>; 3] = unsafe {
+    [
+        MemoryMapped::new(0x0400_0122),
+        MemoryMapped::new(0x0400_0124),
+        MemoryMapped::new(0x0400_0126),
+    ]
+};
+const SIO_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0128) };
+const SIO_SEND: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_012A) };
+const SIO_MODE: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0134) };
+
+/// Multiplayer mode at 115200 bits per second.
+const MULTIPLAYER_MODE: u16 = 0x2003;
+const CHILD_BIT: u16 = 1 << 2;
+const READY_BIT: u16 = 1 << 3;
+const ERROR_BIT: u16 = 1 << 6;
+const START_BIT: u16 = 1 << 7;
+
+const HEADER_SIZE: usize = 0xc0;
+const MIN_IMAGE_SIZE: usize = HEADER_SIZE + 0x100;
+const MAX_IMAGE_SIZE: usize = 0x4_0000;
+/// The colour and speed of the logo animation the clients show while loading.
+const PALETTE_DATA: u8 = 0xd1;
+/// How many frames to wait for the clients to send their keys.
+const KEY_EXCHANGE_ATTEMPTS: u32 = 60;
+/// How many frames to wait after the handshake before sending the image.
+const FRAMES_BEFORE_TRANSFER: u32 = 4;
+
+/// Why sending a multiboot image failed.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultibootError {
+    /// The image must be between 448 bytes and 256KiB, a multiple of 16 bytes
+    /// long and start on a 4 byte boundary.
+    InvalidImage,
+    /// This console isn't plugged into the end of the cable that sends.
+    NotParent,
+    /// [`start`](MultibootHost::start) was called before any consoles were
+    /// found.
+    NoClients,
+    /// A console stopped responding or replied with something unexpected,
+    /// such as if the cable was unplugged.
+    ClientNotResponding,
+    /// The BIOS reported that sending the image failed.
+    TransferFailed,
+}
+
+/// How far through sending the image a [`MultibootHost`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultibootStatus {
+    /// Looking for consoles to send the image to. Bits 1 to 3 of `clients` are
+    /// set for each of players 2 to 4 that have been found. Call
+    /// [`start`](MultibootHost::start) to send the image to them.
+    WaitingForClients {
+        /// Which players have been found.
+        clients: u8,
+    },
+    /// Doing the handshake with the clients.
+    Connecting,
+    /// The next call to [`update`](MultibootHost::update) sends the image,
+    /// which blocks until it is done. The time this takes depends on the size
+    /// of the image, and can be several seconds.
+    Transferring,
+    /// The image has been sent, and the clients are starting it.
+    Complete,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Detecting,
+    Confirming,
+    SendingHeader,
+    ExchangingKeys { attempts: u32 },
+    Waiting { frames: u32 },
+    Transferring,
+    Complete,
+    Failed(MultibootError),
+}
+
+/// The parameters for the BIOS multiboot call.
+#[repr(C)]
+pub(crate) struct MultibootParameters {
+    reserved1: [u32; 5],
+    handshake_data: u8,
+    reserved2: [u8; 3],
+    client_data: [u8; 3],
+    palette_data: u8,
+    reserved3: u8,
+    client_bit: u8,
+    reserved4: [u8; 2],
+    boot_start: *const u8,
+    boot_end: *const u8,
+    reserved5: [u32; 9],
+}
+
+/// Sends a multiboot image to the other consoles plugged into the link cable.
+/// See the [module level documentation](self) for how to use it.
+pub struct MultibootHost {
+    image: &'static [u8],
+    state: State,
+    clients: u8,
+    client_data: [u8; 3],
+}
+
+impl MultibootHost {
+    /// Prepares to send `image`, which is usually a multiboot build of a game
+    /// included with [`include_bytes`]. The image must start on a 4 byte
+    /// boundary, which you can make sure of by putting it in a
+    /// `#[repr(align(4))]` struct.
+    pub fn new(image: &'static [u8]) -> Result<Self, MultibootError> {
+        if image.len() < MIN_IMAGE_SIZE
+            || image.len() > MAX_IMAGE_SIZE
+            || image.len() & 0xf != 0
+            || image.as_ptr() as usize & 3 != 0
+        {
+            return Err(MultibootError::InvalidImage);
+        }
+
+        SIO_MODE.set(0);
+        SIO_CONTROL.set(MULTIPLAYER_MODE);
+
+        Ok(Self {
+            image,
+            state: State::Detecting,
+            clients: 0,
+            client_data: [0xff; 3],
+        })
+    }
+
+    /// Which of players 2 to 4 have been found, in bits 1 to 3.
+    #[must_use]
+    pub fn clients(&self) -> u8 {
+        self.clients
+    }
+
+    /// Stops looking for clients and starts sending the image to the ones
+    /// already found. This does nothing once the image has started sending.
+    pub fn start(&mut self) {
+        if self.state == State::Detecting {
+            self.state = if self.clients == 0 {
+                State::Failed(MultibootError::NoClients)
+            } else {
+                State::Confirming
+            };
+        }
+    }
+
+    /// Does the next step of sending the image. Call this once a frame until it
+    /// returns [`MultibootStatus::Complete`] or an error.
+    pub fn update(&mut self) -> Result<MultibootStatus, MultibootError> {
+        if let Err(error) = self.step() {
+            self.state = State::Failed(error);
+        }
+
+        Ok(match self.state {
+            State::Detecting => MultibootStatus::WaitingForClients {
+                clients: self.clients,
+            },
+            State::Confirming
+            | State::SendingHeader
+            | State::ExchangingKeys { .. }
+            | State::Waiting { .. } => MultibootStatus::Connecting,
+            State::Transferring => MultibootStatus::Transferring,
+            State::Complete => MultibootStatus::Complete,
+            State::Failed(error) => return Err(error),
+        })
+    }
+
+    fn step(&mut self) -> Result<(), MultibootError> {
+        match self.state {
+            State::Detecting => {
+                let replies = transfer(0x6200)?;
+
+                self.clients = 0;
+                for (i, &reply) in replies.iter().enumerate() {
+                    let bit = 1 << (i + 1);
+                    if reply & 0xfff0 == 0x7200 && reply & bit != 0 {
+                        self.clients |= bit as u8;
+                    }
+                }
+            }
+            State::Confirming => {
+                self.expect(0x6100 | u16::from(self.clients), |bit| 0x7200 | bit)?;
+                self.state = State::SendingHeader;
+            }
+            State::SendingHeader => {
+                for (i, bytes) in self.image[..HEADER_SIZE].chunks(2).enumerate() {
+                    let remaining = (HEADER_SIZE / 2 - i) as u16;
+                    let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+
+                    self.expect(value, |bit| (remaining << 8) | bit)?;
+                }
+
+                self.expect(0x6200, |bit| bit)?;
+                self.expect(0x6200 | u16::from(self.clients), |bit| 0x7200 | bit)?;
+
+                self.state = State::ExchangingKeys { attempts: 0 };
+            }
+            State::ExchangingKeys { attempts } => {
+                let replies = transfer(0x6300 | u16::from(PALETTE_DATA))?;
+
+                let mut all_replied = true;
+                for (i, reply) in replies.into_iter().enumerate() {
+                    if self.clients & (1 << (i + 1)) == 0 {
+                        continue;
+                    }
+
+                    if reply >> 8 == 0x73 {
+                        self.client_data[i] = reply as u8;
+                    } else {
+                        all_replied = false;
+                    }
+                }
+
+                if all_replied {
+                    transfer(0x6400 | u16::from(self.handshake_data()))?;
+                    self.state = State::Waiting { frames: 0 };
+                } else if attempts >= KEY_EXCHANGE_ATTEMPTS {
+                    return Err(MultibootError::ClientNotResponding);
+                } else {
+                    self.state = State::ExchangingKeys {
+                        attempts: attempts + 1,
+                    };
+                }
+            }
+            State::Waiting { frames } => {
+                self.state = if frames >= FRAMES_BEFORE_TRANSFER {
+                    State::Transferring
+                } else {
+                    State::Waiting { frames: frames + 1 }
+                };
+            }
+            State::Transferring => {
+                self.send_image()?;
+                self.state = State::Complete;
+            }
+            State::Complete | State::Failed(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// The clients that haven't been found send 0xff as their key.
+    fn handshake_data(&self) -> u8 {
+        self.client_data
+            .iter()
+            .fold(0x11u8, |sum, &data| sum.wrapping_add(data))
+    }
+
+    /// Sends `value` and checks each client replies with `reply(client_bit)`.
+    fn expect(&self, value: u16, reply: impl Fn(u16) -> u16) -> Result<(), MultibootError> {
+        let replies = transfer(value)?;
+
+        for (i, &received) in replies.iter().enumerate() {
+            let bit = 1 << (i + 1);
+            if u16::from(self.clients) & bit != 0 && received != reply(bit) {
+                return Err(MultibootError::ClientNotResponding);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_image(&self) -> Result<(), MultibootError> {
+        let parameters = MultibootParameters {
+            reserved1: [0; 5],
+            handshake_data: self.handshake_data(),
+            reserved2: [0; 3],
+            client_data: self.client_data,
+            palette_data: PALETTE_DATA,
+            reserved3: 0,
+            client_bit: self.clients,
+            reserved4: [0; 2],
+            boot_start: self.image[HEADER_SIZE..].as_ptr(),
+            boot_end: self.image.as_ptr_range().end,
+            reserved5: [0; 9],
+        };
+
+        let succeeded = critical_section::with(|_| unsafe { syscall::multi_boot(&parameters) });
+
+        if succeeded {
+            Ok(())
+        } else {
+            Err(MultibootError::TransferFailed)
+        }
+    }
+}
+
+/// Sends `value` to every client and returns what players 2 to 4 sent back.
+/// Clients that aren't connected reply with `0xffff`.
+fn transfer(value: u16) -> Result<[u16; 3], MultibootError> {
+    let control = SIO_CONTROL.get();
+    if control & CHILD_BIT != 0 {
+        return Err(MultibootError::NotParent);
+    }
+
+    // give the clients a moment to get ready for the next transfer
+    for _ in 0..64 {
+        if SIO_CONTROL.get() & READY_BIT != 0 {
+            break;
+        }
+    }
+
+    SIO_SEND.set(value);
+    SIO_CONTROL.set(control | START_BIT);
+
+    let mut finished = false;
+    for _ in 0..10_000 {
+        if SIO_CONTROL.get() & START_BIT == 0 {
+            finished = true;
+            break;
+        }
+    }
+
+    if !finished || SIO_CONTROL.get() & ERROR_BIT != 0 {
+        return Err(MultibootError::ClientNotResponding);
+    }
+
+    Ok(SIO_MULTI.map(|register| register.get()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(align(4))]
+    struct Aligned<T>(T);
+
+    static IMAGE: Aligned<[u8; 0x200]> = Aligned([0; 0x200]);
+
+    #[test_case]
+    fn images_are_checked(_gba: &mut crate::Gba) {
+        assert_eq!(
+            MultibootHost::new(&IMAGE.0[..0xf0]).err(),
+            Some(MultibootError::InvalidImage)
+        );
+        assert_eq!(
+            MultibootHost::new(&IMAGE.0[..0x1c8]).err(),
+            Some(MultibootError::InvalidImage)
+        );
+        assert_eq!(
+            MultibootHost::new(&IMAGE.0[2..0x1e2]).err(),
+            Some(MultibootError::InvalidImage)
+        );
+        assert!(MultibootHost::new(&IMAGE.0).is_ok());
+    }
+
+    #[test_case]
+    fn handshake_data(_gba: &mut crate::Gba) {
+        let mut host = MultibootHost::new(&IMAGE.0).unwrap();
+        assert_eq!(host.handshake_data(), 0x0e);
+
+        host.client_data = [0x10, 0x20, 0xff];
+        assert_eq!(host.handshake_data(), 0x40);
+    }
+}
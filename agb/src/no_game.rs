@@ -7,12 +7,12 @@ use alloc::{boxed::Box, vec};
 use crate::display::object::{DynamicSprite, PaletteVram, Size, SpriteVram};
 use crate::display::palette16::Palette16;
 use crate::{
-    display::{object::ObjectUnmanaged, HEIGHT, WIDTH},
+    display::{object::ObjectUnmanaged, Rgb15, HEIGHT, WIDTH},
     include_palette,
     interrupt::VBlank,
 };
 
-static PALETTE: &[u16] = &include_palette!("gfx/pastel.png");
+static PALETTE: &[Rgb15] = &include_palette!("gfx/pastel.png");
 
 fn letters() -> Vec<Vec<Vector2D<Num<i32, 8>>>> {
     vec![
@@ -116,9 +116,9 @@ fn generate_sprites() -> Box<[SpriteVram]> {
     let palettes: Vec<PaletteVram> = PALETTE
         .chunks(15)
         .map(|x| {
-            core::iter::once(0)
+            core::iter::once(Rgb15::BLACK)
                 .chain(x.iter().copied())
-                .chain(core::iter::repeat(0))
+                .chain(core::iter::repeat(Rgb15::BLACK))
                 .take(16)
                 .collect::<Vec<_>>()
         })
@@ -4,10 +4,10 @@ use alloc::{collections::TryReserveError, format, vec::Vec};
 use qrcodegen_no_heap::DataTooLong;
 
 use crate::{
-    backtrace,
-    display::{bitmap3::Bitmap3, busy_wait_for_vblank, HEIGHT, WIDTH},
+    backtrace, debug_output,
+    display::{bitmap3::Bitmap3, busy_wait_for_vblank, Rgb15, HEIGHT, WIDTH},
     dma::dma3_exclusive,
-    mgba, syscall, ExternalAllocator,
+    logger, mgba, syscall, ExternalAllocator,
 };
 
 mod text;
@@ -27,7 +27,7 @@ pub fn render_backtrace(trace: &backtrace::Frames, info: &PanicInfo) -> ! {
 
             gba.dma.dma().dma3.disable();
             let mut gfx = gba.display.video.bitmap3();
-            gfx.clear(0xFFFF);
+            gfx.clear(Rgb15::WHITE);
 
             let qrcode_string_data = if WEBSITE.is_empty() {
                 format!("{trace}")
@@ -39,7 +39,7 @@ pub fn render_backtrace(trace: &backtrace::Frames, info: &PanicInfo) -> ! {
             let location = draw_qr_code(&mut gfx, &qrcode_string_data);
 
             let mut trace_text_render =
-                text::BitmapTextRender::new(&mut gfx, (location, 8).into(), 0x0000);
+                text::BitmapTextRender::new(&mut gfx, (location, 8).into(), Rgb15::BLACK);
             let _ = writeln!(
                 &mut trace_text_render,
                 "The game crashed :({}{WEBSITE}\n{trace}",
@@ -51,17 +51,16 @@ pub fn render_backtrace(trace: &backtrace::Frames, info: &PanicInfo) -> ! {
             let mut panic_text_render = text::BitmapTextRender::new(
                 &mut gfx,
                 (8, location.max(trace_location + PADDING)).into(),
-                0x0000,
+                Rgb15::BLACK,
             );
             let _ = write!(&mut panic_text_render, "{info}");
+            write_recent_logs(&mut panic_text_render);
 
             // need to wait 2 frames to ensure that mgba finishes rendering before the fatal call below
             busy_wait_for_vblank();
             busy_wait_for_vblank();
 
-            if let Some(mut mgba) = mgba::Mgba::new() {
-                let _ = mgba.print(format_args!("Error: {info}"), mgba::DebugLevel::Fatal);
-            }
+            let _ = debug_output::print(format_args!("Error: {info}"), mgba::DebugLevel::Fatal);
 
             loop {
                 syscall::halt();
@@ -71,6 +70,55 @@ pub fn render_backtrace(trace: &backtrace::Frames, info: &PanicInfo) -> ! {
 }
 const PADDING: i32 = 8;
 
+/// Writes the last few messages that were logged on hardware, if there are any
+fn write_recent_logs(output: &mut impl Write) {
+    const LINES: usize = 4;
+
+    logger::with_recent_logs(|bytes| {
+        // the oldest message may have been cut off part way through a character
+        let mut bytes = bytes.skip_while(|&byte| byte & 0b1100_0000 == 0b1000_0000);
+        if bytes.clone().next().is_none() {
+            return;
+        }
+
+        // every message ends with a newline
+        let lines = bytes.clone().filter(|&byte| byte == b'\n').count();
+        for _ in LINES..lines {
+            bytes.find(|&byte| byte == b'\n');
+        }
+
+        let _ = write!(output, "\n\nRecent logs:");
+
+        let mut character = [0; 4];
+        let mut character_len = 0;
+        let mut at_line_start = true;
+
+        for byte in bytes {
+            if at_line_start {
+                let _ = writeln!(output);
+                at_line_start = false;
+            }
+
+            if byte == b'\n' {
+                at_line_start = true;
+                continue;
+            }
+
+            character[character_len] = byte;
+            character_len += 1;
+
+            match core::str::from_utf8(&character[..character_len]) {
+                Ok(character) => {
+                    let _ = output.write_str(character);
+                    character_len = 0;
+                }
+                Err(error) if error.error_len().is_some() => character_len = 0,
+                Err(_) => {}
+            }
+        }
+    });
+}
+
 struct QrCodeBuffers {
     temp_buffer: Vec<u8, ExternalAllocator>,
     out_buffer: Vec<u8, ExternalAllocator>,
@@ -128,9 +176,9 @@ fn draw_qr_code(gfx: &mut Bitmap3<'_>, qrcode_string_data: &str) -> i32 {
     for y in 0..HEIGHT {
         for x in 0..WIDTH {
             let colour = if qr_code.get_module(x / 2 - 4, y / 2 - 4) {
-                0x0000
+                Rgb15::BLACK
             } else {
-                0xFFFF
+                Rgb15::WHITE
             };
             gfx.draw_point(x, y, colour);
         }
@@ -1,7 +1,7 @@
 use core::fmt::Write;
 
 use crate::{
-    display::{bitmap3::Bitmap3, Font, HEIGHT, WIDTH},
+    display::{bitmap3::Bitmap3, Font, Rgb15, HEIGHT, WIDTH},
     fixnum::Vector2D,
 };
 
@@ -11,7 +11,7 @@ pub struct BitmapTextRender<'bitmap, 'gba> {
     head_position: Vector2D<i32>,
     start_x: i32,
     bitmap: &'bitmap mut Bitmap3<'gba>,
-    colour: u16,
+    colour: Rgb15,
     previous_char: Option<char>,
 }
 
@@ -19,7 +19,7 @@ impl<'bitmap, 'gba> BitmapTextRender<'bitmap, 'gba> {
     pub fn new(
         bitmap: &'bitmap mut Bitmap3<'gba>,
         position: Vector2D<i32>,
-        start_colour: u16,
+        start_colour: Rgb15,
     ) -> Self {
         Self {
             head_position: position,
@@ -0,0 +1,253 @@
+//! A sampling profiler for finding where your game spends its time.
+//!
+//! The profiler uses a timer to interrupt your game at regular intervals, and
+//! records what was running each time along with a few of the functions that
+//! called it. Once you've stopped it, print the [`Profile`] to the mgba log and
+//! give the log to `agb-debug profile` along with your game's elf file to get
+//! a flamegraph and a table of the functions which take the most time.
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! use agb::profiler::Profiler;
+//!
+//! # fn foo(gba: &mut agb::Gba) {
+//! let mut timers = gba.timers.timers();
+//! // sample every 10000 cycles, keeping up to 2000 samples of 8 callers each
+//! let profiler = Profiler::start(&mut timers.timer2, 10_000, 2000, 8);
+//!
+//! // run the code you want to profile
+//!
+//! profiler.stop().print();
+//! # }
+//! ```
+//!
+//! Callers can only be found for code compiled with frame pointers, which agb
+//! turns on in the template. The profiler is only available with the
+//! `backtrace` feature.
+
+use core::cell::RefCell;
+
+use alloc::vec::Vec;
+use critical_section::Mutex;
+
+use crate::{
+    backtrace::{self, Frames},
+    hash_map::HashMap,
+    interrupt::{add_interrupt_handler, InterruptHandler},
+    timer::{Divider, Timer},
+};
+
+struct Samples {
+    /// The number of addresses stored for each sample, the first of which is
+    /// the program counter and the rest are the callers.
+    stride: usize,
+    addresses: Vec<u32>,
+    count: usize,
+    dropped: usize,
+}
+
+impl Samples {
+    fn record(&mut self, program_counter: u32, frame_pointer: u32) {
+        let start = self.count * self.stride;
+        let Some(sample) = self.addresses.get_mut(start..start + self.stride) else {
+            self.dropped += 1;
+            return;
+        };
+
+        // the interrupt saves the address of the next instruction plus 4
+        sample[0] = program_counter.wrapping_sub(4);
+
+        let mut depth = 1;
+        backtrace::walk_frames(frame_pointer, self.stride - 1, |address| {
+            sample[depth] = address;
+            depth += 1;
+        });
+
+        self.count += 1;
+    }
+
+    fn stacks(&self) -> impl Iterator<Item = &[u32]> {
+        self.addresses[..self.count * self.stride]
+            .chunks(self.stride)
+            .map(|sample| {
+                let depth = sample
+                    .iter()
+                    .position(|&address| address == 0)
+                    .unwrap_or(sample.len());
+                &sample[..depth]
+            })
+    }
+}
+
+static SAMPLES: Mutex<RefCell<Option<Samples>>> = Mutex::new(RefCell::new(None));
+
+/// A running sampling profiler. See the [module level documentation](self).
+pub struct Profiler<'a> {
+    timer: &'a mut Timer,
+    _handler: InterruptHandler,
+    cycles_per_sample: u32,
+}
+
+impl<'a> Profiler<'a> {
+    /// Starts sampling every `period` cycles using `timer`. Up to `max_samples`
+    /// samples are kept, and after that any more are counted but thrown away.
+    /// Each sample has up to `unwind_depth` of the functions which led to the
+    /// code that was running, so 0 records only the function itself.
+    ///
+    /// # Panics
+    ///
+    /// If another profiler is already running.
+    pub fn start(
+        timer: &'a mut Timer,
+        period: u16,
+        max_samples: usize,
+        unwind_depth: usize,
+    ) -> Self {
+        let stride = unwind_depth + 1;
+
+        critical_section::with(|cs| {
+            let mut samples = SAMPLES.borrow_ref_mut(cs);
+            assert!(samples.is_none(), "only one profiler can run at a time");
+
+            *samples = Some(Samples {
+                stride,
+                addresses: alloc::vec![0; max_samples * stride],
+                count: 0,
+                dropped: 0,
+            });
+        });
+
+        let handler = unsafe {
+            add_interrupt_handler(timer.interrupt(), |cs| {
+                if let Some(samples) = SAMPLES.borrow_ref_mut(cs).as_mut() {
+                    samples.record(
+                        crate::program_counter_before_interrupt(),
+                        crate::frame_pointer_before_interrupt(),
+                    );
+                }
+            })
+        };
+
+        timer
+            .set_divider(Divider::Divider1)
+            .set_overflow_amount(period)
+            .set_interrupt(true)
+            .set_enabled(true);
+
+        Self {
+            timer,
+            _handler: handler,
+            cycles_per_sample: u32::from(period),
+        }
+    }
+
+    /// The number of samples recorded so far.
+    #[must_use]
+    pub fn sample_count(&self) -> usize {
+        critical_section::with(|cs| {
+            SAMPLES
+                .borrow_ref(cs)
+                .as_ref()
+                .map_or(0, |samples| samples.count)
+        })
+    }
+
+    /// Stops the profiler and collects the samples into a [`Profile`].
+    #[must_use]
+    pub fn stop(self) -> Profile {
+        let samples = critical_section::with(|cs| SAMPLES.borrow_ref_mut(cs).take())
+            .expect("the profiler's samples should exist while it is running");
+
+        let mut counts: HashMap<&[u32], usize> = HashMap::new();
+        for stack in samples.stacks() {
+            *counts.entry(stack).or_default() += 1;
+        }
+
+        let mut stacks: Vec<_> = counts
+            .into_iter()
+            .map(|(stack, count)| (Frames::new(stack.to_vec()), count))
+            .collect();
+        stacks.sort_by_key(|&(_, count)| core::cmp::Reverse(count));
+
+        Profile {
+            cycles_per_sample: self.cycles_per_sample,
+            samples: samples.count,
+            dropped: samples.dropped,
+            stacks,
+        }
+    }
+}
+
+impl Drop for Profiler<'_> {
+    fn drop(&mut self) {
+        self.timer.set_interrupt(false).set_enabled(false);
+        critical_section::with(|cs| SAMPLES.borrow_ref_mut(cs).take());
+    }
+}
+
+/// The samples taken by a [`Profiler`], with identical ones grouped together.
+pub struct Profile {
+    cycles_per_sample: u32,
+    samples: usize,
+    dropped: usize,
+    stacks: Vec<(Frames, usize)>,
+}
+
+impl Profile {
+    /// The number of samples that were kept.
+    #[must_use]
+    pub fn sample_count(&self) -> usize {
+        self.samples
+    }
+
+    /// The number of samples that were thrown away because there was no room
+    /// left to keep them.
+    #[must_use]
+    pub fn dropped_count(&self) -> usize {
+        self.dropped
+    }
+
+    /// Prints the profile to the mgba log in the format `agb-debug profile`
+    /// reads, with a header line followed by one line for each different stack
+    /// and the number of times it was sampled.
+    pub fn print(&self) {
+        crate::println!(
+            "agb-profile v1 cycles_per_sample={} samples={} dropped={}",
+            self.cycles_per_sample,
+            self.samples,
+            self.dropped
+        );
+
+        for (frames, count) in &self.stacks {
+            crate::println!("profile: {} {}", count, frames);
+        }
+
+        crate::println!("agb-profile end");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn samples_fill_up(_gba: &mut crate::Gba) {
+        let mut samples = Samples {
+            stride: 3,
+            addresses: alloc::vec![0; 4 * 3],
+            count: 0,
+            dropped: 0,
+        };
+
+        // no frame pointer, so only the program counter is recorded
+        for _ in 0..5 {
+            samples.record(0x0800_1004, 0);
+        }
+        samples.record(0x0800_2004, 0);
+
+        assert_eq!(samples.count, 4);
+        assert_eq!(samples.dropped, 2);
+        assert!(samples.stacks().all(|stack| stack == [0x0800_1000]));
+    }
+}
@@ -10,9 +10,11 @@ const CHANNEL_2_FREQUENCY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::ne
 const CHANNEL_4_LENGTH_ENVELOPE: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0078) };
 const CHANNEL_4_FREQUENCY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_007c) };
 
-const MASTER_SOUND_VOLUME_ENABLE: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0080) };
-const MASTER_SOUND_VOLUME_MIXING: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0082) };
-const MASTER_SOUND_STATUS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0084) };
+pub(crate) const MASTER_SOUND_VOLUME_ENABLE: MemoryMapped<u16> =
+    unsafe { MemoryMapped::new(0x0400_0080) };
+pub(crate) const MASTER_SOUND_VOLUME_MIXING: MemoryMapped<u16> =
+    unsafe { MemoryMapped::new(0x0400_0082) };
+pub(crate) const MASTER_SOUND_STATUS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0084) };
 
 #[non_exhaustive]
 pub struct Sound {}
@@ -1,9 +1,11 @@
 use agb_fixnum::Vector2D;
+use bitflags::bitflags;
 use core::arch::asm;
 use core::mem::MaybeUninit;
 
-use crate::display::affine::AffineMatrixBackground;
+use crate::display::affine::{AffineMatrixBackground, AffineMatrixObject};
 use crate::fixnum::Num;
+use crate::multiboot::MultibootParameters;
 
 #[allow(non_snake_case)]
 const fn swi_map(thumb_id: u32) -> u32 {
@@ -14,6 +16,31 @@ const fn swi_map(thumb_id: u32) -> u32 {
     }
 }
 
+bitflags! {
+    /// The memory and registers to clear with the RegisterRamReset syscall
+    /// when [soft resetting](crate::Gba::soft_reset).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RamResetFlags: u32 {
+        /// All of ewram, which can't be used by multiboot games as their code
+        /// is stored there
+        const EWRAM = 1 << 0;
+        /// All of iwram except for the last 512 bytes, which the BIOS uses
+        const IWRAM = 1 << 1;
+        /// Background and object palette memory
+        const PALETTE = 1 << 2;
+        /// Video memory
+        const VRAM = 1 << 3;
+        /// Object attribute memory
+        const OAM = 1 << 4;
+        /// The serial communication registers
+        const SERIAL_REGISTERS = 1 << 5;
+        /// The sound registers
+        const SOUND_REGISTERS = 1 << 6;
+        /// Every other register, such as the display control registers
+        const OTHER_REGISTERS = 1 << 7;
+    }
+}
+
 pub fn halt() {
     unsafe {
         asm!(
@@ -27,6 +54,10 @@ pub fn halt() {
     }
 }
 
+/// Stops the CPU, along with the display, sound and timers, until a keypad,
+/// game pak or serial interrupt happens. The display and sound have to be
+/// turned off first, which [`sleep_until_keys`](crate::input::sleep_until_keys)
+/// does for you.
 pub fn stop() {
     unsafe {
         asm!(
@@ -138,10 +169,44 @@ pub fn arc_tan2(x: i16, y: i32) -> i16 {
     result
 }
 
+/// Clears the memory in `clear` with RegisterRamReset and then restarts the
+/// game from its entrypoint with SoftReset. Both are called from the same asm
+/// block because clearing iwram also clears the stack.
+pub(crate) unsafe fn soft_reset(clear: RamResetFlags) -> ! {
+    asm!(
+        "swi {REGISTER_RAM_RESET}",
+        "swi {SOFT_RESET}",
+        REGISTER_RAM_RESET = const { swi_map(0x01) },
+        SOFT_RESET = const { swi_map(0x00) },
+        in("r0") clear.bits(),
+        options(noreturn)
+    );
+}
+
+/// Sends a multiboot image to other consoles over the link cable in
+/// multiplayer mode, once the handshake is done. Returns whether it succeeded.
+pub(crate) unsafe fn multi_boot(parameters: *const MultibootParameters) -> bool {
+    let result: u32;
+    asm!(
+        "swi {SWI}",
+        SWI = const { swi_map(0x25) },
+        inout("r0") parameters => result,
+        in("r1") 1,
+
+        clobber_abi("C")
+    );
+
+    result == 0
+}
+
+/// Calculates the matrix for an affine background with BgAffineSet, which
+/// scales and then rotates the background around `bg_center`, and puts that
+/// point at `display_center` on the screen.
+///
 /// `rotation` is in revolutions. It is hard to create the rotation, usually
 /// you'll go in from a larger sized type.
 #[must_use]
-pub(crate) fn bg_affine_matrix(
+pub fn bg_affine_matrix(
     bg_center: Vector2D<Num<i32, 8>>,
     display_center: Vector2D<i16>,
     scale: Vector2D<Num<i16, 8>>,
@@ -185,12 +250,880 @@ pub(crate) fn bg_affine_matrix(
     unsafe { output.assume_init() }
 }
 
+/// Calculates the matrix for an affine object with ObjAffineSet, which scales
+/// and then rotates the object. Only the top 8 bits of `rotation` are used.
+#[must_use]
+pub fn obj_affine_matrix(
+    scale: Vector2D<Num<i16, 8>>,
+    rotation: Num<u16, 16>,
+) -> AffineMatrixObject {
+    #[repr(C)]
+    struct Input {
+        scale_x: Num<i16, 8>,
+        scale_y: Num<i16, 8>,
+        rotation: Num<u16, 16>,
+        _padding: u16,
+    }
+
+    let input = Input {
+        scale_x: scale.x,
+        scale_y: scale.y,
+        rotation,
+        _padding: 0,
+    };
+
+    let mut output = MaybeUninit::<AffineMatrixObject>::uninit();
+
+    unsafe {
+        asm!(
+            "swi {SWI}",
+            SWI = const { swi_map(0x0F) },
+            in("r0") &input as *const Input,
+            in("r1") output.as_mut_ptr(),
+            in("r2") 1,
+            // the distance in bytes between each element of the output
+            in("r3") 2,
+
+            clobber_abi("C")
+        );
+    }
+
+    unsafe { output.assume_init() }
+}
+
+const CPU_SET_MAX_LENGTH: usize = (1 << 21) - 1;
+const CPU_SET_FILL: u32 = 1 << 24;
+const CPU_SET_32_BIT: u32 = 1 << 26;
+
+unsafe fn cpu_set(source: *const u8, dest: *mut u8, control: u32) {
+    asm!(
+        "swi {SWI}",
+        SWI = const { swi_map(0x0B) },
+        in("r0") source,
+        in("r1") dest,
+        in("r2") control,
+
+        clobber_abi("C")
+    );
+}
+
+unsafe fn cpu_fast_set(source: *const u32, dest: *mut u32, control: u32) {
+    asm!(
+        "swi {SWI}",
+        SWI = const { swi_map(0x0C) },
+        in("r0") source,
+        in("r1") dest,
+        in("r2") control,
+
+        clobber_abi("C")
+    );
+}
+
+fn assert_same_length(source_length: usize, dest_length: usize) {
+    assert_eq!(
+        source_length, dest_length,
+        "source and destination must be the same length"
+    );
+}
+
+fn cpu_set_length(length: usize) -> u32 {
+    assert!(
+        length <= CPU_SET_MAX_LENGTH,
+        "CpuSet can copy at most {CPU_SET_MAX_LENGTH} elements at once"
+    );
+
+    length as u32
+}
+
+fn cpu_fast_set_length(length: usize) -> u32 {
+    assert!(
+        length.is_multiple_of(8),
+        "CpuFastSet works in blocks of 8 words, but the length is {length}"
+    );
+    assert!(
+        length <= CPU_SET_MAX_LENGTH,
+        "CpuFastSet can copy at most {CPU_SET_MAX_LENGTH} words at once"
+    );
+
+    length as u32
+}
+
+/// Copies `source` into `dest` 16 bits at a time with CpuSet.
+///
+/// # Panics
+///
+/// If `source` and `dest` are different lengths.
+pub fn cpu_copy16(source: &[u16], dest: &mut [u16]) {
+    assert_same_length(source.len(), dest.len());
+    let length = cpu_set_length(dest.len());
+    unsafe { cpu_set(source.as_ptr().cast(), dest.as_mut_ptr().cast(), length) }
+}
+
+/// Fills `dest` with `value` 16 bits at a time with CpuSet.
+pub fn cpu_fill16(value: u16, dest: &mut [u16]) {
+    let length = cpu_set_length(dest.len());
+    unsafe {
+        cpu_set(
+            (&value as *const u16).cast(),
+            dest.as_mut_ptr().cast(),
+            length | CPU_SET_FILL,
+        );
+    }
+}
+
+/// Copies `source` into `dest` 32 bits at a time with CpuSet.
+///
+/// # Panics
+///
+/// If `source` and `dest` are different lengths.
+pub fn cpu_copy32(source: &[u32], dest: &mut [u32]) {
+    assert_same_length(source.len(), dest.len());
+    let length = cpu_set_length(dest.len());
+    unsafe {
+        cpu_set(
+            source.as_ptr().cast(),
+            dest.as_mut_ptr().cast(),
+            length | CPU_SET_32_BIT,
+        );
+    }
+}
+
+/// Fills `dest` with `value` 32 bits at a time with CpuSet.
+pub fn cpu_fill32(value: u32, dest: &mut [u32]) {
+    let length = cpu_set_length(dest.len());
+    unsafe {
+        cpu_set(
+            (&value as *const u32).cast(),
+            dest.as_mut_ptr().cast(),
+            length | CPU_SET_FILL | CPU_SET_32_BIT,
+        );
+    }
+}
+
+/// Copies `source` into `dest` 8 words at a time with CpuFastSet, which is
+/// faster than [`cpu_copy32`].
+///
+/// # Panics
+///
+/// If `source` and `dest` are different lengths, or their length isn't a
+/// multiple of 8.
+pub fn cpu_fast_copy(source: &[u32], dest: &mut [u32]) {
+    assert_same_length(source.len(), dest.len());
+    let length = cpu_fast_set_length(dest.len());
+
+    unsafe { cpu_fast_set(source.as_ptr(), dest.as_mut_ptr(), length) }
+}
+
+/// Fills `dest` with `value` 8 words at a time with CpuFastSet, which is faster
+/// than [`cpu_fill32`].
+///
+/// # Panics
+///
+/// If the length of `dest` isn't a multiple of 8.
+pub fn cpu_fast_fill(value: u32, dest: &mut [u32]) {
+    let length = cpu_fast_set_length(dest.len());
+
+    unsafe { cpu_fast_set(&value, dest.as_mut_ptr(), length | CPU_SET_FILL) }
+}
+
+/// Why compressed data couldn't be decompressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecompressionError {
+    /// The data isn't aligned to 4 bytes
+    Unaligned,
+    /// The header says the data is compressed in a different way
+    WrongCompressionType,
+    /// The output is too small to fit all of the decompressed data
+    OutputTooSmall,
+}
+
+/// Checks the header of `source` and returns the size of the decompressed
+/// data in bytes.
+fn decompressed_size(
+    source: &[u8],
+    compression: u8,
+    compression_mask: u8,
+    output_size: usize,
+    output_unit_size: usize,
+) -> Result<usize, DecompressionError> {
+    if !(source.as_ptr() as usize).is_multiple_of(4) {
+        return Err(DecompressionError::Unaligned);
+    }
+
+    let &[kind, size_low, size_middle, size_high] = source
+        .first_chunk()
+        .ok_or(DecompressionError::WrongCompressionType)?;
+
+    if kind & compression_mask != compression {
+        return Err(DecompressionError::WrongCompressionType);
+    }
+
+    let size = u32::from_le_bytes([size_low, size_middle, size_high, 0]) as usize;
+    // the BIOS writes whole units, so can go past the end of the size
+    if size.next_multiple_of(output_unit_size) > output_size {
+        return Err(DecompressionError::OutputTooSmall);
+    }
+
+    Ok(size)
+}
+
+macro_rules! decompress {
+    ($swi: literal, $source: expr, $output: expr) => {
+        unsafe {
+            asm!(
+                "swi {SWI}",
+                SWI = const { swi_map($swi) },
+                in("r0") $source.as_ptr(),
+                in("r1") $output.as_mut_ptr(),
+
+                clobber_abi("C")
+            );
+        }
+    };
+}
+
+/// Decompresses LZ77 compressed data with LZ77UnCompReadNormalWrite8bit, and
+/// returns the size of the decompressed data in bytes. This writes a byte at a
+/// time, so it can't be used to decompress straight into video memory.
+///
+/// The header is checked to make sure the output is big enough, but the rest of
+/// the data is trusted. Invalid data can cause the BIOS to read past the end of
+/// `source`, but it never writes past the end of `output`.
+pub fn lz77_decompress(source: &[u8], output: &mut [u8]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x10, 0xf0, output.len(), 1)?;
+    decompress!(0x11, source, output);
+    Ok(size)
+}
+
+/// Decompresses LZ77 compressed data with LZ77UnCompReadNormalWrite16bit, and
+/// returns the size of the decompressed data in bytes. This writes 16 bits at
+/// a time, so it can be used to decompress straight into video memory. Data
+/// which copies from the byte just before it can't be decompressed this way.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn lz77_decompress16(source: &[u8], output: &mut [u16]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x10, 0xf0, size_of_val(output), 2)?;
+    decompress!(0x12, source, output);
+    Ok(size)
+}
+
+/// Decompresses Huffman compressed data with HuffUnCompReadNormal, and returns
+/// the size of the decompressed data in bytes. This writes 32 bits at a time.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn huffman_decompress(source: &[u8], output: &mut [u32]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x20, 0xf0, size_of_val(output), 4)?;
+    decompress!(0x13, source, output);
+    Ok(size)
+}
+
+/// Decompresses run length encoded data with RLUnCompReadNormalWrite8bit, and
+/// returns the size of the decompressed data in bytes. This writes a byte at a
+/// time, so it can't be used to decompress straight into video memory.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn run_length_decompress(
+    source: &[u8],
+    output: &mut [u8],
+) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x30, 0xf0, output.len(), 1)?;
+    decompress!(0x14, source, output);
+    Ok(size)
+}
+
+/// Decompresses run length encoded data with RLUnCompReadNormalWrite16bit, and
+/// returns the size of the decompressed data in bytes. This writes 16 bits at a
+/// time, so it can be used to decompress straight into video memory.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn run_length_decompress16(
+    source: &[u8],
+    output: &mut [u16],
+) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x30, 0xf0, size_of_val(output), 2)?;
+    decompress!(0x15, source, output);
+    Ok(size)
+}
+
+/// Undoes a filter where each byte is stored as the difference from the one
+/// before it with Diff8bitUnFilterWrite8bit, and returns the size of the
+/// output in bytes. This writes a byte at a time, so it can't be used to write
+/// straight into video memory.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn diff8_unfilter(source: &[u8], output: &mut [u8]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x81, 0xff, output.len(), 1)?;
+    decompress!(0x16, source, output);
+    Ok(size)
+}
+
+/// Undoes a filter where each byte is stored as the difference from the one
+/// before it with Diff8bitUnFilterWrite16bit, and returns the size of the
+/// output in bytes. This writes 16 bits at a time, so it can be used to write
+/// straight into video memory.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn diff8_unfilter16(source: &[u8], output: &mut [u16]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x81, 0xff, size_of_val(output), 2)?;
+    decompress!(0x17, source, output);
+    Ok(size)
+}
+
+/// Undoes a filter where each 16 bit value is stored as the difference from
+/// the one before it with Diff16bitUnFilter, and returns the size of the
+/// output in bytes.
+///
+/// See [`lz77_decompress`] for what is checked.
+pub fn diff16_unfilter(source: &[u8], output: &mut [u16]) -> Result<usize, DecompressionError> {
+    let size = decompressed_size(source, 0x82, 0xff, size_of_val(output), 2)?;
+    decompress!(0x18, source, output);
+    Ok(size)
+}
+
+/// Unpacks `source`, which is made of `source_bits` bit values, into `output`
+/// as `output_bits` bit values with BitUnPack. The values are packed starting
+/// from the least significant bits.
+///
+/// `offset` is added to every value which isn't zero, or to every value if
+/// `offset_zero` is true. This is useful for moving 1 bit per pixel fonts to a
+/// different palette colour.
+///
+/// # Panics
+///
+/// If `source_bits` isn't 1, 2, 4 or 8, `output_bits` isn't 1, 2, 4, 8, 16 or
+/// 32, `source` is longer than 65535 bytes, `offset` doesn't fit in 31 bits,
+/// or `output` is too small to fit the unpacked values.
+pub fn bit_unpack(
+    source: &[u8],
+    source_bits: u8,
+    output: &mut [u32],
+    output_bits: u8,
+    offset: u32,
+    offset_zero: bool,
+) {
+    #[repr(C)]
+    struct UnpackInfo {
+        source_length: u16,
+        source_bits: u8,
+        output_bits: u8,
+        offset: u32,
+    }
+
+    assert!(
+        matches!(source_bits, 1 | 2 | 4 | 8),
+        "can't unpack {source_bits} bit values"
+    );
+    assert!(
+        matches!(output_bits, 1 | 2 | 4 | 8 | 16 | 32),
+        "can't unpack to {output_bits} bit values"
+    );
+    assert!(offset < 1 << 31, "the offset must fit in 31 bits");
+
+    let source_length = u16::try_from(source.len()).expect("source is too long to unpack");
+    let values = source.len() * 8 / usize::from(source_bits);
+    assert!(
+        (values * usize::from(output_bits)).div_ceil(32) <= output.len(),
+        "output is too small to unpack into"
+    );
+
+    let info = UnpackInfo {
+        source_length,
+        source_bits,
+        output_bits,
+        offset: offset | (u32::from(offset_zero) << 31),
+    };
+
+    unsafe {
+        asm!(
+            "swi {SWI}",
+            SWI = const { swi_map(0x10) },
+            in("r0") source.as_ptr(),
+            in("r1") output.as_mut_ptr(),
+            in("r2") &info as *const UnpackInfo,
+
+            clobber_abi("C")
+        );
+    }
+}
+
+/// Gradually moves the sound bias to 0x200 with SoundBias if `raise` is true, or
+/// down to 0 if it isn't. Moving it slowly avoids the click you would hear from
+/// changing it all at once when turning sound on or off.
+pub fn sound_bias(raise: bool) {
+    unsafe {
+        asm!(
+            "swi {SWI}",
+            SWI = const { swi_map(0x19) },
+            in("r0") u32::from(raise),
+
+            clobber_abi("C")
+        );
+    }
+}
+
+/// The checksum of the BIOS from GetBiosChecksum, which is `0xBAAE187F` on a
+/// GBA and `0xBAAE1880` on a DS.
+#[must_use]
+pub fn bios_checksum() -> u32 {
+    let result: u32;
+    unsafe {
+        asm!(
+            "swi {SWI}",
+            SWI = const { swi_map(0x0D) },
+            lateout("r0") result,
+
+            clobber_abi("C")
+        );
+    }
+    result
+}
+
+/// The frequency to play a sample recorded at `sample_frequency` so that it
+/// sounds like the MIDI note `key` with MidiKey2Freq. `key` 180 plays the
+/// sample at its own frequency, and every 12 below that is an octave lower.
+/// `fine_adjust` adds that many 256ths of a semitone.
+#[must_use]
+pub fn midi_key_to_frequency(sample_frequency: u32, key: u8, fine_adjust: u8) -> u32 {
+    // the start of the WaveData structure, the BIOS only reads the frequency
+    #[repr(C)]
+    struct WaveData {
+        kind: u16,
+        status: u16,
+        frequency: u32,
+    }
+
+    let wave_data = WaveData {
+        kind: 0,
+        status: 0,
+        frequency: sample_frequency,
+    };
+
+    let result: u32;
+    unsafe {
+        asm!(
+            "swi {SWI}",
+            SWI = const { swi_map(0x1F) },
+            inout("r0") &wave_data as *const WaveData => result,
+            in("r1") u32::from(key),
+            in("r2") u32::from(fine_adjust),
+
+            clobber_abi("C")
+        );
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::display::affine::AffineMatrix;
+    use alloc::{vec, vec::Vec};
+
+    use crate::{display::affine::AffineMatrix, memory_mapped::MemoryMapped};
 
     use super::*;
 
+    /// Sample data with runs, repeats and noise in it
+    fn test_data(length: usize) -> Vec<u8> {
+        (0..length)
+            .map(|i| match i % 96 {
+                0..32 => 7,
+                32..64 => (i % 5) as u8,
+                _ => (i * 37 % 251) as u8,
+            })
+            .collect()
+    }
+
+    /// Adds the header and pads to a whole number of words, so the data is
+    /// aligned like the BIOS needs
+    fn compressed(kind: u8, size: usize, data: &[u8]) -> Vec<u32> {
+        let mut bytes = vec![kind, size as u8, (size >> 8) as u8, (size >> 16) as u8];
+        bytes.extend_from_slice(data);
+
+        bytes
+            .chunks(4)
+            .map(|word| {
+                let mut padded = [0; 4];
+                padded[..word.len()].copy_from_slice(word);
+                u32::from_le_bytes(padded)
+            })
+            .collect()
+    }
+
+    fn as_bytes(words: &[u32]) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(words.as_ptr().cast(), size_of_val(words)) }
+    }
+
+    fn as_bytes_16(halfwords: &[u16]) -> Vec<u8> {
+        halfwords.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    fn lz77_compress(data: &[u8]) -> Vec<u32> {
+        let mut output = Vec::new();
+        let mut i = 0;
+
+        while i < data.len() {
+            let flags = output.len();
+            output.push(0);
+
+            for bit in (0..8).rev() {
+                if i >= data.len() {
+                    break;
+                }
+
+                // a distance of at least 2 so it works when writing 16 bits at a time
+                let (length, distance) = (2..=i.min(4096))
+                    .map(|distance| {
+                        let length = (0..18)
+                            .take_while(|&k| {
+                                i + k < data.len() && data[i + k - distance] == data[i + k]
+                            })
+                            .count();
+                        (length, distance)
+                    })
+                    .max_by_key(|&(length, _)| length)
+                    .unwrap_or((0, 0));
+
+                if length >= 3 {
+                    let displacement = distance - 1;
+                    output[flags] |= 1 << bit;
+                    output.push((((length - 3) << 4) | (displacement >> 8)) as u8);
+                    output.push(displacement as u8);
+                    i += length;
+                } else {
+                    output.push(data[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        compressed(0x10, data.len(), &output)
+    }
+
+    fn run_length_compress(data: &[u8]) -> Vec<u32> {
+        let starts_run =
+            |i: usize| i + 2 < data.len() && data[i] == data[i + 1] && data[i] == data[i + 2];
+
+        let mut output = Vec::new();
+        let mut i = 0;
+
+        while i < data.len() {
+            if starts_run(i) {
+                let length = data[i..]
+                    .iter()
+                    .take(130)
+                    .take_while(|&&byte| byte == data[i])
+                    .count();
+                output.push(0x80 | (length - 3) as u8);
+                output.push(data[i]);
+                i += length;
+            } else {
+                let start = i;
+                while i < data.len() && i - start < 128 && !starts_run(i) {
+                    i += 1;
+                }
+
+                output.push((i - start - 1) as u8);
+                output.extend_from_slice(&data[start..i]);
+            }
+        }
+
+        compressed(0x30, data.len(), &output)
+    }
+
+    fn diff8_filter(data: &[u8]) -> Vec<u32> {
+        let differences: Vec<u8> = (0..data.len())
+            .map(|i| data[i].wrapping_sub(if i == 0 { 0 } else { data[i - 1] }))
+            .collect();
+
+        compressed(0x81, data.len(), &differences)
+    }
+
+    #[test_case]
+    fn cpu_set_copies_and_fills(_gba: &mut crate::Gba) {
+        let source16: Vec<u16> = (0..37).map(|i| i * 1000).collect();
+        let mut dest16 = vec![0; 37];
+        cpu_copy16(&source16, &mut dest16);
+        assert_eq!(dest16, source16);
+
+        cpu_fill16(0xabcd, &mut dest16[3..10]);
+        assert!(dest16[3..10].iter().all(|&x| x == 0xabcd));
+        assert_eq!(dest16[10], source16[10]);
+
+        let source32: Vec<u32> = (0..40).map(|i| i * 0x0101_0101).collect();
+        let mut dest32 = [0; 40];
+        cpu_copy32(&source32[..37], &mut dest32[..37]);
+        assert_eq!(dest32[..37], source32[..37]);
+        assert_eq!(dest32[37..], [0; 3]);
+
+        cpu_fill32(0x1234_5678, &mut dest32[..5]);
+        assert_eq!(dest32[..5], [0x1234_5678; 5]);
+
+        cpu_fast_copy(&source32[..32], &mut dest32[..32]);
+        assert_eq!(dest32[..32], source32[..32]);
+
+        cpu_fast_fill(0xdead_beef, &mut dest32[8..24]);
+        assert_eq!(dest32[8..24], [0xdead_beef; 16]);
+        assert_eq!(dest32[24], source32[24]);
+    }
+
+    #[test_case]
+    fn affine_obj(_gba: &mut crate::Gba) {
+        let scale: Vector2D<Num<i16, 8>> = (Num::new(3) / 2, Num::new(3) / 4).into();
+
+        for step in 0..16 {
+            let rotation = Num::<u16, 16>::from_raw(step << 12);
+            let matrix = obj_affine_matrix(scale, rotation);
+
+            let angle = Num::<i32, 8>::new(step.into()) / 16;
+            let (cos, sin) = (angle.cos(), angle.sin());
+            let (scale_x, scale_y) = (scale.x.change_base(), scale.y.change_base());
+            let expected = [scale_x * cos, -scale_x * sin, scale_y * sin, scale_y * cos];
+
+            for (actual, expected) in [matrix.a, matrix.b, matrix.c, matrix.d]
+                .into_iter()
+                .zip(expected)
+            {
+                let error = (i32::from(actual.to_raw()) - expected.to_raw()).abs();
+                assert!(
+                    error <= 4,
+                    "{actual:?} should be close to {expected:?} at step {step}"
+                );
+            }
+        }
+    }
+
+    #[test_case]
+    fn lz77(_gba: &mut crate::Gba) {
+        let data = test_data(500);
+        let source = lz77_compress(&data);
+
+        let mut output = vec![0; 500];
+        assert_eq!(lz77_decompress(as_bytes(&source), &mut output), Ok(500));
+        assert_eq!(output, data);
+
+        let mut output16 = vec![0u16; 250];
+        assert_eq!(lz77_decompress16(as_bytes(&source), &mut output16), Ok(500));
+        assert_eq!(as_bytes_16(&output16), data);
+    }
+
+    #[test_case]
+    fn run_length(_gba: &mut crate::Gba) {
+        let data = test_data(500);
+        let source = run_length_compress(&data);
+
+        let mut output = vec![0; 500];
+        assert_eq!(
+            run_length_decompress(as_bytes(&source), &mut output),
+            Ok(500)
+        );
+        assert_eq!(output, data);
+
+        let mut output16 = vec![0u16; 250];
+        assert_eq!(
+            run_length_decompress16(as_bytes(&source), &mut output16),
+            Ok(500)
+        );
+        assert_eq!(as_bytes_16(&output16), data);
+    }
+
+    #[test_case]
+    fn diff_unfilter(_gba: &mut crate::Gba) {
+        let data = test_data(200);
+        let source = diff8_filter(&data);
+
+        let mut output = vec![0; 200];
+        assert_eq!(diff8_unfilter(as_bytes(&source), &mut output), Ok(200));
+        assert_eq!(output, data);
+
+        let mut output16 = vec![0u16; 100];
+        assert_eq!(diff8_unfilter16(as_bytes(&source), &mut output16), Ok(200));
+        assert_eq!(as_bytes_16(&output16), data);
+
+        let data16: Vec<u16> = (0..100u16).map(|i| i.wrapping_mul(7919)).collect();
+        let differences: Vec<u8> = (0..data16.len())
+            .map(|i| data16[i].wrapping_sub(if i == 0 { 0 } else { data16[i - 1] }))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let source16 = compressed(0x82, 200, &differences);
+
+        let mut output16 = vec![0u16; 100];
+        assert_eq!(diff16_unfilter(as_bytes(&source16), &mut output16), Ok(200));
+        assert_eq!(output16, data16);
+    }
+
+    #[test_case]
+    fn huffman(_gba: &mut crate::Gba) {
+        const SYMBOLS: [u8; 4] = *b"agb!";
+
+        let data: Vec<u8> = (0..64).map(|i| SYMBOLS[i * 7 % 11 % 4]).collect();
+
+        // a tree where every symbol has a 2 bit code, which is its index
+        let mut source = vec![3, 0x00, 0xc0, 0xc1];
+        source.extend_from_slice(&SYMBOLS);
+
+        let mut bits = [0u32; 4];
+        for (i, byte) in data.iter().enumerate() {
+            let code = SYMBOLS.iter().position(|symbol| symbol == byte).unwrap() as u32;
+            bits[i / 16] |= code << (30 - (i % 16) * 2);
+        }
+        source.extend(bits.iter().flat_map(|word| word.to_le_bytes()));
+
+        let source = compressed(0x28, data.len(), &source);
+
+        let mut output = vec![0u32; 16];
+        assert_eq!(huffman_decompress(as_bytes(&source), &mut output), Ok(64));
+        assert_eq!(as_bytes(&output), data);
+    }
+
+    #[test_case]
+    fn decompression_errors(_gba: &mut crate::Gba) {
+        let source = lz77_compress(&test_data(100));
+        let mut output = vec![0; 99];
+
+        assert_eq!(
+            lz77_decompress(as_bytes(&source), &mut output),
+            Err(DecompressionError::OutputTooSmall)
+        );
+        assert_eq!(
+            run_length_decompress(as_bytes(&source), &mut output),
+            Err(DecompressionError::WrongCompressionType)
+        );
+        assert_eq!(
+            lz77_decompress(&as_bytes(&source)[1..], &mut output),
+            Err(DecompressionError::Unaligned)
+        );
+    }
+
+    #[test_case]
+    fn bit_unpacking(_gba: &mut crate::Gba) {
+        fn software_unpack(
+            source: &[u8],
+            source_bits: u32,
+            output_bits: u32,
+            offset: u32,
+            offset_zero: bool,
+        ) -> Vec<u32> {
+            let values = source.iter().flat_map(|&byte| {
+                (0..8 / source_bits)
+                    .map(move |i| (u32::from(byte) >> (i * source_bits)) & ((1 << source_bits) - 1))
+            });
+
+            let mut output = vec![
+                0;
+                (source.len() * 8 / source_bits as usize * output_bits as usize)
+                    .div_ceil(32)
+            ];
+            for (i, value) in values.enumerate() {
+                let value = if value != 0 || offset_zero {
+                    value + offset
+                } else {
+                    value
+                };
+                let bit = i * output_bits as usize;
+                output[bit / 32] |= value << (bit % 32);
+            }
+
+            output
+        }
+
+        let source = [0b1011_0001, 0x0f, 0x5a, 0xc3];
+
+        for (source_bits, output_bits, offset, offset_zero) in [
+            (1, 4, 2, false),
+            (2, 8, 1, true),
+            (4, 16, 0, false),
+            (8, 32, 100, false),
+        ] {
+            let expected = software_unpack(&source, source_bits, output_bits, offset, offset_zero);
+            let mut output = vec![0; expected.len()];
+
+            bit_unpack(
+                &source,
+                source_bits as u8,
+                &mut output,
+                output_bits as u8,
+                offset,
+                offset_zero,
+            );
+            assert_eq!(
+                output, expected,
+                "unpacking {source_bits} bits to {output_bits}"
+            );
+        }
+    }
+
+    #[test_case]
+    fn bios_checksum_is_for_a_gba(_gba: &mut crate::Gba) {
+        assert_eq!(bios_checksum(), 0xBAAE_187F);
+    }
+
+    #[test_case]
+    fn sound_bias_moves_the_bias_level(_gba: &mut crate::Gba) {
+        const SOUND_BIAS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0088) };
+        const BIAS_LEVEL: u16 = 0x3fe;
+
+        let original = SOUND_BIAS.get();
+
+        sound_bias(false);
+        assert_eq!(SOUND_BIAS.get() & BIAS_LEVEL, 0);
+
+        sound_bias(true);
+        assert_eq!(SOUND_BIAS.get() & BIAS_LEVEL, 0x200);
+        // the amplitude resolution isn't changed
+        assert_eq!(SOUND_BIAS.get() & !BIAS_LEVEL, original & !BIAS_LEVEL);
+
+        sound_bias(false);
+        assert_eq!(SOUND_BIAS.get() & BIAS_LEVEL, 0);
+
+        SOUND_BIAS.set(original);
+    }
+
+    /// `sample_frequency * 2 ^ ((key - 180 + fine_adjust / 256) / 12)`, worked
+    /// out with floating point
+    fn software_midi_key_to_frequency(sample_frequency: u32, key: u8, fine_adjust: u8) -> u32 {
+        const STEPS_PER_OCTAVE: i32 = 12 * 256;
+
+        let steps = (i32::from(key) - 180) * 256 + i32::from(fine_adjust);
+        let octaves = steps.div_euclid(STEPS_PER_OCTAVE);
+        let part_octave =
+            f64::from(steps.rem_euclid(STEPS_PER_OCTAVE)) / f64::from(STEPS_PER_OCTAVE);
+
+        // 2 ^ part_octave = e ^ (part_octave * ln 2), from its Taylor series
+        let x = part_octave * core::f64::consts::LN_2;
+        let mut term = 1.0;
+        let mut power = 1.0;
+        for n in 1..20 {
+            term *= x / f64::from(n);
+            power += term;
+        }
+
+        let frequency = f64::from(sample_frequency) * power;
+        let frequency = if octaves < 0 {
+            frequency / f64::from(1 << -octaves)
+        } else {
+            frequency * f64::from(1 << octaves)
+        };
+
+        frequency as u32
+    }
+
+    #[test_case]
+    fn midi_keys(_gba: &mut crate::Gba) {
+        let frequency = 44_100 << 10;
+
+        assert_eq!(midi_key_to_frequency(frequency, 180, 0), frequency);
+
+        for key in (120..180).step_by(7) {
+            for fine_adjust in [0, 1, 64, 128, 255] {
+                let expected = software_midi_key_to_frequency(frequency, key, fine_adjust);
+                let actual = midi_key_to_frequency(frequency, key, fine_adjust);
+                assert!(
+                    actual.abs_diff(expected) <= expected / 1000,
+                    "key {key} with {fine_adjust}: {actual} should be close to {expected}"
+                );
+            }
+        }
+    }
+
     #[test_case]
     fn affine_bg(_gba: &mut crate::Gba) {
         // expect the identity matrix
@@ -10,6 +10,13 @@ const fn timer_control(timer: usize) -> MemoryMapped<u16> {
     unsafe { MemoryMapped::new(0x0400_0102 + 4 * timer) }
 }
 
+/// Stops every timer, including the ones used by the mixer.
+pub(crate) fn stop_all_timers() {
+    for timer in 0..4 {
+        timer_control(timer).set(0);
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Divider {
     // 16.78MHz or 59.59ns
@@ -28,10 +28,96 @@ fn my_logger(category: &str, level: LogLevel, s: String) {
 #[derive(Parser)]
 struct CliArguments {
     rom: PathBuf,
+
+    /// A recording made by agb's `InputRecorder` to play back from when the
+    /// game starts, either as the raw bytes or as the log its `print` wrote
+    #[arg(long)]
+    input: Option<PathBuf>,
+}
+
+const CYCLES_PER_FRAME: u64 = 280_896;
+/// The cycle within each frame that the vblank starts on, after the 160 visible lines
+const VBLANK_CYCLE: u64 = 160 * 1232;
+
+/// Plays back a recording one frame at a time. `InputRecorder` records frame `n`
+/// as the one after the `n`th vblank since the game started, so each frame is
+/// pressed from its vblank until the next one.
+struct InputReplay {
+    runs: VecDeque<(u32, u16)>,
+    next_vblank: u64,
+}
+
+impl InputReplay {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::read(path)?;
+        let bytes = match std::str::from_utf8(&file) {
+            Ok(text) if text.contains(INPUT_RECORDING_PREFIX) => decode_logged_recording(text)?,
+            _ => file,
+        };
+
+        let runs = bytes
+            .chunks_exact(2)
+            .map(|run| {
+                let encoded = u16::from_le_bytes([run[0], run[1]]);
+                (u32::from(encoded & 0x3ff), (encoded >> 10) + 1)
+            })
+            .collect();
+
+        Ok(Self {
+            runs,
+            next_vblank: 0,
+        })
+    }
+
+    /// The buttons to press if a new frame has started, which are released once
+    /// the recording has finished
+    fn buttons_for_cycle(&mut self, cycle: u64) -> Option<u32> {
+        let vblanks = (cycle + CYCLES_PER_FRAME - VBLANK_CYCLE) / CYCLES_PER_FRAME;
+        if vblanks < self.next_vblank {
+            return None;
+        }
+        self.next_vblank += 1;
+
+        let Some((buttons, remaining)) = self.runs.front_mut() else {
+            return Some(0);
+        };
+        let buttons = *buttons;
+
+        *remaining -= 1;
+        if *remaining == 0 {
+            self.runs.pop_front();
+        }
+
+        Some(buttons)
+    }
+}
+
+const INPUT_RECORDING_PREFIX: &str = "input-recording: ";
+
+fn decode_logged_recording(log: &str) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    for line in log.lines() {
+        let Some(start) = line.find(INPUT_RECORDING_PREFIX) else {
+            continue;
+        };
+
+        let hex = line[start + INPUT_RECORDING_PREFIX.len()..].trim();
+        for i in (0..hex.len()).step_by(2) {
+            let byte = hex
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid input recording line {}", line))?;
+            bytes.push(byte);
+        }
+    }
+
+    Ok(bytes)
 }
 
 struct TestRunner {
     mgba: MCore,
+    input: Option<InputReplay>,
 }
 
 enum Timer {
@@ -40,14 +126,14 @@ enum Timer {
 }
 
 impl TestRunner {
-    fn new<V: VFile>(rom: V) -> Result<Self, Box<dyn Error>> {
+    fn new<V: VFile>(rom: V, input: Option<InputReplay>) -> Result<Self, Box<dyn Error>> {
         let mut mgba = MCore::new().ok_or(anyhow!("cannot create core"))?;
 
         mgba::set_global_default_logger(&LOGGER);
 
         mgba.load_rom(rom);
 
-        Ok(Self { mgba })
+        Ok(Self { mgba, input })
     }
 
     fn run(mut self) -> Result<(), Box<dyn Error>> {
@@ -57,6 +143,11 @@ impl TestRunner {
         let mut mark_this_test_as_soft_failed = false;
         loop {
             self.mgba.step();
+            if let Some(input) = &mut self.input {
+                if let Some(buttons) = input.buttons_for_cycle(self.mgba.current_cycle()) {
+                    self.mgba.set_keys(buttons);
+                }
+            }
             while let Some((category, level, message)) = LOGGER_BUFFER.lock().unwrap().pop_front() {
                 match (category.as_ref(), level, message.as_ref()) {
                     (_, LogLevel::Fatal, fatal_message) => {
@@ -135,7 +226,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     let rom = load_rom(args.rom)?;
     let rom = MemoryBacked::new(rom);
 
-    TestRunner::new(rom)?.run()?;
+    let input = args
+        .input
+        .map(|path| {
+            InputReplay::load(&path)
+                .with_context(|| format!("Could not load input recording {}", path.display()))
+        })
+        .transpose()?;
+
+    TestRunner::new(rom, input)?.run()?;
 
     Ok(())
 }
@@ -19,6 +19,7 @@ use agb::{
         },
         palette16::Palette16,
         tiled::VRAM_MANAGER,
+        Rgb15,
     },
     fixnum::{num, Num, Vector2D},
     include_aseprite,
@@ -392,7 +393,7 @@ pub fn main(mut gba: agb::Gba) -> ! {
 
     let _background = gba.display.video.tiled();
 
-    VRAM_MANAGER.set_background_palettes(&[Palette16::new([u16::MAX; 16])]);
+    VRAM_MANAGER.set_background_palettes(&[Palette16::new([Rgb15::WHITE; 16])]);
 
     let vblank = agb::interrupt::VBlank::get();
 
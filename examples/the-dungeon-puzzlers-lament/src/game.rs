@@ -6,7 +6,7 @@ use agb::{
         },
         palette16::Palette16,
         tiled::{BackgroundIterator, RegularBackgroundSize, RegularBackgroundTiles, TileFormat},
-        Priority, HEIGHT,
+        Priority, Rgb15, HEIGHT,
     },
     fixnum::Vector2D,
     input::{Button, ButtonController, Tri},
@@ -42,8 +42,8 @@ struct Lament {
 }
 
 fn generate_text_palette() -> PaletteVram {
-    let mut palette = [0x0; 16];
-    palette[1] = 0xFF_FF;
+    let mut palette = [Rgb15::BLACK; 16];
+    palette[1] = Rgb15::WHITE;
     let palette = Palette16::new(palette);
     PaletteVram::new(&palette).unwrap()
 }
@@ -19,7 +19,7 @@ use agb::{
             InfiniteScrolledMap, RegularBackgroundSize, RegularBackgroundTiles, TileFormat,
             VRAM_MANAGER,
         },
-        Priority, HEIGHT, WIDTH,
+        Priority, Rgb15, HEIGHT, WIDTH,
     },
     fixnum::{num, FixedNum, Rect, Vector2D},
     input::{Button, ButtonController, Tri},
@@ -2123,8 +2123,8 @@ impl<'a> Game<'a> {
         let a = modified_palette.colour(0);
         let b = modified_palette.colour(1);
 
-        modified_palette.update_colour(0, interpolate_colour(a, 17982, time, 120));
-        modified_palette.update_colour(1, interpolate_colour(b, 22427, time, 120));
+        modified_palette.update_colour(0, interpolate_colour(a, Rgb15(17982), time, 120));
+        modified_palette.update_colour(1, interpolate_colour(b, Rgb15(22427), time, 120));
 
         let modified_palettes = [modified_palette];
 
@@ -2136,9 +2136,11 @@ impl<'a> Game<'a> {
 
         let c = modified_palette.colour(2);
 
-        modified_palette.update_colour(0, interpolate_colour(17982, 0x7FFF, time, 600));
-        modified_palette.update_colour(1, interpolate_colour(22427, 0x7FFF, time, 600));
-        modified_palette.update_colour(2, interpolate_colour(c, 0x7FFF, time, 600));
+        modified_palette
+            .update_colour(0, interpolate_colour(Rgb15(17982), Rgb15::WHITE, time, 600));
+        modified_palette
+            .update_colour(1, interpolate_colour(Rgb15(22427), Rgb15::WHITE, time, 600));
+        modified_palette.update_colour(2, interpolate_colour(c, Rgb15::WHITE, time, 600));
 
         let modified_palettes = [modified_palette];
 
@@ -2265,23 +2267,14 @@ fn ping_pong(i: u16, n: u16) -> u16 {
     }
 }
 
-fn interpolate_colour(initial: u16, destination: u16, time_so_far: u16, total_time: u16) -> u16 {
-    const MASK: u16 = 0b11111;
-    fn to_components(c: u16) -> [u16; 3] {
-        [c & MASK, (c >> 5) & MASK, (c >> 10) & MASK]
-    }
-
-    let initial_rgb = to_components(initial);
-    let destination_rgb = to_components(destination);
-    let mut colour = 0;
-
-    for (i, c) in initial_rgb
-        .iter()
-        .zip(destination_rgb)
-        .map(|(a, b)| (b - a) * time_so_far / total_time + a)
-        .enumerate()
-    {
-        colour |= (c & MASK) << (i * 5);
-    }
-    colour
+fn interpolate_colour(
+    initial: Rgb15,
+    destination: Rgb15,
+    time_so_far: u16,
+    total_time: u16,
+) -> Rgb15 {
+    initial.lerp(
+        destination,
+        Number::new(i32::from(time_so_far)) / i32::from(total_time),
+    )
 }